@@ -0,0 +1,284 @@
+//! Native command-line interface, for running the bot outside of Cloudflare Workers.
+//!
+//! String bindings are read from environment variables first, and then from an optional JSON
+//! config file (an object mapping binding names like `DISCOURSE_API_KEY` to strings). State is
+//! kept in a local JSON file.
+
+use std::{collections::HashMap, path::PathBuf, process::ExitCode};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::{
+    config::Config,
+    env::{EnvExt, StringBindingKind},
+    github::Tag,
+    logging::Logger,
+    network::ReqwestTransport,
+    platform::Platform,
+    state::{FileStorage, StateController, Storage},
+    utils, Mode, StateChange,
+};
+
+const DEFAULT_STATE_PATH: &str = "state.json";
+
+const USAGE: &str = "\
+usage: signalupdates-bot [--config <path>] [--state <path>] <command>
+
+commands:
+    run                           check all enabled platforms and post if possible
+    firebase <version>            tell the bot the latest Android version available in Firebase
+    preview <platform> <old> <new>
+                                  print the post that would be made, without posting it
+    coverage <platform> <tag> [<offset>]
+                                  print how much of each language is translated at the tag,
+                                  starting from the language at the offset
+    state show                    print the current state
+    state history                 print the recent changes of the state
+    state rollback <platform> <id>
+                                  restore the state of a platform before the given history entry
+    state clear-pending <platform>
+                                  forget about a post that is waiting for approval
+    state mark-posted <platform> <tag>
+                                  consider the tag already posted
+    state reset-archiving-message <platform>
+                                  post the archiving message to the previous release's topic again
+
+options:
+    --config <path>    JSON file with string bindings (environment variables take precedence)
+    --state <path>     JSON file to keep the state in (default: state.json)";
+
+/// Platforms are given by their state keys, which are looked up once the configuration is loaded.
+enum Command {
+    Run,
+    Firebase {
+        version: String,
+    },
+    Preview {
+        platform: String,
+        old_tag: Tag,
+        new_tag: Tag,
+    },
+    Coverage {
+        platform: String,
+        tag: Tag,
+        offset: usize,
+    },
+    ShowState,
+    ShowHistory,
+    ChangeState {
+        platform: String,
+        state_change: StateChange,
+    },
+}
+
+struct CliEnv {
+    bindings: HashMap<String, String>,
+    state_path: PathBuf,
+}
+
+impl EnvExt for CliEnv {
+    fn string_binding(&self, kind: StringBindingKind, name: &str) -> anyhow::Result<String> {
+        std::env::var(name)
+            .ok()
+            .or_else(|| self.bindings.get(name).cloned())
+            .ok_or_else(|| anyhow!("couldn't get string binding kind = {kind:?}, name = {name}"))
+    }
+
+    fn state_storage(&self) -> anyhow::Result<Box<dyn Storage>> {
+        Ok(Box::new(FileStorage::new(&self.state_path)))
+    }
+}
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (env, command) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("error: {error:#}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match utils::block_on(run(&env, command)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(env: &CliEnv, command: Command) -> anyhow::Result<()> {
+    let transport = ReqwestTransport::new()?;
+    let config = Config::from_env(env)
+        .context("invalid configuration")?
+        .leak();
+
+    match command {
+        Command::Run => crate::main(env, &transport, Mode::MakeNewPostIfPossible).await,
+        Command::Firebase { version } => {
+            crate::main(
+                env,
+                &transport,
+                Mode::EditExistingAndroidPostIfNeeded {
+                    latest_available: Tag::from_exact_version_string(&version),
+                },
+            )
+            .await
+        }
+        Command::Preview {
+            platform,
+            old_tag,
+            new_tag,
+        } => {
+            let _logger = Logger::new();
+
+            let platform = find_platform(config, &platform)?;
+            let preview =
+                crate::preview_post(env, &transport, platform, &old_tag, &new_tag).await?;
+            println!("{}", preview.markdown);
+
+            Ok(())
+        }
+        Command::Coverage {
+            platform,
+            tag,
+            offset,
+        } => {
+            let _logger = Logger::new();
+
+            let platform = find_platform(config, &platform)?;
+            let coverage =
+                crate::translation_coverage(env, &transport, platform, &tag, offset).await?;
+
+            let text =
+                serde_json::to_string_pretty(&coverage).context("could not serialize coverage")?;
+            println!("{text}");
+
+            Ok(())
+        }
+        Command::ShowState => {
+            let state_controller = StateController::from_env(env, config).await?;
+
+            let text = serde_json::to_string_pretty(state_controller.document())
+                .context("could not serialize state")?;
+            println!("{text}");
+
+            Ok(())
+        }
+        Command::ShowHistory => {
+            let state_controller = StateController::from_env(env, config).await?;
+
+            let text = serde_json::to_string_pretty(state_controller.history())
+                .context("could not serialize history")?;
+            println!("{text}");
+
+            Ok(())
+        }
+        Command::ChangeState {
+            platform,
+            state_change,
+        } => {
+            let _logger = Logger::new();
+
+            let platform = find_platform(config, &platform)?;
+            let platform_state = crate::change_state(env, platform, state_change).await?;
+
+            let text = serde_json::to_string_pretty(&platform_state)
+                .context("could not serialize state")?;
+            println!("{text}");
+
+            Ok(())
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> anyhow::Result<(CliEnv, Command)> {
+    let mut config_path = None;
+    let mut state_path = PathBuf::from(DEFAULT_STATE_PATH);
+    let mut positional = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                config_path = Some(args.next().context("missing value for --config")?);
+            }
+            "--state" => {
+                state_path = args.next().context("missing value for --state")?.into();
+            }
+            "-h" | "--help" => bail!("help requested"),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let command = match positional.as_slice() {
+        ["run"] => Command::Run,
+        ["firebase", version] => Command::Firebase {
+            version: version.to_string(),
+        },
+        ["preview", platform, old, new] => Command::Preview {
+            platform: platform.to_lowercase(),
+            old_tag: Tag::new(*old),
+            new_tag: Tag::new(*new),
+        },
+        ["coverage", platform, tag] => Command::Coverage {
+            platform: platform.to_lowercase(),
+            tag: Tag::new(*tag),
+            offset: 0,
+        },
+        ["coverage", platform, tag, offset] => Command::Coverage {
+            platform: platform.to_lowercase(),
+            tag: Tag::new(*tag),
+            offset: offset.parse().context("invalid offset")?,
+        },
+        ["state", "show"] => Command::ShowState,
+        ["state", "history"] => Command::ShowHistory,
+        ["state", "rollback", platform, entry_id] => Command::ChangeState {
+            platform: platform.to_lowercase(),
+            state_change: StateChange::RollBack {
+                entry_id: entry_id.parse().context("invalid history entry ID")?,
+            },
+        },
+        ["state", "clear-pending", platform] => Command::ChangeState {
+            platform: platform.to_lowercase(),
+            state_change: StateChange::ClearPendingState,
+        },
+        ["state", "mark-posted", platform, tag] => Command::ChangeState {
+            platform: platform.to_lowercase(),
+            state_change: StateChange::MarkAsPosted {
+                tag: Tag::new(*tag),
+            },
+        },
+        ["state", "reset-archiving-message", platform] => Command::ChangeState {
+            platform: platform.to_lowercase(),
+            state_change: StateChange::ResetPostedArchivingMessage,
+        },
+        [] => bail!("no command given"),
+        _ => bail!("unknown command: {}", positional.join(" ")),
+    };
+
+    let bindings = match config_path {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("could not read config file {path}"))?;
+
+            serde_json::from_str(&text)
+                .with_context(|| format!("could not parse config file {path}"))?
+        }
+        None => HashMap::new(),
+    };
+
+    Ok((
+        CliEnv {
+            bindings,
+            state_path,
+        },
+        command,
+    ))
+}
+
+fn find_platform(config: &'static Config, key: &str) -> anyhow::Result<Platform> {
+    Platform::from_state_key(config, key).ok_or_else(|| anyhow!("unknown platform: {key}"))
+}
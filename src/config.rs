@@ -0,0 +1,259 @@
+use std::{cell::RefCell, collections::HashSet};
+
+use anyhow::{bail, Context};
+use worker::Url;
+
+use crate::{env::EnvExt, platform::PlatformDefinition};
+
+thread_local! {
+    static LEAKED: RefCell<Vec<&'static Config>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Where the bot posts to and which repositories it watches.
+///
+/// Each run loads its configuration with [`Config::from_env`] and gets its platforms with
+/// [`crate::platform::Platform::iter`], which refer back to the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub discourse_base_url: String,
+    pub github_base_url: String,
+    pub github_api_base_url: String,
+    pub github_raw_base_url: String,
+    /// Built-in definitions come first, in order, so that [`crate::platform::builtin`] platforms
+    /// refer to them.
+    pub platforms: Vec<PlatformDefinition>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            discourse_base_url: String::from("https://community.signalusers.org"),
+            github_base_url: String::from("https://github.com"),
+            github_api_base_url: String::from("https://api.github.com"),
+            github_raw_base_url: String::from("https://raw.githubusercontent.com"),
+            platforms: PlatformDefinition::builtin(),
+        }
+    }
+}
+
+impl Config {
+    /// Uses default values for anything that isn't configured.
+    pub fn from_env(env: &dyn EnvExt) -> anyhow::Result<Self> {
+        let default = Self::default();
+
+        let base_url = |value: Option<String>, default: String| match value {
+            Some(value) => normalize_base_url(&value),
+            None => Ok(default),
+        };
+
+        let mut platforms = PlatformDefinition::builtin();
+        let mut keys = HashSet::new();
+
+        for definition in env.platform_definitions()? {
+            if !keys.insert(definition.key.clone()) {
+                bail!(
+                    "platform is defined more than once in PLATFORMS: {}",
+                    definition.key
+                );
+            }
+
+            match platforms
+                .iter_mut()
+                .find(|builtin| builtin.key == definition.key)
+            {
+                Some(builtin) => *builtin = definition,
+                None => platforms.push(definition),
+            }
+        }
+
+        for (key, repository) in env.github_repositories()? {
+            platforms
+                .iter_mut()
+                .find(|definition| definition.key == key)
+                .with_context(|| format!("unknown platform in GITHUB_REPOSITORIES: {key}"))?
+                .repository = repository;
+        }
+
+        for definition in &platforms {
+            definition
+                .validate()
+                .context("invalid platform definition")?;
+        }
+
+        Ok(Self {
+            discourse_base_url: base_url(env.discourse_base_url(), default.discourse_base_url)
+                .context("invalid DISCOURSE_BASE_URL")?,
+            github_base_url: base_url(env.github_base_url(), default.github_base_url)
+                .context("invalid GITHUB_BASE_URL")?,
+            github_api_base_url: base_url(env.github_api_base_url(), default.github_api_base_url)
+                .context("invalid GITHUB_API_BASE_URL")?,
+            github_raw_base_url: base_url(env.github_raw_base_url(), default.github_raw_base_url)
+                .context("invalid GITHUB_RAW_BASE_URL")?,
+            platforms,
+        })
+    }
+
+    /// Keeps the configuration for as long as the worker runs, so that platforms can refer to it.
+    /// An equal configuration that was kept before is reused instead, so each run doesn't keep
+    /// another copy.
+    pub fn leak(self) -> &'static Self {
+        LEAKED.with(|leaked| {
+            let mut leaked = leaked.borrow_mut();
+
+            match leaked.iter().find(|config| ***config == self) {
+                Some(config) => *config,
+                None => {
+                    let config: &'static Self = Box::leak(Box::new(self));
+                    leaked.push(config);
+                    config
+                }
+            }
+        })
+    }
+
+    pub fn discourse_url(&self, path: &str) -> String {
+        format!("{}/{path}", self.discourse_base_url)
+    }
+
+    /// Links to GitHub in posts omit the protocol unless `specify_protocol` is set.
+    pub fn github_url(&self, path: &str, specify_protocol: bool) -> String {
+        let url = format!("{}/{path}", self.github_base_url);
+
+        match (specify_protocol, url.find("//")) {
+            (false, Some(index)) => url[index..].to_string(),
+            _ => url,
+        }
+    }
+
+    /// The host of [`Config::github_api_base_url`].
+    pub fn github_api_domain(&self) -> Option<String> {
+        Url::parse(&self.github_api_base_url)
+            .ok()?
+            .host_str()
+            .map(String::from)
+    }
+}
+
+fn normalize_base_url(value: &str) -> anyhow::Result<String> {
+    let url = Url::parse(value).context("could not parse URL")?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("URL should use http or https: {value}");
+    }
+
+    Ok(value.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    use super::*;
+    use crate::{
+        env::TestEnv,
+        platform::{builtin::*, Platform, TopicStrategy},
+    };
+
+    #[test]
+    fn from_env() {
+        let env = TestEnv::new(Default::default())
+            .with_binding("DISCOURSE_BASE_URL", "http://localhost:8080/")
+            .with_binding("GITHUB_API_BASE_URL", "")
+            .with_binding(
+                "GITHUB_REPOSITORIES",
+                r#"{ "android": "me/Signal-Android" }"#,
+            );
+
+        let config = Config::from_env(&env).unwrap();
+
+        assert_eq!(config.discourse_base_url, "http://localhost:8080");
+        assert_eq!(config.github_api_base_url, "https://api.github.com");
+        assert_eq!(config.platforms[0].repository, "me/Signal-Android");
+        assert_eq!(config.platforms[1].repository, "signalapp/Signal-iOS");
+    }
+
+    #[test]
+    fn platforms() {
+        let env = TestEnv::new(Default::default()).with_binding(
+            "PLATFORMS",
+            r#"[
+                {
+                    "key": "server",
+                    "name": "Server",
+                    "repository": "me/Signal-Server",
+                    "topic": { "kind": "fixed_topic", "topic_id_variable": "SERVER_TOPIC_ID" },
+                    "color": 1
+                },
+                {
+                    "key": "ringrtc",
+                    "name": "RingRTC",
+                    "repository": "signalapp/ringrtc",
+                    "topic": { "kind": "beta_feedback_topics" },
+                    "color": 2
+                }
+            ]"#,
+        );
+
+        let config = Config::from_env(&env).unwrap().leak();
+        let server = Server.with_config(config);
+
+        assert_eq!(Platform::iter(config).count(), 6);
+        assert_eq!(server.repo_slug(), "me/Signal-Server");
+        assert_eq!(server.color(), 1);
+        assert_eq!(
+            server.topic_strategy(),
+            TopicStrategy::FixedTopic {
+                topic_id_variable: String::from("SERVER_TOPIC_ID")
+            }
+        );
+        assert_eq!(
+            Platform::from_state_key(config, "ringrtc")
+                .unwrap()
+                .to_string(),
+            "RingRTC"
+        );
+        assert_eq!(
+            Android.with_config(config).repo_slug(),
+            "signalapp/Signal-Android"
+        );
+        assert_eq!(Server.repo_slug(), "signalapp/Signal-Server");
+    }
+
+    #[test_case("DISCOURSE_BASE_URL", "community.signalusers.org"; "url without scheme")]
+    #[test_case("GITHUB_BASE_URL", "ftp://github.com"; "url with other scheme")]
+    #[test_case("GITHUB_REPOSITORIES", r#"{ "android": "Signal-Android" }"#; "repository without owner")]
+    #[test_case("GITHUB_REPOSITORIES", r#"{ "windows": "me/Signal-Windows" }"#; "unknown platform")]
+    #[test_case("PLATFORMS", r#"[{ "key": "ios" }]"#; "incomplete platform")]
+    #[test_case("PLATFORMS", r#"[
+        { "key": "x", "name": "X", "repository": "me/x", "topic": { "kind": "beta_feedback_topics" }, "color": 1 },
+        { "key": "x", "name": "X", "repository": "me/x", "topic": { "kind": "beta_feedback_topics" }, "color": 1 }
+    ]"#; "duplicate platform")]
+    fn invalid(name: &str, value: &str) {
+        let env = TestEnv::new(Default::default()).with_binding(name, value);
+        assert!(Config::from_env(&env).is_err());
+    }
+
+    #[test_case(false, "//github.com/signalapp/Signal-iOS")]
+    #[test_case(true, "https://github.com/signalapp/Signal-iOS")]
+    fn github_url(specify_protocol: bool, url: &str) {
+        assert_eq!(
+            Config::default().github_url("signalapp/Signal-iOS", specify_protocol),
+            url
+        );
+    }
+
+    #[test]
+    fn leak() {
+        let config = Config {
+            discourse_base_url: String::from("http://localhost"),
+            ..Default::default()
+        };
+
+        let leaked = config.clone().leak();
+
+        assert_eq!(*leaked, config);
+        assert!(std::ptr::eq(leaked, config.leak()));
+        assert!(!std::ptr::eq(leaked, Config::default().leak()));
+    }
+}
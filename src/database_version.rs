@@ -0,0 +1,265 @@
+use std::fmt;
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+    platform::Platform,
+};
+
+lazy_static! {
+    static ref DATABASE_VERSION_CONSTANT_REGEX: Regex =
+        Regex::new(r"const val DATABASE_VERSION\s*=\s*(\d+)").unwrap();
+    static ref GRDB_SCHEMA_VERSION_REGEX: Regex =
+        Regex::new(r"grdbSchemaVersionLatest\s*:\s*UInt\s*=\s*(\d+)").unwrap();
+    static ref SCHEMA_VERSION_MIGRATION_REGEX: Regex =
+        Regex::new(r"updateToSchemaVersion(\d+)").unwrap();
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseVersionSourceKind {
+    /// Like `const val DATABASE_VERSION = 212` in Kotlin.
+    DatabaseVersionConstant,
+    /// Like `grdbSchemaVersionLatest: UInt = 104` in Swift.
+    GrdbSchemaVersion,
+    /// Imports of migrations like `updateToSchemaVersion1010` in TypeScript.
+    SchemaVersionMigrations,
+}
+
+/// A file in a platform's repository that the local database schema version can be read from (see
+/// [`crate::platform::PlatformDefinition::database_version_source`]).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseVersionSource {
+    pub path: String,
+    pub kind: DatabaseVersionSourceKind,
+}
+
+impl DatabaseVersionSource {
+    pub fn new(path: &str, kind: DatabaseVersionSourceKind) -> Self {
+        Self {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    pub fn for_platform(platform: Platform) -> Option<&'static Self> {
+        platform.definition().database_version_source.as_ref()
+    }
+
+    /// The highest version mentioned in `text`, so that for migrations it's the highest one.
+    pub fn parse(&self, text: &str) -> anyhow::Result<u64> {
+        let regex: &Regex = match self.kind {
+            DatabaseVersionSourceKind::DatabaseVersionConstant => &DATABASE_VERSION_CONSTANT_REGEX,
+            DatabaseVersionSourceKind::GrdbSchemaVersion => &GRDB_SCHEMA_VERSION_REGEX,
+            DatabaseVersionSourceKind::SchemaVersionMigrations => &SCHEMA_VERSION_MIGRATION_REGEX,
+        };
+
+        regex
+            .captures_iter(text)
+            .map(|captures| str::parse(&captures[1]).context("couldn't parse as u64"))
+            .collect::<anyhow::Result<Vec<u64>>>()?
+            .into_iter()
+            .max()
+            .with_context(|| format!("couldn't find database version in {}", self.path))
+    }
+}
+
+/// The local database schema version changing between two tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatabaseVersionChange {
+    pub old_version: u64,
+    pub new_version: u64,
+}
+
+impl DatabaseVersionChange {
+    /// Upper bound of requests [`Self::get`] makes.
+    pub fn estimated_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        match DatabaseVersionSource::for_platform(platform) {
+            Some(source) if comparison.might_have_changed(&[&source.path]) => 2,
+            _ => 0,
+        }
+    }
+
+    /// Returns `None` if the version didn't change, or if the platform has no
+    /// [`DatabaseVersionSource`] or it doesn't exist at either of the tags.
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        comparison: &Comparison,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(source) = DatabaseVersionSource::for_platform(platform) else {
+            return Ok(None);
+        };
+
+        if !comparison.might_have_changed(&[&source.path]) {
+            tracing::trace!("database version source didn't change, skipping");
+            return Ok(None);
+        }
+
+        let (Some(old_version), Some(new_version)) = (
+            Self::get_version(transport, platform, old_tag, source).await?,
+            Self::get_version(transport, platform, new_tag, source).await?,
+        ) else {
+            return Ok(None);
+        };
+
+        tracing::debug!(old_version, new_version);
+
+        Ok((old_version != new_version).then_some(Self {
+            old_version,
+            new_version,
+        }))
+    }
+
+    async fn get_version(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        source: &DatabaseVersionSource,
+    ) -> anyhow::Result<Option<u64>> {
+        github::get_file_content_if_exists(transport, platform, &tag.name, &source.path)
+            .await
+            .with_context(|| format!("couldn't get {} at {}", source.path, tag.name))?
+            .map(|text| source.parse(&text))
+            .transpose()
+    }
+
+    pub fn is_upgrade(&self) -> bool {
+        self.new_version > self.old_version
+    }
+}
+
+/// Like `210 → 212 (downgrade not possible)`.
+impl fmt::Display for DatabaseVersionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} → {}", self.old_version, self.new_version)?;
+
+        if self.is_upgrade() {
+            write!(f, " (downgrade not possible)")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    fn comparison(files: &[&str]) -> Comparison {
+        serde_json::from_value(json!({
+            "total_commits": 0,
+            "commits": [],
+            "files": files.iter().map(|file| json!({ "filename": file })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test_case(Android, "
+object SignalDatabaseMigrations {
+  val TAG: String = Log.tag(SignalDatabaseMigrations.javaClass)
+
+  const val DATABASE_VERSION = 212
+", 212; "Android")]
+    #[test_case(Ios, "
+    public static let grdbSchemaVersionDefault: UInt = 0
+    public static let grdbSchemaVersionLatest: UInt = 104
+", 104; "iOS")]
+    #[test_case(Desktop, "
+import updateToSchemaVersion1000 from './1000-mark-unread-call-history-messages-as-unseen';
+import updateToSchemaVersion1010 from './1010-call-links-table';
+import updateToSchemaVersion920 from './920-clean-more-keys';
+", 1010; "Desktop")]
+    fn parse(platform: Platform, text: &str, result: u64) {
+        let source = DatabaseVersionSource::for_platform(platform).unwrap();
+        assert_eq!(source.parse(text).unwrap(), result);
+    }
+
+    #[test]
+    fn parse_without_version() {
+        let source = DatabaseVersionSource::for_platform(Android).unwrap();
+        assert!(source.parse("object SignalDatabaseMigrations {}").is_err());
+    }
+
+    #[test_case(210, 212, "210 → 212 (downgrade not possible)"; "upgrade")]
+    #[test_case(212, 210, "212 → 210"; "downgrade")]
+    fn display(old_version: u64, new_version: u64, result: &str) {
+        let change = DatabaseVersionChange {
+            old_version,
+            new_version,
+        };
+
+        assert_eq!(change.to_string(), result);
+    }
+
+    #[test]
+    fn get() {
+        let path = &DatabaseVersionSource::for_platform(Android).unwrap().path;
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &format!(
+                    "https://raw.githubusercontent.com/signalapp/Signal-Android/v7.0.0/{path}"
+                ),
+                Response::new(200, String::from("const val DATABASE_VERSION = 210")),
+            )
+            .with_response(
+                Method::Get,
+                &format!(
+                    "https://raw.githubusercontent.com/signalapp/Signal-Android/v7.0.1/{path}"
+                ),
+                Response::new(200, String::from("const val DATABASE_VERSION = 212")),
+            );
+
+        let change = utils::block_on(DatabaseVersionChange::get(
+            &transport,
+            Android,
+            &Tag::new("v7.0.0"),
+            &Tag::new("v7.0.1"),
+            &comparison(&[path]),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            change,
+            Some(DatabaseVersionChange {
+                old_version: 210,
+                new_version: 212,
+            })
+        );
+    }
+
+    #[test]
+    fn get_skips_unchanged_source() {
+        let transport = FakeTransport::new();
+
+        let change = utils::block_on(DatabaseVersionChange::get(
+            &transport,
+            Desktop,
+            &Tag::new("v1.2.0"),
+            &Tag::new("v1.3.0"),
+            &comparison(&["ts/main.ts"]),
+        ))
+        .unwrap();
+
+        assert_eq!(change, None);
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Package names mapped to their versions. Versions may be empty if a manifest doesn't specify
+/// them, like for Gradle libraries whose versions come from a BOM.
+pub type Packages = BTreeMap<String, String>;
+
+lazy_static! {
+    static ref GRADLE_INLINE_TABLE_ENTRY_REGEX: Regex =
+        Regex::new(r#"([\w.\-]+)\s*=\s*"([^"]*)""#).unwrap();
+    static ref PODFILE_LOCK_POD_REGEX: Regex =
+        Regex::new(r#"^  - "?([^\s"(]+) \(([^)]+)\)"?:?$"#).unwrap();
+    static ref POM_PROPERTIES_REGEX: Regex =
+        Regex::new(r"(?s)<properties>(.*?)</properties>").unwrap();
+    static ref POM_PROPERTY_REGEX: Regex = Regex::new(r"<([\w.\-]+)>([^<]*)</([\w.\-]+)>").unwrap();
+    static ref POM_DEPENDENCY_REGEX: Regex =
+        Regex::new(r"(?s)<dependency>(.*?)</dependency>").unwrap();
+    static ref POM_GROUP_ID_REGEX: Regex = Regex::new(r"<groupId>([^<]+)</groupId>").unwrap();
+    static ref POM_ARTIFACT_ID_REGEX: Regex =
+        Regex::new(r"<artifactId>([^<]+)</artifactId>").unwrap();
+    static ref POM_VERSION_REGEX: Regex = Regex::new(r"<version>([^<]+)</version>").unwrap();
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestKind {
+    GradleVersionCatalog,
+    PackageJson,
+    PodfileLock,
+    PackageResolved,
+    PomXml,
+}
+
+use ManifestKind::*;
+
+/// A file in a platform's repository that lists the packages it depends on (see
+/// [`crate::platform::PlatformDefinition::manifests`]).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub path: String,
+    pub kind: ManifestKind,
+}
+
+impl Manifest {
+    pub fn new(path: &str, kind: ManifestKind) -> Self {
+        Self {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    pub fn parse(&self, text: &str) -> anyhow::Result<Packages> {
+        match self.kind {
+            GradleVersionCatalog => Ok(parse_gradle_version_catalog(text)),
+            PackageJson => parse_package_json(text),
+            PodfileLock => Ok(parse_podfile_lock(text)),
+            PackageResolved => parse_package_resolved(text),
+            PomXml => Ok(parse_pom_xml(text)),
+        }
+        .with_context(|| format!("couldn't parse {}", self.path))
+    }
+}
+
+/// Supports the subset of TOML used in version catalogs: `[versions]`, `[libraries]` and
+/// `[plugins]` tables with string values or single-line inline tables.
+fn parse_gradle_version_catalog(text: &str) -> Packages {
+    let mut table = "";
+    let mut versions = HashMap::new();
+    let mut entries = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            table = name.trim_end_matches(']').trim();
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        match table {
+            "versions" => {
+                if let Some(version) = value.trim().split('"').nth(1) {
+                    versions.insert(key.trim().to_string(), version.to_string());
+                }
+            }
+            "libraries" | "plugins" => entries.push(value.trim()),
+            _ => {}
+        }
+    }
+
+    let mut packages = Packages::new();
+
+    for value in entries {
+        if let Some(notation) = value.strip_prefix('"') {
+            let notation = notation.trim_end_matches('"');
+
+            match notation.matches(':').count() {
+                // `group:name:version` for libraries, `id:version` for plugins
+                2 | 1 => {
+                    let (name, version) = notation.rsplit_once(':').unwrap();
+                    packages.insert(name.to_string(), version.to_string());
+                }
+                _ => {
+                    packages.insert(notation.to_string(), String::new());
+                }
+            }
+
+            continue;
+        }
+
+        let fields: HashMap<_, _> = GRADLE_INLINE_TABLE_ENTRY_REGEX
+            .captures_iter(value)
+            .map(|captures| {
+                (
+                    captures.get(1).unwrap().as_str(),
+                    captures.get(2).unwrap().as_str(),
+                )
+            })
+            .collect();
+
+        let name = match (
+            fields.get("module"),
+            fields.get("group"),
+            fields.get("name"),
+            fields.get("id"),
+        ) {
+            (Some(module), ..) => module.to_string(),
+            (None, Some(group), Some(name), _) => format!("{group}:{name}"),
+            (None, _, _, Some(id)) => id.to_string(),
+            _ => continue,
+        };
+
+        let version = match (fields.get("version"), fields.get("version.ref")) {
+            (Some(version), _) => version.to_string(),
+            (None, Some(reference)) => versions.get(*reference).cloned().unwrap_or_default(),
+            (None, None) => String::new(),
+        };
+
+        packages.insert(name, version);
+    }
+
+    packages
+}
+
+fn parse_package_json(text: &str) -> anyhow::Result<Packages> {
+    let value: Value = serde_json::from_str(text).context("couldn't parse JSON")?;
+
+    Ok(["dependencies", "devDependencies", "optionalDependencies"]
+        .iter()
+        .filter_map(|key| value.get(key).and_then(Value::as_object))
+        .flatten()
+        .map(|(name, version)| {
+            (
+                name.clone(),
+                version.as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect())
+}
+
+/// Only takes top-level pods from the `PODS` section into account.
+fn parse_podfile_lock(text: &str) -> Packages {
+    text.lines()
+        .skip_while(|line| *line != "PODS:")
+        .skip(1)
+        .take_while(|line| line.starts_with(' '))
+        .filter_map(|line| PODFILE_LOCK_POD_REGEX.captures(line))
+        .map(|captures| (captures[1].to_string(), captures[2].to_string()))
+        .collect()
+}
+
+/// Supports both the version 1 format and later ones.
+fn parse_package_resolved(text: &str) -> anyhow::Result<Packages> {
+    let value: Value = serde_json::from_str(text).context("couldn't parse JSON")?;
+
+    let pins = value
+        .get("pins")
+        .or_else(|| value.get("object").and_then(|object| object.get("pins")))
+        .and_then(Value::as_array)
+        .context("no pins")?;
+
+    Ok(pins
+        .iter()
+        .filter_map(|pin| {
+            let name = pin
+                .get("identity")
+                .or_else(|| pin.get("package"))
+                .and_then(Value::as_str)?;
+
+            let state = pin.get("state");
+            let field = |key| {
+                state
+                    .and_then(|state| state.get(key))
+                    .and_then(Value::as_str)
+            };
+
+            let version = match (field("version"), field("revision")) {
+                (Some(version), _) => version.to_string(),
+                (None, Some(revision)) => revision.chars().take(7).collect(),
+                (None, None) => String::new(),
+            };
+
+            Some((name.to_string(), version))
+        })
+        .collect())
+}
+
+/// Resolves `${property}` versions using the `<properties>` of the same file.
+fn parse_pom_xml(text: &str) -> Packages {
+    let properties: HashMap<_, _> = POM_PROPERTIES_REGEX
+        .captures(text)
+        .map(|captures| {
+            POM_PROPERTY_REGEX
+                .captures_iter(captures.get(1).unwrap().as_str())
+                .filter(|property| property[1] == property[3])
+                .map(|property| (property[1].to_string(), property[2].trim().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut packages = Packages::new();
+
+    for captures in POM_DEPENDENCY_REGEX.captures_iter(text) {
+        let block = captures.get(1).unwrap().as_str();
+        let field = |regex: &Regex| {
+            regex
+                .captures(block)
+                .map(|captures| captures[1].trim().to_string())
+        };
+
+        let (Some(group_id), Some(artifact_id)) =
+            (field(&POM_GROUP_ID_REGEX), field(&POM_ARTIFACT_ID_REGEX))
+        else {
+            continue;
+        };
+
+        let version = field(&POM_VERSION_REGEX)
+            .map(|version| {
+                version
+                    .strip_prefix("${")
+                    .and_then(|property| property.strip_suffix('}'))
+                    .and_then(|property| properties.get(property).cloned())
+                    .unwrap_or(version)
+            })
+            .unwrap_or_default();
+
+        let entry = packages
+            .entry(format!("{group_id}:{artifact_id}"))
+            .or_default();
+        if entry.is_empty() {
+            *entry = version;
+        }
+    }
+
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    use super::*;
+
+    fn packages(pairs: &[(&str, &str)]) -> Packages {
+        pairs
+            .iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect()
+    }
+
+    #[test_case(GradleVersionCatalog, r#"
+[versions]
+kotlin = "1.9.20"
+libsignal-client = "0.52.3" # comment
+
+[libraries]
+kotlin-stdlib = { module = "org.jetbrains.kotlin:kotlin-stdlib-jdk8", version.ref = "kotlin" }
+libsignal-android = { group = "org.signal", name = "libsignal-android", version.ref = "libsignal-client" }
+ringrtc = "org.signal:ringrtc-android:2.44.0"
+androidx-compose-ui = { module = "androidx.compose.ui:ui" }
+
+[plugins]
+kotlin-android = { id = "org.jetbrains.kotlin.android", version.ref = "kotlin" }
+"#, &[
+        ("androidx.compose.ui:ui", ""),
+        ("org.jetbrains.kotlin.android", "1.9.20"),
+        ("org.jetbrains.kotlin:kotlin-stdlib-jdk8", "1.9.20"),
+        ("org.signal:libsignal-android", "0.52.3"),
+        ("org.signal:ringrtc-android", "2.44.0"),
+    ]; "Gradle version catalog")]
+    #[test_case(PackageJson, r#"{
+  "name": "signal-desktop",
+  "version": "7.0.0",
+  "dependencies": { "@signalapp/ringrtc": "2.44.0" },
+  "devDependencies": { "typescript": "^5.0.0" }
+}"#, &[("@signalapp/ringrtc", "2.44.0"), ("typescript", "^5.0.0")]; "package.json")]
+    #[test_case(PodfileLock, r#"PODS:
+  - blurhash (0.0.1)
+  - "GRDB.swift/SQLCipher (6.24.1)":
+    - SQLCipher (>= 4.0.1)
+  - LibSignalClient (0.52.3):
+    - SignalCoreKit
+
+DEPENDENCIES:
+  - blurhash (from `https://github.com/signalapp/blurhash`)
+"#, &[
+        ("GRDB.swift/SQLCipher", "6.24.1"),
+        ("LibSignalClient", "0.52.3"),
+        ("blurhash", "0.0.1"),
+    ]; "Podfile.lock")]
+    #[test_case(PackageResolved, r#"{
+  "pins": [
+    { "identity": "swift-collections", "state": { "revision": "abcdef1234", "version": "1.0.6" } },
+    { "identity": "libwebp", "state": { "revision": "1234567890abcdef" } }
+  ],
+  "version": 2
+}"#, &[("libwebp", "1234567"), ("swift-collections", "1.0.6")]; "Package.resolved")]
+    #[test_case(PackageResolved, r#"{
+  "object": {
+    "pins": [{ "package": "SwiftCollections", "state": { "version": "1.0.6" } }]
+  },
+  "version": 1
+}"#, &[("SwiftCollections", "1.0.6")]; "Package.resolved version 1")]
+    #[test_case(PomXml, r#"<project>
+  <properties>
+    <jackson.version>2.15.2</jackson.version>
+  </properties>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>com.fasterxml.jackson</groupId>
+        <artifactId>jackson-bom</artifactId>
+        <version>${jackson.version}</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+  <dependencies>
+    <dependency>
+      <groupId>com.fasterxml.jackson</groupId>
+      <artifactId>jackson-bom</artifactId>
+    </dependency>
+    <dependency>
+      <groupId>org.signal</groupId>
+      <artifactId>libsignal-server</artifactId>
+      <version>0.52.3</version>
+    </dependency>
+  </dependencies>
+</project>"#, &[
+        ("com.fasterxml.jackson:jackson-bom", "2.15.2"),
+        ("org.signal:libsignal-server", "0.52.3"),
+    ]; "pom.xml")]
+    fn parse(kind: ManifestKind, text: &str, result: &[(&str, &str)]) {
+        let manifest = Manifest::new("manifest", kind);
+
+        assert_eq!(manifest.parse(text).unwrap(), packages(result));
+    }
+
+    #[test]
+    fn invalid() {
+        let manifest = Manifest::new("package.json", PackageJson);
+
+        assert!(manifest.parse("404: Not Found").is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use anyhow::Context;
+
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+    platform::Platform,
+    utils,
+};
+
+mod manifest;
+mod native;
+mod package_change;
+
+pub use manifest::*;
+pub use native::*;
+pub use package_change::*;
+
+/// How the packages in a single manifest changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestChanges {
+    pub manifest: Manifest,
+    pub changes: Vec<PackageChange>,
+}
+
+/// How the dependencies of a platform changed between two tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyChanges {
+    pub manifests: Vec<ManifestChanges>,
+    pub native_updates: Vec<DependencyUpdate>,
+}
+
+impl DependencyChanges {
+    /// Upper bound of requests [`Self::get`] makes.
+    pub fn estimated_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        let manifests = platform
+            .definition()
+            .manifests
+            .iter()
+            .filter(|manifest| comparison.might_have_changed(&[&manifest.path]))
+            .count();
+
+        2 * manifests as u64
+    }
+
+    /// Only fetches manifests that `comparison` says changed, unless its files are likely
+    /// incomplete.
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        comparison: &Comparison,
+    ) -> anyhow::Result<Self> {
+        let mut manifests = Vec::new();
+
+        for manifest in &platform.definition().manifests {
+            if !comparison.might_have_changed(&[&manifest.path]) {
+                tracing::trace!(manifest.path, "manifest didn't change, skipping");
+                continue;
+            }
+
+            let old = Self::get_packages(transport, platform, old_tag, manifest).await?;
+            let new = Self::get_packages(transport, platform, new_tag, manifest).await?;
+
+            let changes = PackageChange::between(&old, &new);
+            tracing::debug!(manifest.path, changes.len = changes.len());
+
+            if !changes.is_empty() {
+                manifests.push(ManifestChanges {
+                    manifest: manifest.clone(),
+                    changes,
+                });
+            }
+        }
+
+        let native_updates = DependencyUpdate::from_package_changes(
+            platform,
+            manifests
+                .iter()
+                .flat_map(|manifest| manifest.changes.iter()),
+        );
+
+        Ok(Self {
+            manifests,
+            native_updates,
+        })
+    }
+
+    /// A manifest that doesn't exist at `tag` has no packages.
+    async fn get_packages(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        manifest: &Manifest,
+    ) -> anyhow::Result<Packages> {
+        let text =
+            github::get_file_content_if_exists(transport, platform, &tag.name, &manifest.path)
+                .await
+                .with_context(|| format!("couldn't get {} at {}", manifest.path, tag.name))?;
+
+        match text {
+            Some(text) => manifest.parse(&text),
+            None => Ok(Packages::new()),
+        }
+    }
+
+    /// A collapsed section listing at most `max_items` changes in each manifest, or an empty string
+    /// if there are none.
+    pub fn markdown_text(
+        &self,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        max_items: usize,
+    ) -> String {
+        if self.manifests.is_empty() {
+            return String::new();
+        }
+
+        let manifests = self
+            .manifests
+            .iter()
+            .map(|manifest| {
+                let url = platform.github_comparison_url(
+                    &old_tag.name,
+                    &new_tag.name,
+                    Some(&manifest.manifest.path),
+                    false,
+                );
+
+                format!(
+                    "#### [`{}`]({url})\n{}",
+                    manifest.manifest.path,
+                    utils::markdown_list(&manifest.changes, max_items)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        format!("[details=\"Dependencies changed\"]\n[quote]\n{manifests}\n[/quote]\n[/details]\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use serde_json::json;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    fn comparison(files: &[&str]) -> Comparison {
+        serde_json::from_value(json!({
+            "total_commits": 0,
+            "commits": [],
+            "files": files.iter().map(|file| json!({ "filename": file })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn get() {
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.2.0/package.json",
+                Response::new(
+                    200,
+                    json!({ "dependencies": { "@signalapp/libsignal-client": "0.52.2", "uuid": "9.0.0" } })
+                        .to_string(),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.3.0/package.json",
+                Response::new(
+                    200,
+                    json!({ "dependencies": { "@signalapp/libsignal-client": "0.52.3", "zod": "3.22.4" } })
+                        .to_string(),
+                ),
+            );
+
+        let old_tag = Tag::new("v1.2.0");
+        let new_tag = Tag::new("v1.3.0");
+
+        let changes = utils::block_on(DependencyChanges::get(
+            &transport,
+            Desktop,
+            &old_tag,
+            &new_tag,
+            &comparison(&["package.json", "ts/main.ts"]),
+        ))
+        .unwrap();
+
+        assert_eq!(changes.native_updates.len(), 1);
+        assert_str_eq!(
+            changes.markdown_text(Desktop, &old_tag, &new_tag, usize::MAX),
+            "[details=\"Dependencies changed\"]
+[quote]
+#### [`package.json`](//github.com/signalapp/Signal-Desktop/compare/v1.2.0..v1.3.0#diff-7ae45ad102eab3b6d7e7896acd08c427a9b25b346470d7bc6507b6481575d519)
+- Updated `@signalapp/libsignal-client` 0.52.2 → 0.52.3
+- Removed `uuid` 9.0.0
+- Added `zod` 3.22.4
+[/quote]
+[/details]
+"
+        );
+    }
+
+    #[test]
+    fn get_skips_unchanged_manifests() {
+        let transport = FakeTransport::new();
+
+        let changes = utils::block_on(DependencyChanges::get(
+            &transport,
+            Ios,
+            &Tag::new("7.0.0.1"),
+            &Tag::new("7.0.0.2"),
+            &comparison(&["Signal/src/AppDelegate.swift"]),
+        ))
+        .unwrap();
+
+        assert_eq!(changes, DependencyChanges::default());
+        assert!(transport.requests().is_empty());
+    }
+
+    #[test]
+    fn get_with_missing_manifest() {
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-Server/v1.0.0/pom.xml",
+                Response::new(404, String::from("404: Not Found")),
+            )
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-Server/v1.1.0/pom.xml",
+                Response::new(
+                    200,
+                    String::from("<dependency><groupId>a</groupId><artifactId>b</artifactId><version>1</version></dependency>"),
+                ),
+            );
+
+        let changes = utils::block_on(DependencyChanges::get(
+            &transport,
+            Server,
+            &Tag::new("v1.0.0"),
+            &Tag::new("v1.1.0"),
+            &comparison(&["pom.xml"]),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            changes.manifests[0].changes,
+            [PackageChange::Added {
+                name: String::from("a:b"),
+                version: String::from("1"),
+            }]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::PackageChange;
+use crate::{
+    config::Config,
+    platform::{builtin::*, Platform},
+};
+
+/// A native library the apps pin a version of, whose changes are worth linking to from posts.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Dependency {
+    Libsignal,
+    RingRtc,
+}
+
+impl Dependency {
+    /// Recognizes the library by its package name in the platform's manifests (see
+    /// [`crate::platform::PlatformDefinition::native_dependencies`]).
+    pub fn from_package_name(platform: Platform, name: &str) -> Option<Self> {
+        platform.definition().native_dependencies.get(name).copied()
+    }
+
+    pub fn repo_slug(&self, config: &'static Config) -> String {
+        match self {
+            Dependency::Libsignal => Libsignal.with_config(config).repo_slug(),
+            Dependency::RingRtc => String::from("signalapp/ringrtc"),
+        }
+    }
+
+    pub fn github_comparison_url(
+        &self,
+        config: &'static Config,
+        old_version: &str,
+        new_version: &str,
+        specify_protocol: bool,
+    ) -> String {
+        config.github_url(
+            &format!(
+                "{}/compare/v{old_version}...v{new_version}",
+                self.repo_slug(config)
+            ),
+            specify_protocol,
+        )
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Dependency::Libsignal => "libsignal",
+                Dependency::RingRtc => "RingRTC",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyUpdate {
+    pub dependency: Dependency,
+    pub old_version: String,
+    pub new_version: String,
+}
+
+impl DependencyUpdate {
+    /// Picks updates of [`Dependency`] packages, once per dependency.
+    pub fn from_package_changes<'a>(
+        platform: Platform,
+        changes: impl Iterator<Item = &'a PackageChange>,
+    ) -> Vec<Self> {
+        let mut updates: Vec<Self> = Vec::new();
+
+        for change in changes {
+            let PackageChange::Updated {
+                name,
+                old_version,
+                new_version,
+            } = change
+            else {
+                continue;
+            };
+
+            let Some(dependency) = Dependency::from_package_name(platform, name) else {
+                continue;
+            };
+
+            let version = |version: &str| version.trim_start_matches(['^', '~']).to_string();
+            let (old_version, new_version) = (version(old_version), version(new_version));
+
+            if old_version.is_empty()
+                || new_version.is_empty()
+                || old_version == new_version
+                || updates.iter().any(|update| update.dependency == dependency)
+            {
+                continue;
+            }
+
+            updates.push(Self {
+                dependency,
+                old_version,
+                new_version,
+            });
+        }
+
+        updates.sort_unstable_by_key(|update| update.dependency);
+        updates
+    }
+
+    pub fn github_comparison_url(&self, config: &'static Config, specify_protocol: bool) -> String {
+        self.dependency.github_comparison_url(
+            config,
+            &self.old_version,
+            &self.new_version,
+            specify_protocol,
+        )
+    }
+
+    /// Like `libsignal [0.52.2 → 0.52.3](…)`.
+    pub fn markdown_text(&self, config: &'static Config) -> String {
+        format!(
+            "{} [{} → {}]({})",
+            self.dependency,
+            self.old_version,
+            self.new_version,
+            self.github_comparison_url(config, false)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn updated(name: &str, old_version: &str, new_version: &str) -> PackageChange {
+        PackageChange::Updated {
+            name: name.to_string(),
+            old_version: old_version.to_string(),
+            new_version: new_version.to_string(),
+        }
+    }
+
+    #[test]
+    fn from_package_changes() {
+        let changes = [
+            updated("org.signal:ringrtc-android", "^2.44.0", "^2.44.1"),
+            updated("org.signal:libsignal-android", "0.52.2", "0.52.3"),
+            updated("org.signal:libsignal-client", "0.52.2", "0.52.3"),
+            updated("@signalapp/libsignal-client", "0.50.0", "0.51.0"),
+            updated("kotlin", "2.0.0", "2.0.10"),
+            PackageChange::Added {
+                name: String::from("org.signal:libsignal-server"),
+                version: String::from("0.52.3"),
+            },
+        ];
+
+        let updates = DependencyUpdate::from_package_changes(Android, changes.iter());
+
+        assert_eq!(
+            updates
+                .iter()
+                .map(|update| update.markdown_text(Config::default().leak()))
+                .collect::<Vec<_>>(),
+            [
+                "libsignal [0.52.2 → 0.52.3](//github.com/signalapp/libsignal/compare/v0.52.2...v0.52.3)",
+                "RingRTC [2.44.0 → 2.44.1](//github.com/signalapp/ringrtc/compare/v2.44.0...v2.44.1)",
+            ]
+        );
+    }
+}
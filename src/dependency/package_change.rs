@@ -0,0 +1,122 @@
+use std::fmt;
+
+use super::Packages;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageChange {
+    Added {
+        name: String,
+        version: String,
+    },
+    Removed {
+        name: String,
+        version: String,
+    },
+    /// The version changed, which may also be a downgrade.
+    Updated {
+        name: String,
+        old_version: String,
+        new_version: String,
+    },
+}
+
+use PackageChange::*;
+
+impl PackageChange {
+    /// Sorted by package name.
+    pub fn between(old: &Packages, new: &Packages) -> Vec<Self> {
+        let mut changes: Vec<_> = old
+            .iter()
+            .filter_map(|(name, old_version)| match new.get(name) {
+                None => Some(Removed {
+                    name: name.clone(),
+                    version: old_version.clone(),
+                }),
+                Some(new_version) if new_version != old_version => Some(Updated {
+                    name: name.clone(),
+                    old_version: old_version.clone(),
+                    new_version: new_version.clone(),
+                }),
+                Some(_) => None,
+            })
+            .chain(new.iter().filter(|(name, _)| !old.contains_key(*name)).map(
+                |(name, version)| Added {
+                    name: name.clone(),
+                    version: version.clone(),
+                },
+            ))
+            .collect();
+
+        changes.sort_by(|a, b| a.name().cmp(b.name()));
+        changes
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Added { name, .. } | Removed { name, .. } | Updated { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for PackageChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version = |version: &str| {
+            if version.is_empty() {
+                String::new()
+            } else {
+                format!(" {version}")
+            }
+        };
+
+        match self {
+            Added { name, version: v } => write!(f, "Added `{name}`{}", version(v)),
+            Removed { name, version: v } => write!(f, "Removed `{name}`{}", version(v)),
+            Updated {
+                name,
+                old_version,
+                new_version,
+            } => write!(
+                f,
+                "Updated `{name}` {} → {}",
+                if old_version.is_empty() {
+                    "?"
+                } else {
+                    old_version
+                },
+                if new_version.is_empty() {
+                    "?"
+                } else {
+                    new_version
+                }
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn between() {
+        let old = Packages::from([
+            (String::from("a"), String::from("1.0")),
+            (String::from("b"), String::from("1.0")),
+            (String::from("c"), String::from("1.0")),
+        ]);
+        let new = Packages::from([
+            (String::from("b"), String::from("1.1")),
+            (String::from("c"), String::from("1.0")),
+            (String::from("d"), String::new()),
+        ]);
+
+        let changes = PackageChange::between(&old, &new);
+
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["Removed `a` 1.0", "Updated `b` 1.0 → 1.1", "Added `d`"]
+        );
+    }
+}
@@ -1,50 +1,45 @@
 use anyhow::{bail, Context};
 use serde_json::{json, Value};
-use worker::{Env, Method, Url};
+use worker::{Method, Url};
 
 use crate::{
     env::EnvExt,
     markdown::Post,
-    network::{self, ContentType},
-    utils,
-    Platform::*,
+    network::{self, ContentType, Retry, Transport},
+    utils, Platform,
 };
 
-pub async fn notify(
-    env: &Env,
+pub fn mention_role(env: &dyn EnvExt, platform: Platform) -> anyhow::Result<String> {
+    let variable = &platform.definition().discord_mention_role;
+
+    env.discord_mention_role(variable).with_context(|| {
+        format!("could not get Discord mention role for {platform} from {variable}")
+    })
+}
+
+/// Builds the webhook message announcing `post`, mentioning `role`.
+pub fn update_message(
     post: &Post<'_>,
+    role: &str,
     discourse_topic_id: Option<u64>,
     discourse_post_number: Option<u64>,
-) -> anyhow::Result<()> {
-    let url = env
-        .discord_webhook_url_updates()
-        .context("could not get Discord updates webhook URL")?;
-
-    let role = match post.platform() {
-        Android | Ios | Desktop => env
-            .discord_updates_mention_role()
-            .context("could not get Discord updates mention role")?,
-        Server => env
-            .discord_server_updates_mention_role()
-            .context("could not get Discord server updates mention role")?,
-    };
-
-    let url = Url::parse(&url).context("could not parse url")?;
-
+) -> anyhow::Result<Value> {
+    let config = post.platform().config();
     let title = format!("{} {}", post.platform(), post.new_tag().name);
     let (post_url, notice) = match (discourse_topic_id, discourse_post_number) {
-        (Some(topic_id), Some(post_number)) => {
-            (utils::discourse_post_url(topic_id, post_number), None)
-        }
+        (Some(topic_id), Some(post_number)) => (
+            utils::discourse_post_url(config, topic_id, post_number),
+            None,
+        ),
         (Some(topic_id), None) => (
-            utils::discourse_topic_url(topic_id),
+            utils::discourse_topic_url(config, topic_id),
             Some("The update notice is not posted yet, likely awaiting moderation"),
         ),
         (None, Some(post_number)) => {
             bail!("have post number {post_number} but not a topic id")
         }
         (None, None) => (
-            String::from("https://community.signalusers.org/c/25"),
+            config.discourse_url("c/25"),
             Some("Beta feedback thread could not be located. Not created yet?"),
         ),
     };
@@ -64,7 +59,81 @@ pub async fn notify(
         role
     );
 
-    let body = json!({
+    let mut fields = vec![
+        json!({
+            "name": "Build number",
+            "value": post.new_build_configuration()
+                .as_ref()
+                .map(|conf| conf.build_number())
+                .unwrap_or(String::from("n/a")),
+            "inline": true
+        }),
+        json!({
+            "name": "Commits",
+            "value": post.commits().len().to_string(),
+            "inline": true
+        }),
+        json!({
+            "name": "Total commits",
+            "value": post.unfiltered_commits_len().to_string(),
+            "inline": true
+        }),
+        json!({
+            "name": "Languages changed (build)",
+            "value": post.localization_change_collection()
+                .build_changes
+                .unsorted_changes
+                .len()
+                .to_string(),
+            "inline": true
+        }),
+        json!({
+            "name": "Languages changed (release so far)",
+            "value": post.localization_change_collection()
+                .release_changes
+                .as_ref()
+                .map(|changes| changes.unsorted_changes.len())
+                .unwrap_or(0)
+                .to_string(),
+            "inline": true
+        }),
+    ];
+
+    if let Some(update) = &post.header_changes().electron_update {
+        fields.push(json!({
+            "name": "Electron",
+            "value": update.to_string(),
+            "inline": true
+        }));
+    }
+
+    if let Some(change) = post.database_version_change() {
+        fields.push(json!({
+            "name": "Database version",
+            "value": change.to_string(),
+            "inline": true
+        }));
+    }
+
+    fields.extend(
+        post.dependency_changes()
+            .native_updates
+            .iter()
+            .map(|update| {
+                json!({
+                    "name": update.dependency.to_string(),
+                    "value": format!(
+                        "[{} → {}]({})",
+                        update.old_version,
+                        update.new_version,
+                        update.github_comparison_url(config, true)
+                    ),
+                    "inline": true
+                })
+            }),
+    );
+
+    Ok(json!({
         "content": content,
         "embeds": [{
             "color": post.platform().color(),
@@ -78,51 +147,30 @@ pub async fn notify(
             "allowed_mentions": {
                 "roles": [role]
             },
-            "fields": [
-                {
-                    "name": "Build number",
-                    "value": post.new_build_configuration()
-                        .as_ref()
-                        .map(|conf| conf.canonical_version_code.to_string())
-                        .unwrap_or(String::from("n/a")),
-                    "inline": true
-                },
-                {
-                    "name": "Commits",
-                    "value": post.commits().len().to_string(),
-                    "inline": true
-                },
-                {
-                    "name": "Total commits",
-                    "value": post.unfiltered_commits_len().to_string(),
-                    "inline": true
-                },
-                {
-                    "name": "Languages changed (build)",
-                    "value": post.localization_change_collection()
-                        .build_changes
-                        .unsorted_changes
-                        .len()
-                        .to_string(),
-                    "inline": true
-                },
-                {
-                    "name": "Languages changed (release so far)",
-                    "value": post.localization_change_collection()
-                        .release_changes
-                        .as_ref()
-                        .map(|changes| changes.unsorted_changes.len())
-                        .unwrap_or(0)
-                        .to_string(),
-                    "inline": true
-                },
-            ],
+            "fields": fields,
         }],
-    });
+    }))
+}
+
+pub async fn notify(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    post: &Post<'_>,
+    discourse_topic_id: Option<u64>,
+    discourse_post_number: Option<u64>,
+) -> anyhow::Result<()> {
+    let url = env
+        .discord_webhook_url_updates()
+        .context("could not get Discord updates webhook URL")?;
+
+    let role = mention_role(env, post.platform())?;
+    let url = Url::parse(&url).context("could not parse url")?;
+
+    let body = update_message(post, &role, discourse_topic_id, discourse_post_number)?;
 
     tracing::trace!(?body, "will send to discord");
 
-    let request = network::create_request(
+    let mut request = network::create_request(
         url,
         Method::Post,
         ContentType::ApplicationJson,
@@ -131,25 +179,31 @@ pub async fn notify(
         None,
     )
     .context("could not create request to Discord")?;
+    request.retry = Retry::OnRateLimit;
 
-    let mut response = network::fetch(worker::Fetch::Request(request))
+    let response = network::fetch(transport, request)
         .await
         .context("could not send request to Discord")?;
 
-    if response.status_code() < 200 || response.status_code() > 299 {
-        let json: Value = network::json_from_response(&mut response).await?;
+    if !response.is_success() {
+        let json: Value = network::json_from_response(&response)?;
         tracing::debug!(?json, "got response from Discord");
 
         bail!(
             "discord responded with {} status code",
-            response.status_code()
+            response.status_code
         )
     };
 
     Ok(())
 }
 
-pub async fn send_error_message(env: &Env, error: &anyhow::Error, log: &str) -> anyhow::Result<()> {
+pub async fn send_error_message(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    error: &anyhow::Error,
+    log: &str,
+) -> anyhow::Result<()> {
     let role = env
         .discord_errors_mention_role()
         .context("could not get Discord errors mention role")?;
@@ -162,10 +216,15 @@ pub async fn send_error_message(env: &Env, error: &anyhow::Error, log: &str) ->
         _ => format!("**[error]** <@&{role}> (error is too large to show inline, see log)"),
     };
 
-    notify_with_log(env, &message, log).await
+    notify_with_log(env, transport, &message, log).await
 }
 
-pub async fn send_misc_message(env: &Env, content: &str, log: &str) -> anyhow::Result<()> {
+pub async fn send_misc_message(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    content: &str,
+    log: &str,
+) -> anyhow::Result<()> {
     let role = env
         .discord_errors_mention_role()
         .context("could not get Discord errors mention role")?;
@@ -176,10 +235,15 @@ pub async fn send_misc_message(env: &Env, content: &str, log: &str) -> anyhow::R
         _ => format!("**[misc]** (misc message is too large to show inline, see log) <@&{role}>"),
     };
 
-    notify_with_log(env, &message, log).await
+    notify_with_log(env, transport, &message, log).await
 }
 
-async fn notify_with_log(env: &Env, message: &str, log: &str) -> anyhow::Result<()> {
+async fn notify_with_log(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    message: &str,
+    log: &str,
+) -> anyhow::Result<()> {
     let url = env
         .discord_webhook_url_errors()
         .context("could not get Discord errors webhook URL")?;
@@ -212,7 +276,7 @@ async fn notify_with_log(env: &Env, message: &str, log: &str) -> anyhow::Result<
     ]
     .join("\r\n");
 
-    let request = network::create_request(
+    let mut request = network::create_request(
         url,
         Method::Post,
         ContentType::MultipartFormData(boundary.to_string()),
@@ -221,8 +285,9 @@ async fn notify_with_log(env: &Env, message: &str, log: &str) -> anyhow::Result<
         None,
     )
     .context("could not create request to Discord")?;
+    request.retry = Retry::OnRateLimit;
 
-    let response: Value = network::get_json_from_request(request)
+    let response: Value = network::get_json_from_request(transport, request)
         .await
         .context("could not send request to Discord")?;
 
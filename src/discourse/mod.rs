@@ -1,26 +1,29 @@
 use anyhow::{bail, Context};
 use semver::Version;
 use serde_json::json;
-use worker::{Env, Method, Url};
+use worker::{Method, Url};
 
 use crate::{
+    config::Config,
     env::EnvExt,
-    network::{self, ContentType},
-    platform::Platform,
+    network::{self, ContentType, Retry, RunContext, Transport},
+    platform::{Platform, TopicStrategy},
+    utils,
 };
 
 mod types;
 pub use types::*;
 
 pub async fn get_topic_id(
+    transport: &dyn Transport,
     api_key: &str,
     platform: Platform,
     version: &Version,
-    topic_id_for_server_updates: u64,
+    fixed_topic_id: Option<u64>,
 ) -> anyhow::Result<Option<u64>> {
     tracing::debug!(?version, "getting topic id for version");
 
-    let url = Url::parse(&platform.discourse_topic_slug_url(version, topic_id_for_server_updates))
+    let url = Url::parse(&platform.discourse_topic_slug_url(version, fixed_topic_id)?)
         .context("could not parse URL")?;
 
     let request = network::create_request(
@@ -31,7 +34,7 @@ pub async fn get_topic_id(
         None,
         Some(api_key),
     )?;
-    let response: ApiResponse<Topic> = network::get_json_from_request(request).await?;
+    let response: ApiResponse<Topic> = network::get_json_from_request(transport, request).await?;
 
     match &response {
         ApiResponse::Ok(response) => match response.post_stream.posts.first() {
@@ -50,7 +53,8 @@ pub async fn get_topic_id(
 }
 
 pub async fn get_topic_id_or_override(
-    env: &Env,
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
     api_key: &str,
     platform: Platform,
     version: &Version,
@@ -60,22 +64,28 @@ pub async fn get_topic_id_or_override(
             tracing::warn!(id, "using topic id override");
             Ok(Some(id))
         }
-        None => get_topic_id(
-            api_key,
-            platform,
-            version,
-            env.topic_id_for_server_updates()?,
-        )
-        .await
-        .context("could not find topic_id"),
+        None => {
+            let fixed_topic_id = match platform.topic_strategy() {
+                TopicStrategy::BetaFeedbackTopics => None,
+                TopicStrategy::FixedTopic { topic_id_variable } => {
+                    Some(env.fixed_topic_id(&topic_id_variable)?)
+                }
+            };
+
+            get_topic_id(transport, api_key, platform, version, fixed_topic_id)
+                .await
+                .context("could not find topic_id")
+        }
     }
 }
 
-pub fn archiving_post_markdown(new_topic_id: u64) -> String {
+pub fn archiving_post_markdown(config: &Config, new_topic_id: u64) -> String {
     format!(
-        "Beta testing for this release has concluded. If you find any further bugs related to this release or earlier releases, please report them on GitHub (read https://community.signalusers.org/t/27 for more information on how to do that).
+        "Beta testing for this release has concluded. If you find any further bugs related to this release or earlier releases, please report them on GitHub (read {} for more information on how to do that).
 
-If you have feedback specifically related to the new beta version, please post it in the following topic: https://community.signalusers.org/t/{new_topic_id}."
+If you have feedback specifically related to the new beta version, please post it in the following topic: {}.",
+        utils::discourse_topic_url(config, 27),
+        utils::discourse_topic_url(config, new_topic_id),
     )
 }
 
@@ -86,13 +96,15 @@ pub enum PostingOutcome {
 }
 
 pub async fn post(
+    transport: &dyn Transport,
+    context: &RunContext,
+    config: &Config,
     markdown_text: &str,
     api_key: &str,
     topic_id: u64,
     reply_to_post_number: Option<u64>,
 ) -> anyhow::Result<PostingOutcome> {
-    let url = Url::parse("https://community.signalusers.org/posts.json")
-        .context("could not parse URL")?;
+    let url = Url::parse(&config.discourse_url("posts.json")).context("could not parse URL")?;
 
     let body = json!({
         "topic_id": topic_id,
@@ -100,7 +112,7 @@ pub async fn post(
         "raw": markdown_text,
     });
 
-    let request = network::create_request(
+    let mut request = network::create_request(
         url,
         Method::Post,
         ContentType::ApplicationJson,
@@ -108,8 +120,20 @@ pub async fn post(
         Some(body.to_string()),
         Some(api_key),
     )?;
-    let api_response: ApiResponse<CreatePostResponse> =
-        network::get_json_from_request(request).await?;
+    // Discourse rejects rate limited requests before creating anything, but a post could already
+    // have been created if some other error occurs.
+    request.retry = Retry::OnRateLimit;
+
+    let response = network::fetch(transport, request).await?;
+
+    if context.dry_run {
+        return Ok(PostingOutcome::Posted {
+            id: 0,
+            number: reply_to_post_number.unwrap_or(0),
+        });
+    }
+
+    let api_response: ApiResponse<CreatePostResponse> = network::json_from_response(&response)?;
 
     match api_response {
         ApiResponse::Ok(CreatePostResponse::Posted(post)) => Ok(PostingOutcome::Posted {
@@ -124,11 +148,14 @@ pub async fn post(
     }
 }
 
-pub async fn get_post(post_id: u64, api_key: &str) -> anyhow::Result<Post> {
-    let url = Url::parse(&format!(
-        "https://community.signalusers.org/posts/{post_id}.json"
-    ))
-    .context("could not parse URL")?;
+pub async fn get_post(
+    transport: &dyn Transport,
+    config: &Config,
+    post_id: u64,
+    api_key: &str,
+) -> anyhow::Result<Post> {
+    let url = Url::parse(&config.discourse_url(&format!("posts/{post_id}.json")))
+        .context("could not parse URL")?;
 
     let request = network::create_request(
         url,
@@ -138,7 +165,7 @@ pub async fn get_post(post_id: u64, api_key: &str) -> anyhow::Result<Post> {
         None,
         Some(api_key),
     )?;
-    let post: ApiResponse<Post> = network::get_json_from_request(request).await?;
+    let post: ApiResponse<Post> = network::get_json_from_request(transport, request).await?;
 
     Ok(match post {
         ApiResponse::Ok(post) => post,
@@ -147,11 +174,16 @@ pub async fn get_post(post_id: u64, api_key: &str) -> anyhow::Result<Post> {
     })
 }
 
-pub async fn edit_post(post_id: u64, api_key: &str, raw: &str) -> anyhow::Result<WrappedPost> {
-    let url = Url::parse(&format!(
-        "https://community.signalusers.org/posts/{post_id}.json"
-    ))
-    .context("could not parse URL")?;
+pub async fn edit_post(
+    transport: &dyn Transport,
+    context: &RunContext,
+    config: &Config,
+    post_id: u64,
+    api_key: &str,
+    raw: &str,
+) -> anyhow::Result<()> {
+    let url = Url::parse(&config.discourse_url(&format!("posts/{post_id}.json")))
+        .context("could not parse URL")?;
 
     let request = network::create_request(
         url,
@@ -168,20 +200,28 @@ pub async fn edit_post(post_id: u64, api_key: &str, raw: &str) -> anyhow::Result
         ),
         Some(api_key),
     )?;
-    let post: ApiResponse<WrappedPost> = network::get_json_from_request(request).await?;
+    let response = network::fetch(transport, request).await?;
 
-    Ok(match post {
-        ApiResponse::Ok(post) => post,
+    if context.dry_run {
+        return Ok(());
+    }
+
+    let post: ApiResponse<WrappedPost> = network::json_from_response(&response)?;
+
+    match post {
+        ApiResponse::Ok(_) => Ok(()),
         ApiResponse::Err(error) => bail!("error = {error:?}"),
         ApiResponse::Unknown(value) => bail!("unexpected response = {value:?}"),
-    })
+    }
 }
 
-pub async fn get_replies_to_post(post_id: u64) -> anyhow::Result<Vec<Post>> {
-    let url = Url::parse(&format!(
-        "https://community.signalusers.org/posts/{post_id}/replies.json"
-    ))
-    .context("could not parse URL")?;
+pub async fn get_replies_to_post(
+    transport: &dyn Transport,
+    config: &Config,
+    post_id: u64,
+) -> anyhow::Result<Vec<Post>> {
+    let url = Url::parse(&config.discourse_url(&format!("posts/{post_id}/replies.json")))
+        .context("could not parse URL")?;
 
     // Without API key, in case the post is returned for the author even while it's enqueued
     let request = network::create_request(
@@ -192,7 +232,7 @@ pub async fn get_replies_to_post(post_id: u64) -> anyhow::Result<Vec<Post>> {
         None,
         None,
     )?;
-    let posts: ApiResponse<Vec<Post>> = network::get_json_from_request(request).await?;
+    let posts: ApiResponse<Vec<Post>> = network::get_json_from_request(transport, request).await?;
 
     Ok(match posts {
         ApiResponse::Ok(posts) => posts,
@@ -200,3 +240,72 @@ pub async fn get_replies_to_post(post_id: u64) -> anyhow::Result<Vec<Post>> {
         ApiResponse::Unknown(value) => bail!("unexpected response = {value:?}"),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    #[test]
+    fn topic_id_found() {
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "https://community.signalusers.org/t/beta-feedback-for-the-upcoming-android-1-2-release.json",
+            Response::new(
+                200,
+                json!({
+                    "post_stream": {
+                        "posts": [{ "id": 1, "topic_id": 123, "post_number": 1, "user_id": 2 }]
+                    }
+                })
+                .to_string(),
+            ),
+        );
+
+        let topic_id = utils::block_on(get_topic_id(
+            &transport,
+            "key",
+            Android,
+            &Version::new(1, 2, 3),
+            None,
+        ))
+        .unwrap();
+
+        assert_eq!(topic_id, Some(123));
+        assert_eq!(transport.requests()[0].header("User-Api-Key"), Some("key"));
+    }
+
+    #[test]
+    fn topic_id_not_found() {
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "https://community.signalusers.org/t/beta-feedback-for-the-upcoming-ios-1-2-release.json",
+            Response::new(
+                404,
+                json!({
+                    "errors": ["The requested URL or resource could not be found."],
+                    "error_type": "not_found"
+                })
+                .to_string(),
+            ),
+        );
+
+        let topic_id = utils::block_on(get_topic_id(
+            &transport,
+            "key",
+            Ios,
+            &Version::new(1, 2, 3),
+            None,
+        ))
+        .unwrap();
+
+        assert_eq!(topic_id, None);
+    }
+}
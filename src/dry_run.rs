@@ -0,0 +1,306 @@
+//! Simulation mode, enabled with `DRY_RUN`: everything is read as usual, but writes to Discourse,
+//! Discord and the state storage are only recorded, to be reported at the end of the run.
+
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use worker::{Method, Url};
+
+use crate::{
+    env::{EnvExt, StringBindingKind},
+    network::{Request, Response, Transport},
+    state::Storage,
+    utils::BoxFuture,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Request {
+        method: Method,
+        url: Url,
+        body: Option<String>,
+    },
+    Put {
+        key: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Request { method, url, body } => {
+                write!(f, "{} request to {url}", method.as_ref())?;
+
+                match body {
+                    Some(body) => write!(f, " with body: {body}"),
+                    None => Ok(()),
+                }
+            }
+            Action::Put { key, value } => write!(f, "put to storage key {key}: {value}"),
+        }
+    }
+}
+
+/// Records the actions that would have been performed during a dry run.
+///
+/// Clones share the same record.
+#[derive(Clone, Default)]
+pub struct DryRun {
+    actions: Rc<RefCell<Vec<Action>>>,
+    /// Values put to storage, so that they can be read back later in the same run.
+    values: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl DryRun {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn actions(&self) -> Vec<Action> {
+        self.actions.borrow().clone()
+    }
+
+    fn record(&self, action: Action) {
+        tracing::warn!(%action, "dry run; not performing action");
+        self.actions.borrow_mut().push(action);
+    }
+
+    pub fn report(&self) -> String {
+        let actions = self.actions.borrow();
+
+        if actions.is_empty() {
+            return String::from("dry run finished; no actions would have been performed");
+        }
+
+        let list = actions
+            .iter()
+            .enumerate()
+            .map(|(index, action)| format!("{}. {action}", index + 1))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            "dry run finished; the following {} action(s) would have been performed:\n{list}",
+            actions.len()
+        )
+    }
+}
+
+/// Passes requests that don't change anything through, and records all other requests, answering
+/// them with a successful response with an empty JSON object, so that callers expecting JSON can
+/// go on.
+pub struct DryRunTransport<'a> {
+    inner: &'a dyn Transport,
+    dry_run: DryRun,
+}
+
+impl<'a> DryRunTransport<'a> {
+    pub fn new(inner: &'a dyn Transport, dry_run: DryRun) -> Self {
+        Self { inner, dry_run }
+    }
+}
+
+impl Transport for DryRunTransport<'_> {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        match request.method {
+            Method::Get | Method::Head | Method::Options => self.inner.send(request),
+            _ => {
+                self.dry_run.record(Action::Request {
+                    method: request.method,
+                    url: request.url,
+                    body: request.body,
+                });
+
+                Box::pin(async move { Ok(Response::new(200, "{}")) })
+            }
+        }
+    }
+}
+
+/// Reads from the wrapped storage, but records writes, keeping their values in memory.
+pub struct DryRunStorage {
+    inner: Box<dyn Storage>,
+    dry_run: DryRun,
+}
+
+impl Storage for DryRunStorage {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        match self.dry_run.values.borrow().get(key) {
+            Some(value) => {
+                let value = value.clone();
+                Box::pin(async move { Ok(Some(value)) })
+            }
+            None => self.inner.get(key),
+        }
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: String) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.dry_run.record(Action::Put {
+            key: key.to_string(),
+            value: value.clone(),
+        });
+        self.dry_run
+            .values
+            .borrow_mut()
+            .insert(key.to_string(), value);
+
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// Provides the wrapped environment's bindings, with state storage wrapped in [`DryRunStorage`].
+pub struct DryRunEnv<'a> {
+    inner: &'a dyn EnvExt,
+    dry_run: DryRun,
+}
+
+impl<'a> DryRunEnv<'a> {
+    pub fn new(inner: &'a dyn EnvExt, dry_run: DryRun) -> Self {
+        Self { inner, dry_run }
+    }
+}
+
+impl EnvExt for DryRunEnv<'_> {
+    fn string_binding(&self, kind: StringBindingKind, name: &str) -> anyhow::Result<String> {
+        self.inner.string_binding(kind, name)
+    }
+
+    fn state_storage(&self) -> anyhow::Result<Box<dyn Storage>> {
+        Ok(Box::new(DryRunStorage {
+            inner: self.inner.state_storage()?,
+            dry_run: self.dry_run.clone(),
+        }))
+    }
+
+    fn is_dry_run(&self) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        env::TestEnv,
+        network::{self, ContentType, FakeTransport},
+        state::MemoryStorage,
+        utils::block_on,
+    };
+
+    #[test]
+    fn transport() {
+        let inner = FakeTransport::new().with_response(
+            Method::Get,
+            "https://example.com/",
+            Response::new(200, "hello"),
+        );
+
+        let dry_run = DryRun::new();
+        let transport = DryRunTransport::new(&inner, dry_run.clone());
+
+        let get = network::create_request(
+            "https://example.com/".parse().unwrap(),
+            Method::Get,
+            ContentType::TextPlain,
+            ContentType::TextPlain,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(block_on(transport.send(get)).unwrap().body, "hello");
+
+        let post = network::create_request(
+            "https://example.com/posts".parse().unwrap(),
+            Method::Post,
+            ContentType::TextPlain,
+            ContentType::TextPlain,
+            Some(String::from("body")),
+            None,
+        )
+        .unwrap();
+        let response = block_on(transport.send(post)).unwrap();
+        assert!(response.is_success());
+        assert_eq!(response.body, "{}");
+
+        assert_eq!(inner.requests_with_method(Method::Post), []);
+        assert_eq!(
+            dry_run.actions(),
+            [Action::Request {
+                method: Method::Post,
+                url: "https://example.com/posts".parse().unwrap(),
+                body: Some(String::from("body")),
+            }]
+        );
+    }
+
+    #[test]
+    fn discord_message_with_log() {
+        let inner = FakeTransport::new();
+        let dry_run = DryRun::new();
+        let transport = DryRunTransport::new(&inner, dry_run.clone());
+
+        let env = TestEnv::new(MemoryStorage::new())
+            .with_binding(
+                "DISCORD_WEBHOOK_URL",
+                "https://discord.com/api/webhooks/1/token",
+            )
+            .with_binding("DISCORD_ERRORS_MENTION_ROLE", "123");
+
+        block_on(crate::discord::send_misc_message(
+            &env, &transport, "hello", "log",
+        ))
+        .unwrap();
+
+        assert!(inner.requests().is_empty());
+        assert!(matches!(
+            dry_run.actions().as_slice(),
+            [Action::Request { method: Method::Post, url, .. }]
+                if url.as_str() == "https://discord.com/api/webhooks/1/token"
+        ));
+    }
+
+    #[test]
+    fn storage() {
+        let inner = MemoryStorage::new().with_value("a", "1");
+
+        let dry_run = DryRun::new();
+        let test_env = TestEnv::new(inner.clone());
+        let env = DryRunEnv::new(&test_env, dry_run.clone());
+
+        let storage = env.state_storage().unwrap();
+        block_on(storage.put("b", String::from("2"))).unwrap();
+
+        let storage = env.state_storage().unwrap();
+        assert_eq!(block_on(storage.get("a")).unwrap().as_deref(), Some("1"));
+        assert_eq!(block_on(storage.get("b")).unwrap().as_deref(), Some("2"));
+
+        assert_eq!(inner.value("b"), None);
+        assert_eq!(
+            dry_run.actions(),
+            [Action::Put {
+                key: String::from("b"),
+                value: String::from("2"),
+            }]
+        );
+    }
+
+    #[test]
+    fn report() {
+        let dry_run = DryRun::new();
+        assert_eq!(
+            dry_run.report(),
+            "dry run finished; no actions would have been performed"
+        );
+
+        dry_run.record(Action::Put {
+            key: String::from("state"),
+            value: String::from("{}"),
+        });
+        assert_eq!(
+            dry_run.report(),
+            "dry run finished; the following 1 action(s) would have been performed:\n1. put to storage key state: {}"
+        );
+    }
+}
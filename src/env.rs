@@ -1,113 +1,252 @@
-use anyhow::{anyhow, Context};
-use strum::IntoEnumIterator;
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context};
 use worker::{wasm_bindgen::JsValue, Env};
 
-use crate::platform::Platform;
+use crate::{
+    config::Config,
+    network::{RetryPolicy, DEFAULT_SUBREQUEST_LIMIT},
+    platform::{Platform, PlatformDefinition},
+    state::{KvStorage, Storage},
+};
+
+const STATE_KV_BINDING: &str = "STATE";
 
-#[derive(Debug)]
-enum StringBindingKind {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringBindingKind {
     Secret,
     Var,
 }
 
 use StringBindingKind::*;
 
-fn get_env_string(env: &Env, kind: StringBindingKind, name: &str) -> anyhow::Result<String> {
-    let string_binding = match kind {
-        Secret => env.secret(name),
-        Var => env.var(name),
+/// `string` is a comma-separated list of [`PlatformDefinition::key`]s.
+fn filter_platforms(config: &'static Config, string: &str) -> anyhow::Result<Vec<Platform>> {
+    let keys: Vec<&str> = string
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .collect();
+
+    if let Some(key) = keys
+        .iter()
+        .find(|key| Platform::from_state_key(config, key).is_none())
+    {
+        bail!("unknown platform in ENABLED_PLATFORMS: {key}");
     }
-    .map_err(|e| anyhow!(e.to_string()))
-    .with_context(|| anyhow!("couldn't get string binding kind = {kind:?}, name = {name}"))?;
 
-    JsValue::from(string_binding)
-        .as_string()
-        .ok_or_else(|| anyhow!("couldn't get value of string binding"))
-}
-
-fn filter_platforms(string: &str) -> Vec<Platform> {
-    Platform::iter()
-        .filter(|platform| {
-            string.contains(platform.to_string().to_lowercase().chars().next().unwrap())
-        })
-        .collect()
+    Ok(Platform::iter(config)
+        .filter(|platform| keys.contains(&platform.definition().key.as_str()))
+        .collect())
 }
 
+/// The bindings the bot runs with: string variables and secrets, and state storage.
+///
+/// Implemented for the Workers [`Env`], and for other environments like the native command-line
+/// binary.
 pub trait EnvExt {
-    fn discourse_api_key(&self) -> anyhow::Result<String>;
-
-    fn discord_webhook_url_errors(&self) -> anyhow::Result<String>;
-    fn discord_webhook_url_updates(&self) -> anyhow::Result<String>;
-    fn discord_errors_mention_role(&self) -> anyhow::Result<String>;
-    fn discord_server_updates_mention_role(&self) -> anyhow::Result<String>;
-    fn discord_updates_mention_role(&self) -> anyhow::Result<String>;
-
-    fn user_id(&self) -> anyhow::Result<u64>;
-
-    fn topic_id_for_server_updates(&self) -> anyhow::Result<u64>;
-    fn topic_id_override(&self) -> anyhow::Result<Option<u64>>;
+    fn string_binding(&self, kind: StringBindingKind, name: &str) -> anyhow::Result<String>;
+    fn state_storage(&self) -> anyhow::Result<Box<dyn Storage>>;
 
-    fn is_dry_run(&self) -> anyhow::Result<bool>;
-    fn enabled_platforms(&self) -> anyhow::Result<Vec<Platform>>;
-
-    fn access_token(&self) -> anyhow::Result<String>;
-}
-
-impl EnvExt for Env {
     fn discourse_api_key(&self) -> anyhow::Result<String> {
-        get_env_string(self, Secret, "DISCOURSE_API_KEY")
+        self.string_binding(Secret, "DISCOURSE_API_KEY")
     }
 
     fn discord_webhook_url_errors(&self) -> anyhow::Result<String> {
-        get_env_string(self, Secret, "DISCORD_WEBHOOK_URL")
+        self.string_binding(Secret, "DISCORD_WEBHOOK_URL")
     }
 
     fn discord_webhook_url_updates(&self) -> anyhow::Result<String> {
-        get_env_string(self, Secret, "DISCORD_WEBHOOK_URL_UPDATES")
+        self.string_binding(Secret, "DISCORD_WEBHOOK_URL_UPDATES")
     }
 
     fn discord_errors_mention_role(&self) -> anyhow::Result<String> {
-        get_env_string(self, Var, "DISCORD_ERRORS_MENTION_ROLE")
+        self.string_binding(Var, "DISCORD_ERRORS_MENTION_ROLE")
     }
 
-    fn discord_server_updates_mention_role(&self) -> anyhow::Result<String> {
-        get_env_string(self, Var, "DISCORD_SERVER_UPDATES_MENTION_ROLE")
-    }
-
-    fn discord_updates_mention_role(&self) -> anyhow::Result<String> {
-        get_env_string(self, Var, "DISCORD_UPDATES_MENTION_ROLE")
+    /// `variable` comes from [`PlatformDefinition::discord_mention_role`].
+    fn discord_mention_role(&self, variable: &str) -> anyhow::Result<String> {
+        self.string_binding(Var, variable)
     }
 
     fn user_id(&self) -> anyhow::Result<u64> {
-        get_env_string(self, Var, "USER_ID").map(|string| {
+        self.string_binding(Var, "USER_ID").map(|string| {
             string
                 .parse()
                 .context("couldn't parse user ID from the environment")
         })?
     }
 
-    fn topic_id_for_server_updates(&self) -> anyhow::Result<u64> {
-        get_env_string(self, Var, "TOPIC_ID_FOR_SERVER_UPDATES").map(|string| {
+    /// `variable` comes from [`crate::platform::TopicStrategy::FixedTopic`].
+    fn fixed_topic_id(&self, variable: &str) -> anyhow::Result<u64> {
+        self.string_binding(Var, variable).map(|string| {
             string
                 .parse()
-                .context("couldn't parse topic ID for server updates from the environment")
+                .with_context(|| format!("couldn't parse topic ID from {variable}"))
         })?
     }
 
     fn topic_id_override(&self) -> anyhow::Result<Option<u64>> {
-        get_env_string(self, Var, "TOPIC_ID_OVERRIDE").map(|string| string.parse().ok())
+        self.string_binding(Var, "TOPIC_ID_OVERRIDE")
+            .map(|string| string.parse().ok())
     }
 
     fn is_dry_run(&self) -> anyhow::Result<bool> {
-        get_env_string(self, Var, "DRY_RUN").map(|string| string == "true")
+        self.string_binding(Var, "DRY_RUN")
+            .map(|string| string == "true")
     }
 
-    fn enabled_platforms(&self) -> anyhow::Result<Vec<Platform>> {
-        get_env_string(self, Var, "ENABLED_PLATFORMS").map(|s| filter_platforms(&s))
+    fn enabled_platforms(&self, config: &'static Config) -> anyhow::Result<Vec<Platform>> {
+        filter_platforms(config, &self.string_binding(Var, "ENABLED_PLATFORMS")?)
     }
 
     fn access_token(&self) -> anyhow::Result<String> {
-        get_env_string(self, Var, "ACCESS_TOKEN")
+        self.string_binding(Var, "ACCESS_TOKEN")
+    }
+
+    fn discourse_base_url(&self) -> Option<String> {
+        optional_binding(self, "DISCOURSE_BASE_URL")
+    }
+
+    fn github_base_url(&self) -> Option<String> {
+        optional_binding(self, "GITHUB_BASE_URL")
+    }
+
+    fn github_api_base_url(&self) -> Option<String> {
+        optional_binding(self, "GITHUB_API_BASE_URL")
+    }
+
+    fn github_raw_base_url(&self) -> Option<String> {
+        optional_binding(self, "GITHUB_RAW_BASE_URL")
+    }
+
+    /// Map of platform state keys to repositories, like `{ "android": "owner/Signal-Android" }`.
+    fn github_repositories(&self) -> anyhow::Result<HashMap<String, String>> {
+        match optional_binding(self, "GITHUB_REPOSITORIES") {
+            Some(string) => serde_json::from_str(&string)
+                .context("couldn't parse GitHub repositories from the environment"),
+            None => Ok(HashMap::new()),
+        }
+    }
+
+    /// Definitions that replace the built-in ones with the same key or add new platforms.
+    fn platform_definitions(&self) -> anyhow::Result<Vec<PlatformDefinition>> {
+        match optional_binding(self, "PLATFORMS") {
+            Some(string) => serde_json::from_str(&string)
+                .context("couldn't parse platform definitions from the environment"),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn github_token(&self) -> Option<String> {
+        self.string_binding(Secret, "GITHUB_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+    }
+
+    /// How many GitHub API requests to keep in reserve; platforms are deferred rather than
+    /// dipping into them.
+    fn github_min_remaining_requests(&self) -> anyhow::Result<u64> {
+        Ok(optional_number(self, "GITHUB_MIN_REMAINING_REQUESTS")?.unwrap_or(10))
+    }
+
+    /// How many requests a run can make, see [`crate::network::SubrequestBudgetTransport`].
+    fn subrequest_limit(&self) -> anyhow::Result<u64> {
+        Ok(optional_number(self, "SUBREQUEST_LIMIT")?.unwrap_or(DEFAULT_SUBREQUEST_LIMIT))
+    }
+
+    /// Uses [`RetryPolicy::default`] values for variables that aren't set or are empty.
+    fn retry_policy(&self) -> anyhow::Result<RetryPolicy> {
+        let default = RetryPolicy::default();
+
+        Ok(RetryPolicy {
+            max_attempts: optional_number(self, "RETRY_MAX_ATTEMPTS")?
+                .unwrap_or(default.max_attempts),
+            initial_delay_milliseconds: optional_number(self, "RETRY_INITIAL_DELAY_MS")?
+                .unwrap_or(default.initial_delay_milliseconds),
+            max_delay_milliseconds: optional_number(self, "RETRY_MAX_DELAY_MS")?
+                .unwrap_or(default.max_delay_milliseconds),
+        })
+    }
+}
+
+/// A variable that may be not set or empty.
+fn optional_binding<E: EnvExt + ?Sized>(env: &E, name: &str) -> Option<String> {
+    env.string_binding(Var, name)
+        .ok()
+        .filter(|string| !string.is_empty())
+}
+
+fn optional_number<E: EnvExt + ?Sized, T: std::str::FromStr>(
+    env: &E,
+    name: &str,
+) -> anyhow::Result<Option<T>> {
+    optional_binding(env, name)
+        .map(|string| {
+            string
+                .parse()
+                .map_err(|_| anyhow!("couldn't parse {name} from the environment"))
+        })
+        .transpose()
+}
+
+impl EnvExt for Env {
+    fn string_binding(&self, kind: StringBindingKind, name: &str) -> anyhow::Result<String> {
+        let string_binding = match kind {
+            Secret => self.secret(name),
+            Var => self.var(name),
+        }
+        .map_err(|e| anyhow!(e.to_string()))
+        .with_context(|| anyhow!("couldn't get string binding kind = {kind:?}, name = {name}"))?;
+
+        JsValue::from(string_binding)
+            .as_string()
+            .ok_or_else(|| anyhow!("couldn't get value of string binding"))
+    }
+
+    fn state_storage(&self) -> anyhow::Result<Box<dyn Storage>> {
+        let kv_store = self
+            .kv(STATE_KV_BINDING)
+            .map_err(|e| anyhow!(e.to_string()))
+            .context("could not get KV store")?;
+
+        Ok(Box::new(KvStorage::new(kv_store)))
+    }
+}
+
+/// Bindings for tests, with state kept in memory.
+#[cfg(test)]
+pub struct TestEnv {
+    bindings: std::collections::HashMap<String, String>,
+    storage: crate::state::MemoryStorage,
+}
+
+#[cfg(test)]
+impl TestEnv {
+    pub fn new(storage: crate::state::MemoryStorage) -> Self {
+        Self {
+            bindings: Default::default(),
+            storage,
+        }
+    }
+
+    pub fn with_binding(mut self, name: &str, value: &str) -> Self {
+        self.bindings.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+impl EnvExt for TestEnv {
+    fn string_binding(&self, kind: StringBindingKind, name: &str) -> anyhow::Result<String> {
+        self.bindings
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("couldn't get string binding kind = {kind:?}, name = {name}"))
+    }
+
+    fn state_storage(&self) -> anyhow::Result<Box<dyn Storage>> {
+        Ok(Box::new(self.storage.clone()))
     }
 }
 
@@ -117,14 +256,36 @@ mod tests {
     use test_case::test_case;
 
     use super::*;
-    use crate::platform::Platform::*;
-
-    #[test_case("aid", &[Android, Ios, Desktop])]
-    #[test_case("ai", &[Android, Ios])]
-    #[test_case("id", &[Ios, Desktop])]
-    #[test_case("a", &[Android])]
-    #[test_case("", &[])]
-    fn filter_platforms(string: &str, output: &[Platform]) {
-        assert_eq!(super::filter_platforms(string), output);
+    use crate::platform::builtin::*;
+
+    #[test_case("android,ios,desktop", Some(&[Android, Ios, Desktop]))]
+    #[test_case("ios, android", Some(&[Android, Ios]))]
+    #[test_case("ios,desktop,", Some(&[Ios, Desktop]))]
+    #[test_case("android", Some(&[Android]))]
+    #[test_case("", Some(&[]))]
+    #[test_case("aid", None)]
+    fn filter_platforms(string: &str, output: Option<&[Platform]>) {
+        assert_eq!(
+            super::filter_platforms(Config::default().leak(), string).ok(),
+            output.map(<[Platform]>::to_vec)
+        );
+    }
+
+    #[test]
+    fn retry_policy() {
+        let env = TestEnv::new(Default::default())
+            .with_binding("RETRY_MAX_ATTEMPTS", "5")
+            .with_binding("RETRY_INITIAL_DELAY_MS", "");
+
+        assert_eq!(
+            env.retry_policy().unwrap(),
+            RetryPolicy {
+                max_attempts: 5,
+                ..Default::default()
+            }
+        );
+
+        let env = env.with_binding("RETRY_MAX_DELAY_MS", "never");
+        assert!(env.retry_policy().is_err());
     }
 }
@@ -0,0 +1,473 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+    platform::Platform,
+    utils,
+};
+
+/// Flag names mapped to their default values. Values are empty if a source file doesn't specify
+/// them, like for remote config keys that are only declared.
+pub type Flags = BTreeMap<String, String>;
+
+lazy_static! {
+    static ref KOTLIN_REMOTE_VALUE_REGEX: Regex = Regex::new(
+        r#"(?s)\bremote\w*\(\s*key\s*=\s*"([^"]+)"(?:\s*,\s*defaultValue\s*=\s*([^,\n)]+))?"#
+    )
+    .unwrap();
+    static ref KOTLIN_CONST_REGEX: Regex =
+        Regex::new(r#"const val \w+\s*=\s*"((?:android|global)\.[\w.\-]+)""#).unwrap();
+    static ref TYPESCRIPT_KEY_REGEX: Regex =
+        Regex::new(r"'((?:desktop|global)\.[\w.\-]+)'").unwrap();
+    static ref SWIFT_STATIC_REGEX: Regex = Regex::new(
+        r"^\s*(?:public |internal )?static (?:let|var) (\w+)(?:\s*:\s*[\w.]+)?\s*=\s*(.+?)\s*$"
+    )
+    .unwrap();
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagSourceKind {
+    Kotlin,
+    TypeScript,
+    Swift,
+}
+
+use FlagSourceKind::*;
+
+/// A file in a platform's repository that declares feature flags or remote config keys (see
+/// [`crate::platform::PlatformDefinition::flag_sources`]).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FlagSource {
+    pub path: String,
+    pub kind: FlagSourceKind,
+}
+
+impl FlagSource {
+    pub fn new(path: &str, kind: FlagSourceKind) -> Self {
+        Self {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    pub fn parse(&self, text: &str) -> Flags {
+        match self.kind {
+            Kotlin => parse_kotlin(text),
+            TypeScript => parse_typescript(text),
+            Swift => parse_swift(text),
+        }
+    }
+}
+
+/// Remote values declared with delegates like `remoteBoolean(key = …, defaultValue = …)`, as
+/// well as keys declared as constants in older versions.
+fn parse_kotlin(text: &str) -> Flags {
+    let mut flags: Flags = KOTLIN_CONST_REGEX
+        .captures_iter(text)
+        .map(|captures| (captures[1].to_string(), String::new()))
+        .collect();
+
+    for captures in KOTLIN_REMOTE_VALUE_REGEX.captures_iter(text) {
+        let default_value = captures
+            .get(2)
+            .map(|value| value.as_str().trim().to_string())
+            .unwrap_or_default();
+
+        flags.insert(captures[1].to_string(), default_value);
+    }
+
+    flags
+}
+
+fn parse_typescript(text: &str) -> Flags {
+    TYPESCRIPT_KEY_REGEX
+        .captures_iter(text)
+        .map(|captures| (captures[1].to_string(), String::new()))
+        .collect()
+}
+
+/// Only takes single-line static properties into account.
+fn parse_swift(text: &str) -> Flags {
+    text.lines()
+        .filter_map(|line| SWIFT_STATIC_REGEX.captures(line))
+        .filter(|captures| !captures[2].ends_with('{'))
+        .map(|captures| (captures[1].to_string(), captures[2].to_string()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagChange {
+    Added {
+        name: String,
+        value: String,
+    },
+    Removed {
+        name: String,
+    },
+    Changed {
+        name: String,
+        old_value: String,
+        new_value: String,
+    },
+}
+
+impl FlagChange {
+    /// Sorted by flag name.
+    pub fn between(old: &Flags, new: &Flags) -> Vec<Self> {
+        let mut changes: Vec<_> =
+            old.iter()
+                .filter_map(|(name, old_value)| match new.get(name) {
+                    None => Some(FlagChange::Removed { name: name.clone() }),
+                    Some(new_value) if new_value != old_value => Some(FlagChange::Changed {
+                        name: name.clone(),
+                        old_value: old_value.clone(),
+                        new_value: new_value.clone(),
+                    }),
+                    Some(_) => None,
+                })
+                .chain(new.iter().filter(|(name, _)| !old.contains_key(*name)).map(
+                    |(name, value)| FlagChange::Added {
+                        name: name.clone(),
+                        value: value.clone(),
+                    },
+                ))
+                .collect();
+
+        changes.sort_by(|a, b| a.name().cmp(b.name()));
+        changes
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            FlagChange::Added { name, .. }
+            | FlagChange::Removed { name }
+            | FlagChange::Changed { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for FlagChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &str| {
+            if value.is_empty() {
+                String::from("?")
+            } else {
+                format!("`{value}`")
+            }
+        };
+
+        match self {
+            FlagChange::Added { name, value: v } if v.is_empty() => write!(f, "Added `{name}`"),
+            FlagChange::Added { name, value: v } => write!(f, "Added `{name}` = {}", value(v)),
+            FlagChange::Removed { name } => write!(f, "Removed `{name}`"),
+            FlagChange::Changed {
+                name,
+                old_value,
+                new_value,
+            } => write!(
+                f,
+                "Changed `{name}` {} → {}",
+                value(old_value),
+                value(new_value)
+            ),
+        }
+    }
+}
+
+/// How the feature flags of a platform changed between two tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlagChanges {
+    /// The source the new flags were read from, if any.
+    pub source: Option<FlagSource>,
+    pub changes: Vec<FlagChange>,
+}
+
+impl FeatureFlagChanges {
+    /// Upper bound of requests [`Self::get`] makes.
+    pub fn estimated_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        let sources = &platform.definition().flag_sources;
+        let paths: Vec<_> = sources.iter().map(|source| source.path.as_str()).collect();
+
+        if comparison.might_have_changed(&paths) {
+            2 * sources.len() as u64
+        } else {
+            0
+        }
+    }
+
+    /// Only fetches sources if `comparison` says one of them changed, unless its files are likely
+    /// incomplete.
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        comparison: &Comparison,
+    ) -> anyhow::Result<Self> {
+        let sources = &platform.definition().flag_sources;
+
+        let paths: Vec<_> = sources.iter().map(|source| source.path.as_str()).collect();
+
+        if !comparison.might_have_changed(&paths) {
+            tracing::trace!("feature flag sources didn't change, skipping");
+            return Ok(Self::default());
+        }
+
+        let (_, old_flags) = Self::get_flags(transport, platform, old_tag, sources).await?;
+        let (source, new_flags) = Self::get_flags(transport, platform, new_tag, sources).await?;
+
+        let changes = FlagChange::between(&old_flags, &new_flags);
+        tracing::debug!(changes.len = changes.len());
+
+        Ok(Self { source, changes })
+    }
+
+    /// Uses the first of `sources` that exists at `tag`, or no flags if none do.
+    async fn get_flags(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        sources: &[FlagSource],
+    ) -> anyhow::Result<(Option<FlagSource>, Flags)> {
+        for source in sources {
+            let text =
+                github::get_file_content_if_exists(transport, platform, &tag.name, &source.path)
+                    .await
+                    .with_context(|| format!("couldn't get {} at {}", source.path, tag.name))?;
+
+            if let Some(text) = text {
+                return Ok((Some(source.clone()), source.parse(&text)));
+            }
+        }
+
+        Ok((None, Flags::new()))
+    }
+
+    /// A collapsed section listing at most `max_items` changed flags, or an empty string if there
+    /// are none.
+    pub fn markdown_text(
+        &self,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        max_items: usize,
+    ) -> String {
+        let Some(source) = self.source.as_ref().filter(|_| !self.changes.is_empty()) else {
+            return String::new();
+        };
+
+        let url =
+            platform.github_comparison_url(&old_tag.name, &new_tag.name, Some(&source.path), false);
+
+        let changes = utils::markdown_list(&self.changes, max_items);
+
+        format!(
+            "[details=\"Feature flags changed\"]\n[quote]\n#### [`{}`]({url})\n{changes}\n[/quote]\n[/details]\n",
+            source.path
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use serde_json::json;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    fn flags(flags: &[(&str, &str)]) -> Flags {
+        flags
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn comparison(files: &[&str]) -> Comparison {
+        serde_json::from_value(json!({
+            "total_commits": 0,
+            "commits": [],
+            "files": files.iter().map(|file| json!({ "filename": file })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test_case(Kotlin, r#"
+  @JvmStatic
+  @get:JvmName("internalUser")
+  val internalUser: Boolean by remoteBoolean(
+    key = "android.internalUser",
+    defaultValue = false,
+    hotSwappable = true
+  )
+
+  val maxAttachmentCount: Int by remoteInt(key = "android.attachments.maxCount", defaultValue = 32, hotSwappable = true)
+
+  private val disabledCountries: String by remoteString(
+    key = "android.disabledCountries",
+    defaultValue = "",
+    hotSwappable = true
+  )
+"#, &[("android.attachments.maxCount", "32"), ("android.disabledCountries", "\"\""), ("android.internalUser", "false")]; "kotlin remote values")]
+    #[test_case(Kotlin, r#"
+  private const val PAYMENTS_KILL_SWITCH = "android.payments.kill"
+  private const val GROUPS_V2_RECOMMENDED_LIMIT = "global.groupsv2.maxGroupSize"
+"#, &[("android.payments.kill", ""), ("global.groupsv2.maxGroupSize", "")]; "kotlin constants")]
+    #[test_case(TypeScript, "
+export type ConfigKeyType =
+  | 'desktop.announcementGroup'
+  | 'desktop.internalUser'
+  | 'global.attachments.maxBytes';
+", &[("desktop.announcementGroup", ""), ("desktop.internalUser", ""), ("global.attachments.maxBytes", "")]; "typescript")]
+    #[test_case(Swift, "
+public enum FeatureFlags {
+    public static let phoneNumberSharing = build.includes(.internal)
+    public static let usernames: Bool = true
+
+    public static var isPrerelease: Bool {
+        build.includes(.beta)
+    }
+}
+", &[("phoneNumberSharing", "build.includes(.internal)"), ("usernames", "true")]; "swift")]
+    fn parse(kind: FlagSourceKind, text: &str, result: &[(&str, &str)]) {
+        let source = FlagSource::new("", kind);
+        assert_eq!(source.parse(text), flags(result));
+    }
+
+    #[test]
+    fn between() {
+        let changes = FlagChange::between(
+            &flags(&[("a", "true"), ("b", "false"), ("c", "")]),
+            &flags(&[("b", "true"), ("c", ""), ("d", "1"), ("e", "")]),
+        );
+
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "Removed `a`",
+                "Changed `b` `false` → `true`",
+                "Added `d` = `1`",
+                "Added `e`",
+            ]
+        );
+    }
+
+    #[test]
+    fn get() {
+        let source = "ts/RemoteConfig.ts";
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &format!(
+                    "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.2.0/{source}"
+                ),
+                Response::new(200, String::from("| 'desktop.a'\n| 'desktop.b'")),
+            )
+            .with_response(
+                Method::Get,
+                &format!(
+                    "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.3.0/{source}"
+                ),
+                Response::new(200, String::from("| 'desktop.b'\n| 'desktop.c'")),
+            );
+
+        let old_tag = Tag::new("v1.2.0");
+        let new_tag = Tag::new("v1.3.0");
+
+        let changes = utils::block_on(FeatureFlagChanges::get(
+            &transport,
+            Desktop,
+            &old_tag,
+            &new_tag,
+            &comparison(&[source]),
+        ))
+        .unwrap();
+
+        assert_str_eq!(
+            changes.markdown_text(Desktop, &old_tag, &new_tag, usize::MAX),
+            "[details=\"Feature flags changed\"]
+[quote]
+#### [`ts/RemoteConfig.ts`](//github.com/signalapp/Signal-Desktop/compare/v1.2.0..v1.3.0#diff-81b9d2a2ee9da86accb14027d81a1494a56baaaf3b277690e7cb07c8422d693e)
+- Removed `desktop.a`
+- Added `desktop.c`
+[/quote]
+[/details]
+"
+        );
+    }
+
+    #[test]
+    fn get_falls_back_to_older_source() {
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-iOS/7.0.0.1/SignalServiceKit/Util/FeatureFlags.swift",
+                Response::new(404, String::from("404: Not Found")),
+            )
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-iOS/7.0.0.1/SignalServiceKit/src/Util/FeatureFlags.swift",
+                Response::new(200, String::from("    public static let usernames = false")),
+            )
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-iOS/7.0.0.2/SignalServiceKit/Util/FeatureFlags.swift",
+                Response::new(200, String::from("    public static let usernames = true")),
+            );
+
+        let changes = utils::block_on(FeatureFlagChanges::get(
+            &transport,
+            Ios,
+            &Tag::new("7.0.0.1"),
+            &Tag::new("7.0.0.2"),
+            &comparison(&[
+                "SignalServiceKit/src/Util/FeatureFlags.swift",
+                "SignalServiceKit/Util/FeatureFlags.swift",
+            ]),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            changes.source.as_ref(),
+            Ios.definition().flag_sources.first()
+        );
+        assert_eq!(
+            changes.changes,
+            [FlagChange::Changed {
+                name: String::from("usernames"),
+                old_value: String::from("false"),
+                new_value: String::from("true"),
+            }]
+        );
+    }
+
+    #[test]
+    fn get_skips_unchanged_sources() {
+        let transport = FakeTransport::new();
+
+        let changes = utils::block_on(FeatureFlagChanges::get(
+            &transport,
+            Android,
+            &Tag::new("v7.0.0"),
+            &Tag::new("v7.0.1"),
+            &comparison(&["app/build.gradle.kts"]),
+        ))
+        .unwrap();
+
+        assert_eq!(changes, FeatureFlagChanges::default());
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -1,42 +1,40 @@
-use anyhow::{anyhow, bail, Context};
+use anyhow::{bail, Context};
 use semver::Version;
 use serde::de::DeserializeOwned;
-use worker::{Fetch, Method, Url};
+use worker::{Method, Url};
 
 use crate::{
-    network::{self, ContentType},
+    network::{self, ContentType, Transport},
     platform::Platform,
 };
 
+mod rate_limit;
 mod types;
+
+pub use rate_limit::*;
 pub use types::*;
 
 pub async fn get_tags_to_post(
+    transport: &dyn Transport,
     last_posted_tag: Tag,
     platform: Platform,
 ) -> anyhow::Result<Vec<(Tag, Version)>> {
     tracing::debug!(?last_posted_tag, %platform, "getting tags for platform from GitHub until last_posted_tag is found");
 
     let enough_tags = get_paginated_response(
+        transport,
         &platform.github_api_tags_url(),
         vec![],
         |target, source| target.append(source),
         |result| result.contains(&last_posted_tag),
+        true,
     )
     .await
     .context("could not fetch tags from GitHub")?;
 
     tracing::trace!(enough_tags.len = ?enough_tags.len());
 
-    let mut tags: Vec<(Tag, Version)> = enough_tags
-        .iter()
-        .filter_map(|tag| tag.to_version().ok().map(|version| (tag.clone(), version)))
-        .filter(|(_, version)| platform.should_post_version(version))
-        .collect();
-
-    tracing::trace!(tags.len = ?tags.len());
-
-    tags.sort_unstable_by(|(_, lhs), (_, rhs)| lhs.cmp(rhs));
+    let tags = sorted_postable_tags(&enough_tags, platform);
 
     let tags_to_post: Vec<(Tag, Version)> = tags
         .iter()
@@ -49,6 +47,7 @@ pub async fn get_tags_to_post(
 }
 
 pub async fn get_comparison(
+    transport: &dyn Transport,
     platform: Platform,
     old_tag: &str,
     new_tag: &str,
@@ -61,6 +60,7 @@ pub async fn get_comparison(
     let initial_url = platform.github_api_comparison_url(old_tag, new_tag);
 
     let comparison = get_paginated_response(
+        transport,
         &initial_url,
         Comparison {
             total_commits: 0,
@@ -75,6 +75,7 @@ pub async fn get_comparison(
             }
         },
         |_| false,
+        false,
     )
     .await?;
 
@@ -90,12 +91,17 @@ pub async fn get_comparison(
     Ok(comparison)
 }
 
-pub async fn get_commit(platform: Platform, sha: &str) -> anyhow::Result<Commit> {
+pub async fn get_commit(
+    transport: &dyn Transport,
+    platform: Platform,
+    sha: &str,
+) -> anyhow::Result<Commit> {
     tracing::debug!(commit.sha = sha, %platform, "getting commit for platform from GitHub");
 
     let initial_url = platform.github_api_commit_url(sha);
 
     let commit = get_paginated_response(
+        transport,
         &initial_url,
         Commit {
             sha: sha.to_string(),
@@ -112,18 +118,58 @@ pub async fn get_commit(platform: Platform, sha: &str) -> anyhow::Result<Commit>
             }
         },
         |_| false,
+        false,
     )
     .await?;
 
     Ok(commit)
 }
 
+/// The tags on the first page of tags, which are the most recent ones, that would be posted about,
+/// sorted by version.
+pub async fn get_recent_tags(
+    transport: &dyn Transport,
+    platform: Platform,
+) -> anyhow::Result<Vec<(Tag, Version)>> {
+    tracing::debug!(%platform, "getting recent tags for platform from GitHub");
+
+    let recent_tags = get_paginated_response(
+        transport,
+        &platform.github_api_tags_url(),
+        vec![],
+        |target, source| target.append(source),
+        |_| true,
+        true,
+    )
+    .await
+    .context("could not fetch tags from GitHub")?;
+
+    Ok(sorted_postable_tags(&recent_tags, platform))
+}
+
+fn sorted_postable_tags(tags: &[Tag], platform: Platform) -> Vec<(Tag, Version)> {
+    let mut tags: Vec<(Tag, Version)> = tags
+        .iter()
+        .filter_map(|tag| tag.to_version().ok().map(|version| (tag.clone(), version)))
+        .filter(|(_, version)| platform.should_post_version(version))
+        .collect();
+
+    tracing::trace!(tags.len = ?tags.len());
+
+    tags.sort_unstable_by(|(_, lhs), (_, rhs)| lhs.cmp(rhs));
+    tags
+}
+
 /// `merge`: `Fn(&mut target, &mut source)`
+///
+/// `cache_with_etag`: see [`network::Request::cache_with_etag`].
 async fn get_paginated_response<T, F, P>(
+    transport: &dyn Transport,
     initial_url: &str,
     initial_result: T,
     merge: F,
     stop_if_result: P,
+    cache_with_etag: bool,
 ) -> anyhow::Result<T>
 where
     T: DeserializeOwned,
@@ -143,7 +189,7 @@ where
         tracing::trace!(page, url = url_string, "getting page");
 
         let url = Url::parse(&url_string).context("could not parse URL")?;
-        let request = network::create_request(
+        let mut request = network::create_request(
             url,
             Method::Get,
             ContentType::ApplicationJson,
@@ -151,14 +197,14 @@ where
             None,
             None,
         )?;
+        request.cache_with_etag = cache_with_etag;
 
-        let mut response = network::fetch(Fetch::Request(request))
+        let response = network::fetch(transport, request)
             .await
             .context("could not fetch from GitHub")?;
 
-        let mut part: T = network::json_from_response(&mut response)
-            .await
-            .context("could not get JSON for part")?;
+        let mut part: T =
+            network::json_from_response(&response).context("could not get JSON for part")?;
 
         merge(&mut result, &mut part);
 
@@ -169,7 +215,7 @@ where
             break;
         }
 
-        let link_header_string = match response.headers().get("Link").unwrap() {
+        let link_header_string = match response.header("Link") {
             Some(header_string) => header_string,
             None => {
                 tracing::warn!(
@@ -179,7 +225,7 @@ where
             }
         };
 
-        let link_header = parse_link_header::parse_with_rel(&link_header_string)
+        let link_header = parse_link_header::parse_with_rel(link_header_string)
             .context("could not parse `Link` header")?;
 
         match link_header.get("next") {
@@ -198,6 +244,7 @@ where
 }
 
 pub async fn get_file_content(
+    transport: &dyn Transport,
     platform: Platform,
     revision: &str,
     path: &str,
@@ -216,13 +263,155 @@ pub async fn get_file_content(
         None,
     )?;
 
-    let mut response = network::fetch(Fetch::Request(request))
+    let response = network::fetch(transport, request)
         .await
         .context("could not fetch from GitHub")?;
 
-    response
-        .text()
+    Ok(response.body)
+}
+
+/// Paths of the files and directories in the directory at `path`. GitHub lists at most 1000 of them.
+pub async fn get_directory_paths(
+    transport: &dyn Transport,
+    platform: Platform,
+    revision: &str,
+    path: &str,
+) -> anyhow::Result<Vec<String>> {
+    tracing::trace!(?platform, revision, path, "getting directory contents");
+
+    let url = platform.github_api_contents_url(path, revision);
+    let url = Url::parse(&url).context("could not parse URL")?;
+
+    let request = network::create_request(
+        url,
+        Method::Get,
+        ContentType::ApplicationJson,
+        ContentType::ApplicationJson,
+        None,
+        None,
+    )?;
+
+    let response = network::fetch(transport, request)
         .await
-        .map_err(|e| anyhow!(e.to_string()))
-        .context("couldn't get text")
+        .context("could not fetch from GitHub")?;
+
+    if !response.is_success() {
+        bail!(
+            "unexpected status code {} for {path} at {revision}",
+            response.status_code
+        );
+    }
+
+    let entries: Vec<ContentsEntry> =
+        network::json_from_response(&response).context("could not get directory contents")?;
+
+    Ok(entries.into_iter().map(|entry| entry.path).collect())
+}
+
+/// Like [`get_file_content`], but returns `None` if there's no such file at `revision`.
+pub async fn get_file_content_if_exists(
+    transport: &dyn Transport,
+    platform: Platform,
+    revision: &str,
+    path: &str,
+) -> anyhow::Result<Option<String>> {
+    tracing::trace!(
+        ?platform,
+        revision,
+        path,
+        "getting file content if it exists"
+    );
+
+    let url = platform.github_raw_url(revision) + "/" + path;
+    let url = Url::parse(&url).context("could not parse URL")?;
+
+    let request = network::create_request(
+        url,
+        Method::Get,
+        ContentType::ApplicationJson,
+        ContentType::TextPlain,
+        None,
+        None,
+    )?;
+
+    let response = network::fetch(transport, request)
+        .await
+        .context("could not fetch from GitHub")?;
+
+    match response.status_code {
+        404 => Ok(None),
+        _ if response.is_success() => Ok(Some(response.body)),
+        status_code => bail!("unexpected status code {status_code} for {path} at {revision}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    const TAGS_URL: &str = "https://api.github.com/repos/signalapp/Signal-Android/tags";
+
+    fn tags_response(names: &[&str]) -> Response {
+        Response::new(
+            200,
+            json!(names
+                .iter()
+                .map(|name| json!({ "name": name }))
+                .collect::<Vec<_>>())
+            .to_string(),
+        )
+    }
+
+    #[test]
+    fn tags_to_post_across_pages() {
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &format!("{TAGS_URL}?page=1&per_page=100"),
+                tags_response(&["v1.2.6", "v1.2.5.1", "v1.2.5"]).with_header(
+                    "Link",
+                    &format!(r#"<{TAGS_URL}?page=2&per_page=100>; rel="next""#),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                &format!("{TAGS_URL}?page=2&per_page=100"),
+                tags_response(&["v1.2.4", "v1.2.3"]).with_header(
+                    "Link",
+                    &format!(r#"<{TAGS_URL}?page=3&per_page=100>; rel="next""#),
+                ),
+            );
+
+        let tags = utils::block_on(get_tags_to_post(&transport, Tag::new("v1.2.4"), Android))
+            .unwrap()
+            .into_iter()
+            .map(|(tag, _)| tag.name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(tags, vec!["v1.2.4", "v1.2.5", "v1.2.6"]);
+        assert_eq!(
+            transport.requests().len(),
+            2,
+            "should stop once the last posted tag is found"
+        );
+    }
+
+    #[test]
+    fn comparison_error_status() {
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "https://api.github.com/repos/signalapp/Signal-Android/compare/v1.2.3...v1.2.4?page=1&per_page=100",
+            Response::new(502, "Bad Gateway"),
+        );
+
+        assert!(utils::block_on(get_comparison(&transport, Android, "v1.2.3", "v1.2.4")).is_err());
+    }
 }
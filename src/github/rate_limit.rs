@@ -0,0 +1,159 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::{
+    network::{RateLimitHandle, Request, Response, Transport},
+    utils::BoxFuture,
+};
+
+/// GitHub API request budget, as reported in the headers of the latest response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub remaining: u64,
+    /// When the budget is replenished, in seconds since the Unix epoch.
+    pub reset: u64,
+}
+
+impl RateLimit {
+    pub fn from_response(response: &Response) -> Option<Self> {
+        Some(Self {
+            remaining: response.header("X-RateLimit-Remaining")?.parse().ok()?,
+            reset: response.header("X-RateLimit-Reset")?.parse().ok()?,
+        })
+    }
+
+    /// Whether fewer than `needed` requests can be made before the budget is replenished.
+    pub fn is_insufficient(&self, needed: u64, now: u64) -> bool {
+        self.remaining < needed && now < self.reset
+    }
+}
+
+/// Authenticates requests to the GitHub API if a token is available, and keeps track of the
+/// [`RateLimit`] reported in responses to them (see [`Self::rate_limit`]).
+pub struct GitHubTransport<'a> {
+    inner: &'a dyn Transport,
+    /// Like `api.github.com`, see [`crate::config::Config::github_api_domain`].
+    api_domain: Option<String>,
+    token: Option<String>,
+    rate_limit: RateLimitHandle,
+}
+
+impl<'a> GitHubTransport<'a> {
+    pub fn new(
+        inner: &'a dyn Transport,
+        api_domain: Option<String>,
+        token: Option<String>,
+    ) -> Self {
+        Self {
+            inner,
+            api_domain,
+            token,
+            rate_limit: Rc::new(Cell::new(None)),
+        }
+    }
+
+    /// Shares the latest [`RateLimit`], like with [`crate::network::RunContext`].
+    pub fn rate_limit(&self) -> RateLimitHandle {
+        self.rate_limit.clone()
+    }
+}
+
+impl Transport for GitHubTransport<'_> {
+    fn send(&self, mut request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        let is_api_request = request.url.host_str() == self.api_domain.as_deref();
+
+        if let (true, Some(token)) = (is_api_request, &self.token) {
+            request.set_header("Authorization", &format!("Bearer {token}"));
+        }
+
+        Box::pin(async move {
+            let result = self.inner.send(request).await;
+
+            if let (true, Ok(response)) = (is_api_request, &result) {
+                if let Some(rate_limit) = RateLimit::from_response(response) {
+                    tracing::debug!(rate_limit.remaining, rate_limit.reset, "GitHub rate limit");
+                    self.rate_limit.set(Some(rate_limit));
+                }
+            }
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        network::{create_request, ContentType, FakeTransport},
+        utils::block_on,
+    };
+
+    fn get(transport: &dyn Transport, url: &str) {
+        let request = create_request(
+            url.parse().unwrap(),
+            Method::Get,
+            ContentType::ApplicationJson,
+            ContentType::ApplicationJson,
+            None,
+            None,
+        )
+        .unwrap();
+
+        block_on(transport.send(request)).unwrap();
+    }
+
+    #[test]
+    fn authenticates_and_tracks_api_requests() {
+        let api_url = "https://api.github.com/repos/signalapp/Signal-Android/tags";
+        let raw_url = "https://raw.githubusercontent.com/signalapp/Signal-Android/main/README.md";
+
+        let inner = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                api_url,
+                Response::new(200, "[]")
+                    .with_header("X-RateLimit-Remaining", "41")
+                    .with_header("X-RateLimit-Reset", "1700000000"),
+            )
+            .with_response(Method::Get, raw_url, Response::new(200, ""));
+
+        let transport = GitHubTransport::new(
+            &inner,
+            Config::default().github_api_domain(),
+            Some(String::from("token")),
+        );
+        assert_eq!(transport.rate_limit().get(), None);
+
+        get(&transport, api_url);
+        get(&transport, raw_url);
+
+        let requests = inner.requests();
+        assert_eq!(requests[0].header("Authorization"), Some("Bearer token"));
+        assert_eq!(requests[1].header("Authorization"), None);
+
+        assert_eq!(
+            transport.rate_limit().get(),
+            Some(RateLimit {
+                remaining: 41,
+                reset: 1700000000,
+            })
+        );
+    }
+
+    #[test_case(5, 10, 100, true; "low")]
+    #[test_case(10, 10, 100, false; "enough")]
+    #[test_case(5, 10, 200, false; "already reset")]
+    fn is_insufficient(remaining: u64, needed: u64, now: u64, result: bool) {
+        let rate_limit = RateLimit {
+            remaining,
+            reset: 200,
+        };
+
+        assert_eq!(rate_limit.is_insufficient(needed, now), result);
+    }
+}
@@ -26,7 +26,7 @@ impl Tag {
     }
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "cli"))]
 impl Tag {
     pub fn new(name: impl Into<String>) -> Self {
         Self { name: name.into() }
@@ -55,6 +55,17 @@ impl Comparison {
             .as_ref()
             .map(|files| files.len() != Self::GITHUB_API_MAX_FILES)
     }
+
+    /// Whether any of `paths` changed, erring on the side of `true` if `files` is likely
+    /// incomplete.
+    pub fn might_have_changed(&self, paths: &[&str]) -> bool {
+        match (&self.files, self.are_files_likely_complete()) {
+            (Some(files), Some(true)) => files
+                .iter()
+                .any(|file| paths.contains(&file.filename.as_str())),
+            _ => true,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -90,7 +101,6 @@ pub struct File {
     pub filename: String,
 }
 
-#[cfg(test)]
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ContentsEntry {
     pub path: String,
@@ -184,4 +194,25 @@ mod tests {
             }
         );
     }
+
+    #[test_case(Some(vec!["a.txt", "b.txt"]), &["b.txt"], true; "changed")]
+    #[test_case(Some(vec!["a.txt"]), &["b.txt"], false; "unchanged")]
+    #[test_case(None, &["b.txt"], true; "no files")]
+    #[test_case(Some(vec!["a.txt"; Comparison::GITHUB_API_MAX_FILES]), &["b.txt"], true; "likely incomplete files")]
+    fn might_have_changed(files: Option<Vec<&str>>, paths: &[&str], result: bool) {
+        let comparison = Comparison {
+            total_commits: 0,
+            commits: vec![],
+            files: files.map(|files| {
+                files
+                    .into_iter()
+                    .map(|filename| File {
+                        filename: filename.to_string(),
+                    })
+                    .collect()
+            }),
+        };
+
+        assert_eq!(comparison.might_have_changed(paths), result);
+    }
 }
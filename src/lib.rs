@@ -1,8 +1,15 @@
 #![feature(array_windows)]
 
+#[cfg(feature = "cli")]
+pub mod cli;
+mod config;
+mod database_version;
+mod dependency;
 mod discord;
 mod discourse;
+mod dry_run;
 mod env;
+mod feature_flag;
 mod github;
 mod localization;
 mod logging;
@@ -10,42 +17,69 @@ mod markdown;
 mod network;
 mod panic_hook;
 mod platform;
+mod release_notes;
 mod state;
 mod utils;
 
+use std::future::Future;
+
 use anyhow::{bail, Context};
 use chrono::prelude::*;
-use github::Tag;
+use github::{GitHubTransport, Tag};
 use semver::Version;
+use serde::Serialize;
 use subtle::ConstantTimeEq;
 use worker::{event, Env, ScheduleContext, ScheduledEvent};
 
 use crate::{
+    config::Config,
+    database_version::DatabaseVersionChange,
+    dependency::DependencyChanges,
     discourse::PostingOutcome,
+    dry_run::{DryRun, DryRunEnv, DryRunTransport},
     env::EnvExt,
+    feature_flag::FeatureFlagChanges,
     localization::{
-        Completeness, LocalizationChange, LocalizationChangeCollection, LocalizationChanges,
+        Completeness, LocalizationChange, LocalizationChangeCollection,
+        LocalizationChangeRenderMode, LocalizationChanges, SourceStringChanges, StringCounts,
+        TranslationCoverage,
     },
     logging::Logger,
-    platform::{
-        android::BuildConfiguration,
-        Platform::{self, *},
+    network::{
+        EtagCacheTransport, RetryingTransport, RunContext, SubrequestBudgetTransport, Transport,
+        WorkerTransport, DEFAULT_SUBREQUEST_LIMIT,
     },
-    state::{PostInformation, StateController},
+    platform::{builtin::*, BuildConfiguration, HeaderChanges, Platform},
+    release_notes::ReleaseNotes,
+    state::{ChangeReason, PostInformation, StateController},
 };
 
 const POSTING_DELAY_MILLISECONDS: u64 = 5000;
 
+/// Subrequests kept for posting to Discourse and notifying Discord (including about errors) when
+/// deciding how much optional work there's room for.
+const SUBREQUESTS_RESERVED_FOR_POSTING: u64 = 5;
+
 enum Mode {
     MakeNewPostIfPossible,
     EditExistingAndroidPostIfNeeded { latest_available: Tag },
 }
 
+#[derive(Debug)]
+enum StateChange {
+    ClearPendingState,
+    MarkAsPosted { tag: Tag },
+    ResetPostedArchivingMessage,
+    RollBack { entry_id: u64 },
+}
+
 enum PlatformCheckOutcome {
     WaitingForApproval,
     LatestVersionIsAlreadyPosted,
     NewTopicNotFound,
     PostedCommits,
+    DeferredForGitHubRateLimit,
+    InitializedState,
 }
 
 use Mode::*;
@@ -63,67 +97,325 @@ pub async fn fetch(
 
     router
         .get_async("/:token/firebase/:version", |_req, ctx| async move {
-            if let Some(token) = ctx.param("token") {
+            if is_authorized(&ctx) {
                 if let Some(version) = ctx.param("version") {
-                    if token
-                        .as_bytes()
-                        .ct_eq(ctx.env.access_token().unwrap().as_bytes())
-                        .unwrap_u8()
-                        == 1
-                    {
-                        main(
-                            &ctx.env,
-                            EditExistingAndroidPostIfNeeded {
-                                latest_available: Tag::from_exact_version_string(version),
-                            },
-                        )
-                        .await;
-                    }
+                    let _ = main(
+                        &ctx.env,
+                        &WorkerTransport,
+                        EditExistingAndroidPostIfNeeded {
+                            latest_available: Tag::from_exact_version_string(version),
+                        },
+                    )
+                    .await;
                 }
             }
 
             worker::Response::empty()
         })
         .get_async("/:token/run", |_req, ctx| async move {
-            if let Some(token) = ctx.param("token") {
-                if token
-                    .as_bytes()
-                    .ct_eq(ctx.env.access_token().unwrap().as_bytes())
-                    .unwrap_u8()
-                    == 1
-                {
-                    main(&ctx.env, MakeNewPostIfPossible).await;
-                }
+            if is_authorized(&ctx) {
+                let _ = main(&ctx.env, &WorkerTransport, MakeNewPostIfPossible).await;
             }
 
             worker::Response::empty()
         })
+        .get_async("/:token/state", |_req, ctx| async move {
+            admin_route(ctx, |ctx, config| async move {
+                json_response(
+                    StateController::from_env(&ctx.env, config)
+                        .await
+                        .map(|state_controller| state_controller.document().platforms.clone()),
+                )
+            })
+            .await
+        })
+        .put_async("/:token/state", |mut req, ctx| async move {
+            admin_route(ctx, |ctx, config| async move {
+                let states = match req.text().await.map(|text| serde_json::from_str(&text)) {
+                    Ok(Ok(states)) => states,
+                    Ok(Err(error)) => return worker::Response::error(format!("{error}"), 400),
+                    Err(error) => return Err(error),
+                };
+
+                json_response(replace_state(&ctx.env, config, states).await)
+            })
+            .await
+        })
+        .post_async(
+            "/:token/state/:platform/clear-pending",
+            |_req, ctx| async move {
+                change_state_route(ctx, |_| Some(StateChange::ClearPendingState)).await
+            },
+        )
+        .post_async(
+            "/:token/state/:platform/mark-posted/:tag",
+            |_req, ctx| async move {
+                change_state_route(ctx, |ctx| {
+                    ctx.param("tag").map(|tag| StateChange::MarkAsPosted {
+                        tag: Tag { name: tag.clone() },
+                    })
+                })
+                .await
+            },
+        )
+        .post_async(
+            "/:token/state/:platform/reset-archiving-message",
+            |_req, ctx| async move {
+                change_state_route(ctx, |_| Some(StateChange::ResetPostedArchivingMessage)).await
+            },
+        )
+        .get_async(
+            "/:token/preview/:platform/:old/:new",
+            |_req, ctx| async move {
+                admin_route(ctx, |ctx, config| async move {
+                    let Some(platform) = ctx
+                        .param("platform")
+                        .and_then(|platform| Platform::from_state_key(config, platform))
+                    else {
+                        return worker::Response::error("Unknown platform", 404);
+                    };
+
+                    let (Some(old), Some(new)) = (ctx.param("old"), ctx.param("new")) else {
+                        return worker::Response::error("Invalid parameters", 400);
+                    };
+
+                    json_response(
+                        preview_post(
+                            &ctx.env,
+                            &WorkerTransport,
+                            platform,
+                            &Tag { name: old.clone() },
+                            &Tag { name: new.clone() },
+                        )
+                        .await,
+                    )
+                })
+                .await
+            },
+        )
+        .get_async("/:token/coverage/:platform/:tag", |req, ctx| async move {
+            let offset = req
+                .url()?
+                .query_pairs()
+                .find(|(name, _)| name == "offset")
+                .map(|(_, value)| value.parse::<usize>());
+
+            admin_route(ctx, |ctx, config| async move {
+                let Some(platform) = ctx
+                    .param("platform")
+                    .and_then(|platform| Platform::from_state_key(config, platform))
+                else {
+                    return worker::Response::error("Unknown platform", 404);
+                };
+
+                let (Some(tag), Ok(offset)) = (ctx.param("tag"), offset.unwrap_or(Ok(0))) else {
+                    return worker::Response::error("Invalid parameters", 400);
+                };
+
+                json_response(
+                    translation_coverage(
+                        &ctx.env,
+                        &WorkerTransport,
+                        platform,
+                        &Tag { name: tag.clone() },
+                        offset,
+                    )
+                    .await,
+                )
+            })
+            .await
+        })
+        .get_async("/:token/history", |_req, ctx| async move {
+            admin_route(ctx, |ctx, config| async move {
+                json_response(
+                    StateController::from_env(&ctx.env, config)
+                        .await
+                        .map(|state_controller| state_controller.history().to_vec()),
+                )
+            })
+            .await
+        })
+        .post_async(
+            "/:token/history/:platform/:id/rollback",
+            |_req, ctx| async move {
+                change_state_route(ctx, |ctx| {
+                    ctx.param("id")
+                        .and_then(|id| id.parse().ok())
+                        .map(|entry_id| StateChange::RollBack { entry_id })
+                })
+                .await
+            },
+        )
         .run(req, env)
         .await
 }
 
+fn is_authorized<D>(ctx: &worker::RouteContext<D>) -> bool {
+    match (ctx.param("token"), ctx.env.access_token()) {
+        (Some(token), Ok(access_token)) => {
+            token.as_bytes().ct_eq(access_token.as_bytes()).unwrap_u8() == 1
+        }
+        _ => false,
+    }
+}
+
+/// Runs the handler of a route that needs the access token with the configuration, logging to the
+/// console while it runs.
+async fn admin_route<F, Fut>(
+    ctx: worker::RouteContext<()>,
+    handler: F,
+) -> worker::Result<worker::Response>
+where
+    F: FnOnce(worker::RouteContext<()>, &'static Config) -> Fut,
+    Fut: Future<Output = worker::Result<worker::Response>>,
+{
+    if !is_authorized(&ctx) {
+        return worker::Response::error("Unauthorized", 401);
+    }
+
+    let _logger = Logger::new();
+
+    let config = match Config::from_env(&ctx.env).context("invalid configuration") {
+        Ok(config) => config.leak(),
+        Err(error) => return json_response::<()>(Err(error)),
+    };
+
+    handler(ctx, config).await
+}
+
+/// Applies the change described by the route to the platform given in its `:platform` parameter,
+/// responding with the platform's new state.
+async fn change_state_route(
+    ctx: worker::RouteContext<()>,
+    state_change: fn(&worker::RouteContext<()>) -> Option<StateChange>,
+) -> worker::Result<worker::Response> {
+    admin_route(ctx, |ctx, config| async move {
+        let Some(platform) = ctx
+            .param("platform")
+            .and_then(|platform| Platform::from_state_key(config, platform))
+        else {
+            return worker::Response::error("Unknown platform", 404);
+        };
+
+        let Some(state_change) = state_change(&ctx) else {
+            return worker::Response::error("Invalid parameters", 400);
+        };
+
+        json_response(change_state(&ctx.env, platform, state_change).await)
+    })
+    .await
+}
+
+fn json_response<T: Serialize>(result: anyhow::Result<T>) -> worker::Result<worker::Response> {
+    match result {
+        Ok(value) => worker::Response::from_json(&value),
+        Err(error) => {
+            tracing::error!(?error);
+            worker::Response::error(format!("{error:#}"), 500)
+        }
+    }
+}
+
 #[event(scheduled)]
 pub async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
-    main(&env, MakeNewPostIfPossible).await;
+    let _ = main(&env, &WorkerTransport, MakeNewPostIfPossible).await;
 }
 
-async fn main(env: &Env, mode: Mode) {
+/// Runs the bot in the given mode, reporting any error to Discord before returning it.
+async fn main(env: &dyn EnvExt, transport: &dyn Transport, mode: Mode) -> anyhow::Result<()> {
     let logger = Logger::new();
 
-    let result = match mode {
-        MakeNewPostIfPossible => check_all_platforms(env, &logger).await,
-        EditExistingAndroidPostIfNeeded { latest_available } => {
-            edit_existing_android_post_if_needed(env, latest_available).await
+    let is_dry_run = env.is_dry_run().unwrap_or_else(|error| {
+        tracing::warn!(
+            ?error,
+            "couldn't determine whether this is a dry run, assuming it isn't"
+        );
+        false
+    });
+
+    let retry_policy = env.retry_policy().unwrap_or_else(|error| {
+        tracing::warn!(?error, "couldn't get retry policy, using the default one");
+        Default::default()
+    });
+    let subrequest_limit = env.subrequest_limit().unwrap_or_else(|error| {
+        tracing::warn!(
+            ?error,
+            "couldn't get subrequest limit, using the default one"
+        );
+        DEFAULT_SUBREQUEST_LIMIT
+    });
+    let dry_run = DryRun::new();
+    let dry_run_env = DryRunEnv::new(env, dry_run.clone());
+
+    let env: &dyn EnvExt = if is_dry_run {
+        tracing::warn!("dry run; writes will only be recorded");
+        &dry_run_env
+    } else {
+        env
+    };
+
+    let config = Config::from_env(env).map(Config::leak);
+    let subrequest_budget_transport = SubrequestBudgetTransport::new(transport, subrequest_limit);
+    let github_transport = GitHubTransport::new(
+        &subrequest_budget_transport,
+        config
+            .as_ref()
+            .ok()
+            .and_then(|config| config.github_api_domain()),
+        env.github_token(),
+    );
+    let retrying_transport = RetryingTransport::new(&github_transport, retry_policy);
+    let context = RunContext::new(is_dry_run)
+        .with_github_rate_limit(github_transport.rate_limit())
+        .with_subrequest_budget(subrequest_budget_transport.remaining());
+
+    let etag_cache_transport;
+    let transport: &dyn Transport = match env.state_storage() {
+        Ok(storage) => {
+            etag_cache_transport = EtagCacheTransport::new(&retrying_transport, storage);
+            &etag_cache_transport
+        }
+        Err(error) => {
+            tracing::warn!(?error, "couldn't get storage for caching responses");
+            &retrying_transport
         }
     };
 
-    match result {
+    let dry_run_transport = DryRunTransport::new(transport, dry_run.clone());
+    let transport: &dyn Transport = if is_dry_run {
+        &dry_run_transport
+    } else {
+        transport
+    };
+
+    let result = async {
+        let config = config.context("invalid configuration")?;
+
+        match mode {
+            MakeNewPostIfPossible => {
+                check_all_platforms(env, config, transport, &context, &logger).await
+            }
+            EditExistingAndroidPostIfNeeded { latest_available } => {
+                edit_existing_android_post_if_needed(
+                    env,
+                    config,
+                    transport,
+                    &context,
+                    latest_available,
+                )
+                .await
+            }
+        }
+    }
+    .await;
+
+    match &result {
         Err(error) => {
             tracing::error!(?error);
 
             let log = logger.collect_log();
 
-            match discord::send_error_message(env, &error, &log)
+            match discord::send_error_message(env, transport, error, &log)
                 .await
                 .context("could not send error message to Discord")
             {
@@ -133,10 +425,60 @@ async fn main(env: &Env, mode: Mode) {
         }
         Ok(_) => tracing::info!("finished successfully"),
     }
+
+    if is_dry_run {
+        tracing::info!("{}", dry_run.report());
+    }
+
+    result
+}
+
+async fn replace_state(
+    env: &dyn EnvExt,
+    config: &'static Config,
+    states: state::State,
+) -> anyhow::Result<state::State> {
+    tracing::info!("replacing state");
+
+    let mut state_controller = StateController::from_env(env, config).await?;
+    state_controller
+        .set_platform_states(states, ChangeReason::Edited)
+        .await?;
+
+    Ok(state_controller.document().platforms.clone())
+}
+
+/// Changes the state of `platform` as requested by an administrator, returning its new state.
+async fn change_state(
+    env: &dyn EnvExt,
+    platform: Platform,
+    state_change: StateChange,
+) -> anyhow::Result<state::PlatformState> {
+    tracing::info!(%platform, ?state_change, "changing platform state");
+
+    let mut state_controller = StateController::from_env(env, platform.config()).await?;
+
+    match state_change {
+        StateChange::ClearPendingState => state_controller.clear_pending_state(platform).await?,
+        StateChange::MarkAsPosted { tag } => state_controller.mark_as_posted(platform, tag).await?,
+        StateChange::ResetPostedArchivingMessage => {
+            state_controller
+                .reset_posted_archiving_message(platform)
+                .await?
+        }
+        StateChange::RollBack { entry_id } => {
+            state_controller.roll_back(platform, entry_id).await?
+        }
+    }
+
+    Ok(state_controller.platform_state(platform)?.clone())
 }
 
 async fn edit_existing_android_post_if_needed(
-    env: &Env,
+    env: &dyn EnvExt,
+    config: &'static Config,
+    transport: &dyn Transport,
+    context: &RunContext,
     latest_available: Tag,
 ) -> anyhow::Result<()> {
     tracing::debug!(?latest_available, "the god from beyond told us the new latest available version in firebase. let's see what we can do");
@@ -144,7 +486,7 @@ async fn edit_existing_android_post_if_needed(
         .to_version()
         .context("couldn't make version out of new latest available one")?;
 
-    let mut state_controller = state::StateController::from_kv(env).await?;
+    let mut state_controller = state::StateController::from_env(env, config).await?;
     let previous_v = state_controller
         .most_recent_android_firebase_version_tag()
         .to_version()
@@ -153,7 +495,7 @@ async fn edit_existing_android_post_if_needed(
     if latest_available_v > previous_v {
         tracing::debug!("latest_available > previous. good");
 
-        let platform_state = &state_controller.platform_state(Android);
+        let platform_state = &state_controller.platform_state(Android.with_config(config))?;
         tracing::debug!(?platform_state.last_posted_tag);
 
         if platform_state.last_posted_tag == latest_available {
@@ -163,7 +505,8 @@ async fn edit_existing_android_post_if_needed(
                 let discourse_api_key = env.discourse_api_key()?;
 
                 tracing::trace!("getting existing post...");
-                let existing_post = discourse::get_post(id, &discourse_api_key).await?;
+                let existing_post =
+                    discourse::get_post(transport, config, id, &discourse_api_key).await?;
 
                 if let Some(raw) = existing_post.raw {
                     let new_raw = raw.replace(
@@ -171,7 +514,15 @@ async fn edit_existing_android_post_if_needed(
                         Android.availability_notice(true),
                     );
 
-                    discourse::edit_post(id, &discourse_api_key, &new_raw).await?;
+                    discourse::edit_post(
+                        transport,
+                        context,
+                        config,
+                        id,
+                        &discourse_api_key,
+                        &new_raw,
+                    )
+                    .await?;
                     tracing::trace!("probably edited post!");
                 } else {
                     bail!("no raw in post?");
@@ -194,17 +545,31 @@ async fn edit_existing_android_post_if_needed(
     Ok(())
 }
 
-async fn check_all_platforms(env: &Env, logger: &Logger) -> anyhow::Result<()> {
+async fn check_all_platforms(
+    env: &dyn EnvExt,
+    config: &'static Config,
+    transport: &dyn Transport,
+    context: &RunContext,
+    logger: &Logger,
+) -> anyhow::Result<()> {
     let now = DateTime::from(utils::now());
     tracing::debug!("now = {} (seconds: {})", now.to_rfc3339(), now.timestamp());
 
-    let platforms = utils::platforms_order(&env.enabled_platforms()?, now.time())?;
+    let platforms = utils::platforms_order(&env.enabled_platforms(config)?, now.time());
     tracing::debug!(?platforms);
 
-    let mut state_controller = state::StateController::from_kv(env).await?;
+    let mut state_controller = state::StateController::from_env(env, config).await?;
 
     for platform in platforms {
-        let outcome = check_platform(&mut state_controller, env, platform, logger).await?;
+        let outcome = check_platform(
+            &mut state_controller,
+            env,
+            transport,
+            context,
+            platform,
+            logger,
+        )
+        .await?;
 
         match outcome {
             WaitingForApproval => tracing::warn!("outcome: waiting for post approval"),
@@ -212,6 +577,12 @@ async fn check_all_platforms(env: &Env, logger: &Logger) -> anyhow::Result<()> {
                 tracing::info!("outcome: latest version is already posted")
             }
             NewTopicNotFound => tracing::warn!("outcome: no topic found, may be not created yet"),
+            DeferredForGitHubRateLimit => {
+                tracing::warn!("outcome: deferred until the GitHub rate limit resets")
+            }
+            InitializedState => {
+                tracing::warn!("outcome: initialized state, will post newer versions from now on")
+            }
             PostedCommits => {
                 tracing::warn!(%platform, "outcome: already posted for platform and currently doing only one \"commits\" post per invocation, done");
                 break;
@@ -225,12 +596,17 @@ async fn check_all_platforms(env: &Env, logger: &Logger) -> anyhow::Result<()> {
 }
 
 // Use to send the log via Discord (e.g. in case of non-fatal errors).
-async fn notify_for_debugging(env: &Env, logger: &Logger, reason: &str) -> anyhow::Result<()> {
+async fn notify_for_debugging(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    logger: &Logger,
+    reason: &str,
+) -> anyhow::Result<()> {
     tracing::info!("called notify_for_debugging");
 
     let log = logger.collect_log();
 
-    discord::send_misc_message(env, reason, &log)
+    discord::send_misc_message(env, transport, reason, &log)
         .await
         .context("could not send misc message to Discord")?;
 
@@ -241,22 +617,45 @@ async fn notify_for_debugging(env: &Env, logger: &Logger, reason: &str) -> anyho
 
 async fn check_platform(
     state_controller: &mut StateController,
-    env: &Env,
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    context: &RunContext,
     platform: Platform,
     logger: &Logger,
 ) -> anyhow::Result<PlatformCheckOutcome> {
     tracing::debug!(%platform, "checking platform");
 
+    if !state_controller.has_platform_state(platform) {
+        tracing::warn!("no state for platform, initializing it with the latest tag");
+
+        if is_github_budget_insufficient(env, context, platform, 1) {
+            return Ok(DeferredForGitHubRateLimit);
+        }
+
+        let recent_tags = github::get_recent_tags(transport, platform)
+            .await
+            .context("could not obtain recent tags")?;
+        let initial_state = initial_platform_state(&recent_tags)
+            .context("could not find tags to initialize the platform state with")?;
+
+        state_controller
+            .initialize_platform_state(platform, initial_state)
+            .await
+            .context("could not initialize platform state")?;
+
+        return Ok(InitializedState);
+    }
+
     // Check if a pending post exists and has been approved
     if let Some(pending_state) = state_controller
-        .platform_state(platform)
+        .platform_state(platform)?
         .pending_state
         .as_deref()
     {
         tracing::debug!("a post is waiting for approval");
 
         match state_controller
-            .platform_state(platform)
+            .platform_state(platform)?
             .last_post
             .as_ref()
             .map(|post| post.id)
@@ -265,9 +664,10 @@ async fn check_platform(
                 tracing::debug!("checking if there is a reply by the bot to the latest known post");
 
                 let user_id = env.user_id().context("couldn't get user id from env")?;
-                let posts = discourse::get_replies_to_post(last_post_id)
-                    .await
-                    .context("couldn't get replies to latest known post")?;
+                let posts =
+                    discourse::get_replies_to_post(transport, platform.config(), last_post_id)
+                        .await
+                        .context("couldn't get replies to latest known post")?;
 
                 tracing::trace!(?posts);
 
@@ -283,7 +683,7 @@ async fn check_platform(
                     });
 
                     state_controller
-                        .set_platform_state(platform, new_state)
+                        .set_platform_state(platform, new_state, ChangeReason::ApprovalConfirmed)
                         .await
                         .context("could not set platform state after confirming post approval")?;
 
@@ -291,7 +691,8 @@ async fn check_platform(
 
                     // Submit log for potentially helping debug incorrect `reply_to_post_number` on post after an approved post
                     // TODO: remove once issue is resolved
-                    notify_for_debugging(env, logger, "confirmed approval of post").await?;
+                    notify_for_debugging(env, transport, logger, "confirmed approval of post")
+                        .await?;
                 } else {
                     return Ok(WaitingForApproval);
                 }
@@ -300,7 +701,11 @@ async fn check_platform(
                 tracing::warn!("there is no last_post in state; confirming post approval isn't implemented in this state; assuming it is already approved, but with an unknown post ID and number");
 
                 state_controller
-                    .set_platform_state(platform, pending_state.clone())
+                    .set_platform_state(
+                        platform,
+                        pending_state.clone(),
+                        ChangeReason::ApprovalConfirmed,
+                    )
                     .await
                     .context("could not set platform state after assumed post approval")?;
 
@@ -308,16 +713,21 @@ async fn check_platform(
 
                 // Submit log for potentially helping debug incorrect `reply_to_post_number` on post after an approved post
                 // TODO: remove once issue is resolved
-                notify_for_debugging(env, logger, "confirmed approval of post").await?;
+                notify_for_debugging(env, transport, logger, "confirmed approval of post").await?;
             }
         }
     } else {
         tracing::trace!("no post waiting for approval, continuing main logic");
     }
 
+    if is_github_budget_insufficient(env, context, platform, 1) {
+        return Ok(DeferredForGitHubRateLimit);
+    }
+
     let tags_to_post = github::get_tags_to_post(
+        transport,
         state_controller
-            .platform_state(platform)
+            .platform_state(platform)?
             .last_posted_tag
             .clone(),
         platform,
@@ -332,17 +742,21 @@ async fn check_platform(
 
         let discourse_api_key = env.discourse_api_key()?;
 
-        let new_topic_id =
-            discourse::get_topic_id_or_override(env, &discourse_api_key, platform, new_version)
-                .await
-                .context("could not find new_topic_id")?;
+        let new_topic_id = discourse::get_topic_id_or_override(
+            env,
+            transport,
+            &discourse_api_key,
+            platform,
+            new_version,
+        )
+        .await
+        .context("could not find new_topic_id")?;
 
         match new_topic_id {
             Some(new_topic_id) => {
                 tracing::debug!(new_topic_id);
 
-                let same_release = old_version.major == new_version.major
-                    && old_version.minor == new_version.minor;
+                let same_release = is_same_release(old_version, new_version);
                 tracing::debug!(same_release);
 
                 // Post archiving message to old topic if necessary and possible
@@ -350,6 +764,8 @@ async fn check_platform(
                     same_release,
                     state_controller,
                     env,
+                    transport,
+                    context,
                     platform,
                     &discourse_api_key,
                     old_version,
@@ -361,7 +777,7 @@ async fn check_platform(
 
                 let reply_to_post_number = if same_release {
                     state_controller
-                        .platform_state(platform)
+                        .platform_state(platform)?
                         .last_post
                         .as_ref()
                         .map(|post| post.number)
@@ -370,172 +786,53 @@ async fn check_platform(
                 };
                 tracing::debug!(reply_to_post_number);
 
-                let comparison = github::get_comparison(platform, &old_tag.name, &new_tag.name)
-                    .await
-                    .context("could not get build comparison from GitHub")?;
-
-                tracing::trace!(
-                    comparison.total_commits,
-                    comparison.commits.len = comparison.commits.len(),
-                    comparison.files.len = comparison.files.as_ref().map(|files| files.len()),
-                );
-
-                let unfiltered_commits: Vec<markdown::Commit> = comparison
-                    .commits
-                    .iter()
-                    .map(|github_commit| {
-                        markdown::Commit::from_github_commit(platform, github_commit)
-                    })
-                    .collect();
-
-                let unfiltered_commits_len = unfiltered_commits.len();
-                tracing::trace!(unfiltered_commits.len = unfiltered_commits_len);
-
-                let commits: Vec<markdown::Commit> = unfiltered_commits
-                    .into_iter()
-                    .filter(|commit| platform.should_show_commit(commit.full_message()))
-                    .collect();
-
-                let commits_len = commits.len();
-                tracing::trace!(commits.len = commits_len);
-
-                let mut build_localization_changes =
-                    LocalizationChanges::from_comparison(platform, old_tag, new_tag, &comparison);
-
-                if let Completeness::Incomplete = build_localization_changes.completeness {
-                    let localization_change_commits: Vec<_> = commits
-                        .iter()
-                        .filter(|commit| commit.is_likely_localization_change())
-                        .collect();
-
-                    tracing::trace!(?localization_change_commits);
-
-                    if !localization_change_commits.is_empty() {
-                        let mut all_complete = true;
-
-                        for commit in localization_change_commits {
-                            let with_files = github::get_commit(platform, commit.sha()).await?;
-
-                            let mut changes = LocalizationChange::unsorted_changes_from_files(
-                                platform,
-                                &with_files.files,
-                            );
-
-                            tracing::trace!(changes.len = changes.len());
-
-                            build_localization_changes.add_unsorted_changes(&mut changes);
-
-                            let complete = with_files.are_files_likely_complete().unwrap();
-                            tracing::trace!(commit.sha = commit.sha(), files.complete = complete);
-
-                            all_complete &= complete;
-                        }
-
-                        if all_complete {
-                            build_localization_changes.completeness = Completeness::LikelyComplete;
-
-                            tracing::debug!(
-                                ?build_localization_changes.completeness,
-                                "got complete files for all localization change commits"
-                            );
-                        }
-                    }
+                if is_github_budget_insufficient(env, context, platform, 1) {
+                    return Ok(DeferredForGitHubRateLimit);
                 }
 
-                let localization_changes_completeness = build_localization_changes
-                    .completeness
-                    .min(if !same_release {
-                        Completeness::Complete
-                    } else {
-                        state_controller
-                            .platform_state(platform)
-                            .localization_changes_completeness
-                    });
-
-                let (release_localization_changes, last_posted_tag_previous_release) =
-                    if !same_release {
-                        (None, old_tag)
-                    } else {
-                        let last_posted_tag_previous_release = &state_controller
-                            .platform_state(platform)
-                            .last_posted_tag_previous_release;
-
-                        let changes = state_controller
-                            .platform_state(platform)
-                            .localization_changes
-                            .clone();
-
-                        let mut release_localization_changes = LocalizationChanges {
-                            platform,
-                            old_tag: last_posted_tag_previous_release,
-                            new_tag,
-                            completeness: localization_changes_completeness,
-                            unsorted_changes: changes,
-                        };
-
-                        release_localization_changes.add_unsorted_changes(
-                            &mut build_localization_changes.unsorted_changes.clone(),
-                        );
-
-                        (
-                            Some(release_localization_changes),
-                            last_posted_tag_previous_release,
-                        )
-                    };
-
-                tracing::debug!(
-                    ?last_posted_tag_previous_release,
-                    ?release_localization_changes,
-                );
+                let comparison =
+                    github::get_comparison(transport, platform, &old_tag.name, &new_tag.name)
+                        .await
+                        .context("could not get build comparison from GitHub")?;
 
-                let localization_changes = release_localization_changes
-                    .as_ref()
-                    .unwrap_or(&build_localization_changes)
-                    .unsorted_changes
-                    .clone();
+                let needed_requests = estimated_github_requests_for_post(platform, &comparison);
+                if is_github_budget_insufficient(env, context, platform, needed_requests) {
+                    return Ok(DeferredForGitHubRateLimit);
+                }
 
-                let new_build_configuration = if platform == Platform::Android {
-                    match get_android_build_configuration(new_tag).await {
-                        Ok(config) => Some(config),
+                let new_build_configuration =
+                    match BuildConfiguration::get(transport, platform, new_tag).await {
+                        Ok(config) => config,
                         Err(e) => {
                             tracing::error!("couldn't get new build configuration: {e:?}");
                             notify_for_debugging(
                                 env,
+                                transport,
                                 logger,
                                 "couldn't get new build configuration",
                             )
                             .await?;
                             None
                         }
-                    }
-                } else {
-                    None
-                };
-
-                let available = match platform {
-                    Android => {
-                        state_controller.most_recent_android_firebase_version_tag() == new_tag
-                    }
-                    Ios | Desktop | Server => false, // dummy value,
-                };
+                    };
 
-                let post = markdown::Post::new(
+                let post = make_post(
+                    state_controller,
+                    transport,
+                    context,
                     platform,
                     old_tag,
                     new_tag,
+                    same_release,
+                    &comparison,
                     new_build_configuration,
-                    available,
-                    commits,
-                    unfiltered_commits_len,
-                    LocalizationChangeCollection {
-                        build_changes: build_localization_changes,
-                        release_changes: release_localization_changes,
-                    },
-                );
+                )
+                .await?;
 
                 let outcome = post
                     .post(
-                        env.is_dry_run()?,
+                        transport,
+                        context,
                         &discourse_api_key,
                         new_topic_id,
                         reply_to_post_number,
@@ -547,6 +844,7 @@ async fn check_platform(
 
                 let result = discord::notify(
                     env,
+                    transport,
                     &post,
                     Some(new_topic_id),
                     if let PostingOutcome::Posted { number, .. } = outcome {
@@ -565,6 +863,7 @@ async fn check_platform(
                     // Here, ignore if an error occurs
                     let _ = notify_for_debugging(
                         env,
+                        transport,
                         logger,
                         "could not notify discord about new version",
                     )
@@ -572,13 +871,20 @@ async fn check_platform(
                 }
 
                 let final_state = {
+                    let collection = post.localization_change_collection();
+                    let localization_changes = collection
+                        .release_changes
+                        .as_ref()
+                        .unwrap_or(&collection.build_changes);
+
                     let mut new_state = state::PlatformState {
-                        last_posted_tag_previous_release: last_posted_tag_previous_release.clone(),
+                        last_posted_tag_previous_release: localization_changes.old_tag.clone(),
                         last_posted_tag: new_tag.clone(),
                         last_post: None,
                         posted_archiving_message: false,
-                        localization_changes_completeness,
-                        localization_changes,
+                        localization_changes_completeness: localization_changes.completeness,
+                        localization_changes: localization_changes.unsorted_changes.clone(),
+                        localization_string_counts: localization_changes.string_counts.clone(),
                         pending_state: None,
                     };
 
@@ -592,7 +898,8 @@ async fn check_platform(
                             new_state
                         }
                         PostingOutcome::Enqueued => {
-                            let mut final_state = state_controller.platform_state(platform).clone();
+                            let mut final_state =
+                                state_controller.platform_state(platform)?.clone();
                             final_state.pending_state = Some(Box::new(new_state));
                             final_state
                         }
@@ -600,7 +907,7 @@ async fn check_platform(
                 };
 
                 state_controller
-                    .set_platform_state(platform, final_state)
+                    .set_platform_state(platform, final_state, ChangeReason::Posted)
                     .await
                     .context("could not set platform state after posting commits")?;
 
@@ -613,10 +920,74 @@ async fn check_platform(
     }
 }
 
+/// Whether `needed` GitHub API requests would leave fewer than the configured reserve of requests,
+/// in which case `platform` should be left for a later run rather than failing midway.
+fn is_github_budget_insufficient(
+    env: &dyn EnvExt,
+    context: &RunContext,
+    platform: Platform,
+    needed: u64,
+) -> bool {
+    let Some(rate_limit) = context.github_rate_limit() else {
+        return false;
+    };
+
+    let reserve = env.github_min_remaining_requests().unwrap_or_else(|error| {
+        tracing::warn!(?error, "couldn't get GitHub request reserve, using 10");
+        10
+    });
+
+    let now = utils::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let insufficient = rate_limit.is_insufficient(needed + reserve, now);
+
+    if insufficient {
+        tracing::warn!(
+            %platform,
+            rate_limit.remaining,
+            rate_limit.reset,
+            needed,
+            reserve,
+            "not enough GitHub requests left, deferring platform"
+        );
+    }
+
+    insufficient
+}
+
+/// GitHub API requests [`make_post`] makes once `comparison` is known: one for each localization
+/// change commit. Files are fetched from `raw.githubusercontent.com`, which doesn't count towards
+/// the API rate limit.
+fn estimated_github_requests_for_post(platform: Platform, comparison: &github::Comparison) -> u64 {
+    comparison
+        .commits
+        .iter()
+        .map(|github_commit| markdown::Commit::from_github_commit(platform, github_commit))
+        .filter(|commit| commit.is_likely_localization_change())
+        .count() as u64
+}
+
+/// Upper bound of subrequests [`make_post`] makes for the [`markdown::PostSections`], which are
+/// kept out of the budget for optional localization details.
+fn estimated_subrequests_for_sections(platform: Platform, comparison: &github::Comparison) -> u64 {
+    HeaderChanges::estimated_requests(platform, comparison)
+        + DependencyChanges::estimated_requests(platform, comparison)
+        + FeatureFlagChanges::estimated_requests(platform, comparison)
+        + DatabaseVersionChange::estimated_requests(platform, comparison)
+        + SourceStringChanges::estimated_requests(platform, comparison)
+        + ReleaseNotes::estimated_requests(platform, comparison)
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn post_archiving_message_if_necessary(
     same_release: bool,
     state_controller: &mut StateController,
-    env: &Env,
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    context: &RunContext,
     platform: Platform,
     discourse_api_key: &str,
     old_version: &Version,
@@ -632,7 +1003,7 @@ async fn post_archiving_message_if_necessary(
 
     if same_release
         || state_controller
-            .platform_state(platform)
+            .platform_state(platform)?
             .posted_archiving_message
     {
         tracing::trace!("archiving message not necessary");
@@ -641,48 +1012,53 @@ async fn post_archiving_message_if_necessary(
         tracing::trace!("attempting to post archiving message");
     }
 
-    let old_topic_id =
-        discourse::get_topic_id_or_override(env, discourse_api_key, platform, old_version)
-            .await
-            .context("could not find old_topic_id")?;
+    let old_topic_id = discourse::get_topic_id_or_override(
+        env,
+        transport,
+        discourse_api_key,
+        platform,
+        old_version,
+    )
+    .await
+    .context("could not find old_topic_id")?;
 
     match old_topic_id {
         Some(old_topic_id) => {
             tracing::debug!(old_topic_id);
 
-            let markdown_text = discourse::archiving_post_markdown(new_topic_id);
+            let markdown_text = discourse::archiving_post_markdown(platform.config(), new_topic_id);
             tracing::debug!(markdown_text.len = markdown_text.len());
 
-            let result = if !env.is_dry_run()? {
-                discourse::post(
-                    &markdown_text,
-                    discourse_api_key,
-                    old_topic_id,
-                    state_controller
-                        .platform_state(platform)
-                        .last_post
-                        .as_ref()
-                        .map(|post| post.number),
-                )
-                .await
-            } else {
-                tracing::warn!("dry run; not posting to Discourse");
-                Ok(PostingOutcome::Posted { id: 0, number: 0 })
-            };
+            let result = discourse::post(
+                transport,
+                context,
+                platform.config(),
+                &markdown_text,
+                discourse_api_key,
+                old_topic_id,
+                state_controller
+                    .platform_state(platform)?
+                    .last_post
+                    .as_ref()
+                    .map(|post| post.number),
+            )
+            .await;
 
             match result {
                 Ok(outcome) => {
                     tracing::info!(?outcome, "posted archiving message");
 
-                    let mut new_state = state_controller.platform_state(platform).clone();
+                    let mut new_state = state_controller.platform_state(platform)?.clone();
                     new_state.posted_archiving_message = true;
 
                     state_controller
-                        .set_platform_state(platform, new_state)
+                        .set_platform_state(platform, new_state, ChangeReason::ArchivingPosted)
                         .await
                         .context("could not set platform state after posting archiving message")?;
 
-                    utils::delay(POSTING_DELAY_MILLISECONDS).await;
+                    if !context.dry_run {
+                        utils::delay(POSTING_DELAY_MILLISECONDS).await;
+                    }
                 }
                 Err(_) => {
                     tracing::warn!("could not post archiving message to old topic, it is likely already archived; ignoring, will not post archiving message for this release");
@@ -697,11 +1073,710 @@ async fn post_archiving_message_if_necessary(
     Ok(())
 }
 
-async fn get_android_build_configuration(new_tag: &Tag) -> anyhow::Result<BuildConfiguration> {
-    let file = github::get_file_content(Platform::Android, &new_tag.name, "app/build.gradle.kts")
+fn is_same_release(old_version: &Version, new_version: &Version) -> bool {
+    old_version.major == new_version.major && old_version.minor == new_version.minor
+}
+
+/// The latest of `tags` counts as already posted, so that only newer versions get posted about.
+fn initial_platform_state(tags: &[(Tag, Version)]) -> Option<state::PlatformState> {
+    let (last_tag, last_version) = tags.last()?;
+    let (previous_release_tag, _) = tags
+        .iter()
+        .rev()
+        .find(|(_, version)| !is_same_release(version, last_version))?;
+
+    Some(state::PlatformState::new(
+        previous_release_tag.clone(),
+        last_tag.clone(),
+    ))
+}
+
+/// Gathers everything needed for the post about `old_tag...new_tag`, without posting it or
+/// changing the state.
+#[allow(clippy::too_many_arguments)]
+async fn make_post<'a>(
+    state_controller: &'a StateController,
+    transport: &dyn Transport,
+    context: &RunContext,
+    platform: Platform,
+    old_tag: &'a Tag,
+    new_tag: &'a Tag,
+    same_release: bool,
+    comparison: &'a github::Comparison,
+    new_build_configuration: Option<BuildConfiguration>,
+) -> anyhow::Result<markdown::Post<'a>> {
+    tracing::trace!(
+        comparison.total_commits,
+        comparison.commits.len = comparison.commits.len(),
+        comparison.files.len = comparison.files.as_ref().map(|files| files.len()),
+    );
+
+    let unfiltered_commits: Vec<markdown::Commit> = comparison
+        .commits
+        .iter()
+        .map(|github_commit| markdown::Commit::from_github_commit(platform, github_commit))
+        .collect();
+
+    let unfiltered_commits_len = unfiltered_commits.len();
+    tracing::trace!(unfiltered_commits.len = unfiltered_commits_len);
+
+    let commits: Vec<markdown::Commit> = unfiltered_commits
+        .into_iter()
+        .filter(|commit| platform.should_show_commit(commit.full_message()))
+        .collect();
+
+    let commits_len = commits.len();
+    tracing::trace!(commits.len = commits_len);
+
+    // Details that can be left out only get the subrequests that aren't needed for the rest.
+    let reserved_subrequests =
+        estimated_subrequests_for_sections(platform, comparison) + SUBREQUESTS_RESERVED_FOR_POSTING;
+    let spare_subrequests = || {
+        context
+            .subrequests_left()
+            .map(|left| left.saturating_sub(reserved_subrequests))
+    };
+
+    let mut build_localization_changes =
+        LocalizationChanges::from_comparison(platform, old_tag, new_tag, comparison);
+
+    if let Completeness::Incomplete = build_localization_changes.completeness {
+        let localization_change_commits: Vec<_> = commits
+            .iter()
+            .filter(|commit| commit.is_likely_localization_change())
+            .collect();
+
+        tracing::trace!(?localization_change_commits);
+
+        if !localization_change_commits.is_empty() {
+            let mut all_complete = true;
+
+            for commit in localization_change_commits {
+                if spare_subrequests() == Some(0) {
+                    tracing::warn!("not enough subrequests left to get the rest of localization change commits");
+                    all_complete = false;
+                    break;
+                }
+
+                let with_files = github::get_commit(transport, platform, commit.sha()).await?;
+
+                let mut changes =
+                    LocalizationChange::unsorted_changes_from_files(platform, &with_files.files);
+
+                tracing::trace!(changes.len = changes.len());
+
+                build_localization_changes.add_unsorted_changes(&mut changes);
+
+                let complete = with_files.are_files_likely_complete().unwrap();
+                tracing::trace!(commit.sha = commit.sha(), files.complete = complete);
+
+                all_complete &= complete;
+            }
+
+            if all_complete {
+                build_localization_changes.completeness = Completeness::LikelyComplete;
+
+                tracing::debug!(
+                    ?build_localization_changes.completeness,
+                    "got complete files for all localization change commits"
+                );
+            }
+        }
+    }
+
+    build_localization_changes.string_counts = StringCounts::get(
+        transport,
+        platform,
+        old_tag,
+        new_tag,
+        &build_localization_changes.unsorted_changes,
+        spare_subrequests(),
+    )
+    .await;
+
+    if platform.should_show_translation_coverage() {
+        build_localization_changes.coverage = TranslationCoverage::get_for_changes(
+            transport,
+            platform,
+            new_tag,
+            &build_localization_changes.unsorted_changes,
+            spare_subrequests(),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("couldn't get translation coverage: {e:?}");
+            Default::default()
+        });
+    }
+
+    let localization_changes_completeness =
+        build_localization_changes
+            .completeness
+            .min(if !same_release {
+                Completeness::Complete
+            } else {
+                state_controller
+                    .platform_state(platform)?
+                    .localization_changes_completeness
+            });
+
+    let (release_localization_changes, last_posted_tag_previous_release) = if !same_release {
+        (None, old_tag)
+    } else {
+        let last_posted_tag_previous_release = &state_controller
+            .platform_state(platform)?
+            .last_posted_tag_previous_release;
+
+        let changes = state_controller
+            .platform_state(platform)?
+            .localization_changes
+            .clone();
+
+        let string_counts = state_controller
+            .platform_state(platform)?
+            .localization_string_counts
+            .clone();
+
+        let mut release_localization_changes = LocalizationChanges {
+            platform,
+            old_tag: last_posted_tag_previous_release,
+            new_tag,
+            completeness: localization_changes_completeness,
+            unsorted_changes: changes,
+            string_counts,
+            coverage: build_localization_changes.coverage.clone(),
+        };
+
+        release_localization_changes
+            .add_unsorted_changes(&mut build_localization_changes.unsorted_changes.clone());
+        release_localization_changes.add_string_counts(&build_localization_changes.string_counts);
+
+        (
+            Some(release_localization_changes),
+            last_posted_tag_previous_release,
+        )
+    };
+
+    tracing::debug!(
+        ?last_posted_tag_previous_release,
+        ?release_localization_changes,
+    );
+
+    let available = if platform == Android {
+        state_controller.most_recent_android_firebase_version_tag() == new_tag
+    } else {
+        false // dummy value
+    };
+
+    let dependency_changes =
+        DependencyChanges::get(transport, platform, old_tag, new_tag, comparison)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("couldn't get dependency changes: {e:?}");
+                DependencyChanges::default()
+            });
+
+    let feature_flag_changes =
+        FeatureFlagChanges::get(transport, platform, old_tag, new_tag, comparison)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("couldn't get feature flag changes: {e:?}");
+                FeatureFlagChanges::default()
+            });
+
+    let database_version_change =
+        DatabaseVersionChange::get(transport, platform, old_tag, new_tag, comparison)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("couldn't get database version change: {e:?}");
+                None
+            });
+
+    let source_string_changes =
+        SourceStringChanges::get(transport, platform, old_tag, new_tag, comparison)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("couldn't get source string changes: {e:?}");
+                SourceStringChanges::default()
+            });
+
+    let release_notes =
+        ReleaseNotes::get_if_changed(transport, platform, old_tag, new_tag, comparison)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("couldn't get release notes: {e:?}");
+                None
+            });
+
+    let header_changes = HeaderChanges::get(
+        transport,
+        platform,
+        old_tag,
+        new_tag,
+        comparison,
+        new_build_configuration.as_ref(),
+    )
+    .await
+    .unwrap_or_else(|e| {
+        tracing::error!("couldn't get header changes: {e:?}");
+        HeaderChanges::default()
+    });
+
+    Ok(markdown::Post::new(
+        platform,
+        old_tag,
+        new_tag,
+        new_build_configuration,
+        available,
+        markdown::PostSections {
+            database_version_change,
+            header_changes,
+            release_notes,
+            dependency_changes,
+            feature_flag_changes,
+            source_string_changes,
+        },
+        commits,
+        unfiltered_commits_len,
+        LocalizationChangeCollection {
+            build_changes: build_localization_changes,
+            release_changes: release_localization_changes,
+        },
+    ))
+}
+
+/// What the bot would post about a pair of tags.
+#[derive(Debug, Serialize)]
+struct Preview {
+    markdown: String,
+    section_render_mode: markdown::SectionRenderMode,
+    render_mode: LocalizationChangeRenderMode,
+    discord_message: serde_json::Value,
+}
+
+/// Renders the post that would be made about `old_tag...new_tag`, using the same logic as
+/// [`check_platform`], but without posting anything or changing the state.
+async fn preview_post(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    platform: Platform,
+    old_tag: &Tag,
+    new_tag: &Tag,
+) -> anyhow::Result<Preview> {
+    tracing::debug!(%platform, ?old_tag, ?new_tag, "previewing post");
+
+    let subrequest_budget_transport =
+        SubrequestBudgetTransport::new(transport, env.subrequest_limit()?);
+    let transport = &GitHubTransport::new(
+        &subrequest_budget_transport,
+        platform.config().github_api_domain(),
+        env.github_token(),
+    );
+    let context = RunContext::default()
+        .with_github_rate_limit(transport.rate_limit())
+        .with_subrequest_budget(subrequest_budget_transport.remaining());
+
+    let state_controller = StateController::from_env(env, platform.config()).await?;
+
+    let old_version = old_tag.to_version().context("invalid old tag")?;
+    let new_version = new_tag.to_version().context("invalid new tag")?;
+    let same_release = is_same_release(&old_version, &new_version);
+
+    let discourse_api_key = env.discourse_api_key()?;
+    let topic_id = discourse::get_topic_id_or_override(
+        env,
+        transport,
+        &discourse_api_key,
+        platform,
+        &new_version,
+    )
+    .await
+    .context("could not find topic id")?;
+
+    let comparison = github::get_comparison(transport, platform, &old_tag.name, &new_tag.name)
+        .await
+        .context("could not get build comparison from GitHub")?;
+
+    let new_build_configuration = BuildConfiguration::get(transport, platform, new_tag)
         .await
-        .context("couldn't get app/build.gradle.kts file content")?;
+        .unwrap_or_else(|e| {
+            tracing::error!("couldn't get new build configuration: {e:?}");
+            None
+        });
+
+    let post = make_post(
+        &state_controller,
+        transport,
+        &context,
+        platform,
+        old_tag,
+        new_tag,
+        same_release,
+        &comparison,
+        new_build_configuration,
+    )
+    .await?;
+
+    let (markdown, section_render_mode, render_mode) = post.markdown()?;
+
+    let role = discord::mention_role(env, platform)?;
+    let discord_message = discord::update_message(&post, &role, topic_id, None)?;
+
+    Ok(Preview {
+        markdown,
+        section_render_mode,
+        render_mode,
+        discord_message,
+    })
+}
+
+/// How much of the platform's English strings are translated into each language at `tag`,
+/// starting from the language at `offset` (see [`TranslationCoverage::next_offset`]).
+async fn translation_coverage(
+    env: &dyn EnvExt,
+    transport: &dyn Transport,
+    platform: Platform,
+    tag: &Tag,
+    offset: usize,
+) -> anyhow::Result<TranslationCoverage> {
+    tracing::debug!(%platform, ?tag, offset, "getting translation coverage");
+
+    let subrequest_budget_transport =
+        SubrequestBudgetTransport::new(transport, env.subrequest_limit()?);
+    let transport = &GitHubTransport::new(
+        &subrequest_budget_transport,
+        platform.config().github_api_domain(),
+        env.github_token(),
+    );
+    let context = RunContext::default()
+        .with_github_rate_limit(transport.rate_limit())
+        .with_subrequest_budget(subrequest_budget_transport.remaining());
+
+    TranslationCoverage::get(transport, &context, platform, tag, offset).await
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        env::TestEnv,
+        network::{FakeTransport, Response},
+        state::MemoryStorage,
+    };
+
+    const STATE: &str = r#"{
+        "android": { "last_posted_tag_previous_release": { "name": "v1.2.3" }, "last_posted_tag": { "name": "v1.3.0" } },
+        "ios": { "last_posted_tag_previous_release": { "name": "1.2.0.4-beta" }, "last_posted_tag": { "name": "1.3.0.4-beta" } },
+        "desktop": { "last_posted_tag_previous_release": { "name": "v1.2.0-beta.1" }, "last_posted_tag": { "name": "v1.3.0-beta.1" } },
+        "server": { "last_posted_tag_previous_release": { "name": "v11.99.0" }, "last_posted_tag": { "name": "v12.6.0" } },
+        "libsignal": { "last_posted_tag_previous_release": { "name": "v0.51.0" }, "last_posted_tag": { "name": "v0.52.3" } }
+    }"#;
+
+    fn desktop_comparison_response() -> Response {
+        let comparison = json!({
+            "total_commits": 2,
+            "commits": [
+                { "sha": "a".repeat(40), "commit": { "message": "Fix a bug." } },
+                { "sha": "b".repeat(40), "commit": { "message": "Bump version to 1.3.0-beta.2" } },
+            ],
+            "files": [{ "filename": "_locales/de/messages.json" }],
+        });
+
+        Response::new(200, comparison.to_string())
+    }
+
+    #[test]
+    fn preview_has_no_side_effects() {
+        let storage = MemoryStorage::new().with_value("state", STATE);
+        let env = TestEnv::new(storage.clone())
+            .with_binding("DISCOURSE_API_KEY", "key")
+            .with_binding("TOPIC_ID_OVERRIDE", "123")
+            .with_binding("DISCORD_UPDATES_MENTION_ROLE", "456");
+
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "https://api.github.com/repos/signalapp/Signal-Desktop/compare/v1.3.0-beta.1...v1.3.0-beta.2?page=1&per_page=100",
+            desktop_comparison_response(),
+        );
+
+        let preview = utils::block_on(preview_post(
+            &env,
+            &transport,
+            Desktop,
+            &Tag::new("v1.3.0-beta.1"),
+            &Tag::new("v1.3.0-beta.2"),
+        ))
+        .unwrap();
+
+        assert!(preview.markdown.starts_with("## New Version: 1.3.0-beta.2"));
+        assert!(preview.markdown.contains("Fix a bug."));
+        assert!(matches!(
+            preview.section_render_mode,
+            markdown::SectionRenderMode::Full
+        ));
+        assert!(matches!(
+            preview.render_mode,
+            LocalizationChangeRenderMode::Full
+        ));
+        assert_eq!(
+            preview.discord_message["embeds"][0]["title"],
+            "Desktop v1.3.0-beta.2"
+        );
+        assert_eq!(
+            preview.discord_message["embeds"][0]["url"],
+            utils::discourse_topic_url(&Config::default(), 123)
+        );
+
+        assert_eq!(
+            transport.requests().len(),
+            transport.requests_with_method(Method::Get).len()
+        );
+        assert_eq!(storage.value("state").as_deref(), Some(STATE));
+    }
+
+    #[test]
+    fn preview_libsignal_uses_its_topic_and_role() {
+        let storage = MemoryStorage::new().with_value("state", STATE);
+        let env = TestEnv::new(storage)
+            .with_binding("DISCOURSE_API_KEY", "key")
+            .with_binding("TOPIC_ID_OVERRIDE", "")
+            .with_binding("TOPIC_ID_FOR_LIBSIGNAL_UPDATES", "77")
+            .with_binding("DISCORD_LIBSIGNAL_UPDATES_MENTION_ROLE", "789");
+
+        let comparison = json!({
+            "total_commits": 1,
+            "commits": [{ "sha": "a".repeat(40), "commit": { "message": "Update strings for errors" } }],
+            "files": [],
+        });
+
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                "https://community.signalusers.org/t/77.json",
+                Response::new(
+                    200,
+                    json!({
+                        "post_stream": {
+                            "posts": [{ "id": 1, "topic_id": 77, "post_number": 1, "user_id": 2 }]
+                        }
+                    })
+                    .to_string(),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                "https://api.github.com/repos/signalapp/libsignal/compare/v0.52.3...v0.53.0?page=1&per_page=100",
+                Response::new(200, comparison.to_string()),
+            );
+
+        let preview = utils::block_on(preview_post(
+            &env,
+            &transport,
+            Libsignal,
+            &Tag::new("v0.52.3"),
+            &Tag::new("v0.53.0"),
+        ))
+        .unwrap();
+
+        assert!(preview.markdown.starts_with("## New Version: 0.53.0"));
+        assert!(!preview.markdown.contains("Localization changes"));
+        assert!(preview.discord_message["content"]
+            .as_str()
+            .unwrap()
+            .contains("<@&789>"));
+        assert_eq!(
+            preview.discord_message["embeds"][0]["url"],
+            utils::discourse_topic_url(&Config::default(), 77)
+        );
+        assert_eq!(transport.requests().len(), 2, "should not get commits");
+    }
+
+    #[test]
+    fn preview_uses_configured_instances() {
+        let storage = MemoryStorage::new().with_value("state", STATE);
+        let env = TestEnv::new(storage)
+            .with_binding("DISCOURSE_API_KEY", "key")
+            .with_binding("TOPIC_ID_OVERRIDE", "123")
+            .with_binding("DISCORD_UPDATES_MENTION_ROLE", "456")
+            .with_binding("DISCOURSE_BASE_URL", "http://localhost:8080")
+            .with_binding("GITHUB_BASE_URL", "http://localhost:8081")
+            .with_binding("GITHUB_API_BASE_URL", "http://localhost:8081/api")
+            .with_binding(
+                "GITHUB_REPOSITORIES",
+                r#"{ "desktop": "me/Signal-Desktop" }"#,
+            );
+
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "http://localhost:8081/api/repos/me/Signal-Desktop/compare/v1.3.0-beta.1...v1.3.0-beta.2?page=1&per_page=100",
+            desktop_comparison_response(),
+        );
+
+        let config = Config::from_env(&env).unwrap().leak();
+
+        let preview = utils::block_on(preview_post(
+            &env,
+            &transport,
+            Desktop.with_config(config),
+            &Tag::new("v1.3.0-beta.1"),
+            &Tag::new("v1.3.0-beta.2"),
+        ))
+        .unwrap();
+
+        assert!(preview
+            .markdown
+            .contains("(//localhost:8081/me/Signal-Desktop/commit/"));
+        assert_eq!(
+            preview.discord_message["embeds"][0]["url"],
+            "http://localhost:8080/t/123"
+        );
+    }
+
+    #[test]
+    fn dry_run_only_reads() {
+        let storage = MemoryStorage::new().with_value("state", STATE);
+        let env = TestEnv::new(storage.clone())
+            .with_binding("DRY_RUN", "true")
+            .with_binding("RETRY_MAX_ATTEMPTS", "1")
+            .with_binding("RETRY_INITIAL_DELAY_MS", "0")
+            .with_binding("ENABLED_PLATFORMS", "desktop")
+            .with_binding("DISCOURSE_API_KEY", "key")
+            .with_binding("TOPIC_ID_OVERRIDE", "123")
+            .with_binding("DISCORD_UPDATES_MENTION_ROLE", "456")
+            .with_binding(
+                "DISCORD_WEBHOOK_URL_UPDATES",
+                "https://discord.com/api/webhooks/1/token",
+            );
+
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                "https://api.github.com/repos/signalapp/Signal-Desktop/tags?page=1&per_page=100",
+                Response::new(
+                    200,
+                    json!([{ "name": "v1.3.0-beta.2" }, { "name": "v1.3.0-beta.1" }]).to_string(),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                "https://api.github.com/repos/signalapp/Signal-Desktop/compare/v1.3.0-beta.1...v1.3.0-beta.2?page=1&per_page=100",
+                desktop_comparison_response(),
+            )
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.3.0-beta.1/_locales/de/messages.json",
+                Response::new(200, json!({ "a": { "messageformat": "A" } }).to_string()),
+            )
+            .with_response(
+                Method::Get,
+                "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.3.0-beta.2/_locales/de/messages.json",
+                Response::new(200, json!({ "a": { "messageformat": "Ah" } }).to_string()),
+            );
+
+        utils::block_on(main(&env, &transport, MakeNewPostIfPossible)).unwrap();
+
+        assert_eq!(
+            transport.requests().len(),
+            transport.requests_with_method(Method::Get).len()
+        );
+        assert_eq!(storage.value("state").as_deref(), Some(STATE));
+    }
+
+    #[test]
+    fn low_github_rate_limit_defers_platform() {
+        let storage = MemoryStorage::new().with_value("state", STATE);
+        let env = TestEnv::new(storage.clone())
+            .with_binding("RETRY_MAX_ATTEMPTS", "1")
+            .with_binding("RETRY_INITIAL_DELAY_MS", "0")
+            .with_binding("ENABLED_PLATFORMS", "desktop")
+            .with_binding("DISCOURSE_API_KEY", "key")
+            .with_binding("TOPIC_ID_OVERRIDE", "123")
+            .with_binding("GITHUB_TOKEN", "token");
+
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "https://api.github.com/repos/signalapp/Signal-Desktop/tags?page=1&per_page=100",
+            Response::new(
+                200,
+                json!([{ "name": "v1.3.0-beta.2" }, { "name": "v1.3.0-beta.1" }]).to_string(),
+            )
+            .with_header("X-RateLimit-Remaining", "5")
+            .with_header("X-RateLimit-Reset", "4102444800"),
+        );
+
+        utils::block_on(main(&env, &transport, MakeNewPostIfPossible)).unwrap();
 
-    BuildConfiguration::from_app_build_gradle_kts(&file)
-        .context("couldn't parse build configuration")
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1, "should not get the comparison");
+        assert_eq!(requests[0].header("Authorization"), Some("Bearer token"));
+        assert_eq!(storage.value("state").as_deref(), Some(STATE));
+    }
+
+    #[test]
+    fn platform_without_state_is_initialized() {
+        let state_without_desktop: serde_json::Value = {
+            let mut state: serde_json::Value = serde_json::from_str(STATE).unwrap();
+            state.as_object_mut().unwrap().remove("desktop");
+            state
+        };
+        let storage = MemoryStorage::new().with_value("state", &state_without_desktop.to_string());
+        let env = TestEnv::new(storage.clone())
+            .with_binding("ENABLED_PLATFORMS", "desktop")
+            .with_binding("DISCOURSE_API_KEY", "key");
+
+        let transport = FakeTransport::new().with_response(
+            Method::Get,
+            "https://api.github.com/repos/signalapp/Signal-Desktop/tags?page=1&per_page=100",
+            Response::new(
+                200,
+                json!([
+                    { "name": "v1.3.0-beta.2" },
+                    { "name": "v1.3.0-beta.1" },
+                    { "name": "v1.2.0-beta.3" },
+                ])
+                .to_string(),
+            ),
+        );
+
+        utils::block_on(main(&env, &transport, MakeNewPostIfPossible)).unwrap();
+
+        assert_eq!(transport.requests().len(), 1, "should not post anything");
+
+        let state_controller =
+            utils::block_on(StateController::from_env(&env, Config::default().leak())).unwrap();
+        assert_eq!(
+            state_controller.platform_state(Desktop).unwrap(),
+            &state::PlatformState::new(Tag::new("v1.2.0-beta.3"), Tag::new("v1.3.0-beta.2"))
+        );
+        assert!(state_controller.history().is_empty());
+    }
+
+    #[test]
+    fn estimated_requests_for_post() {
+        let comparison: github::Comparison = serde_json::from_value(json!({
+            "total_commits": 2,
+            "commits": [
+                { "sha": "a".repeat(40), "commit": { "message": "Update dependencies." } },
+                { "sha": "b".repeat(40), "commit": { "message": "Update translations." } },
+            ],
+            "files": [
+                { "filename": "_locales/de/messages.json" },
+                { "filename": "_locales/en/messages.json" },
+                { "filename": "package.json" },
+                { "filename": "ts/sql/migrations/index.ts" },
+            ],
+        }))
+        .unwrap();
+
+        // Only getting the localization change commit uses the API.
+        assert_eq!(estimated_github_requests_for_post(Desktop, &comparison), 1);
+
+        // 2 for each of the English strings, the Electron version, the manifest, and the database
+        // version.
+        assert_eq!(estimated_subrequests_for_sections(Desktop, &comparison), 8);
+    }
 }
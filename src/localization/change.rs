@@ -7,13 +7,11 @@ use serde::{Deserialize, Serialize};
 use crate::{
     github::{File, Tag},
     localization::{
-        Language,
+        KindStringCounts, Language,
+        LocalizationLayout::{self, *},
         StringsFileKind::{self, *},
     },
-    platform::{
-        Platform::{self, *},
-        ANDROID_DEFAULT_STRINGS_FILENAME, SERVER_STRINGS_FILENAME,
-    },
+    platform::{Platform, ANDROID_DEFAULT_STRINGS_FILENAME, SERVER_STRINGS_FILENAME},
 };
 
 pub type UnsortedChanges = HashMap<Language, HashSet<StringsFileKind>>;
@@ -25,8 +23,8 @@ const IOS_LANGUAGE_CODE_PATTERN: &str = "([a-zA-Z]{2,3}(_[A-Z]{2})?)";
 const IOS_APP_STORE_LANGUAGE_CODE_PATTERN: &str = "([a-zA-Z]{2,3}(-[a-zA-Z]{2,4})?)";
 const DESKTOP_LANGUAGE_CODE_PATTERN: &str = "([a-zA-Z]{2,3}(-[A-Z]{2})?)";
 
-fn regex(platform: Platform, kind: StringsFileKind, pattern: &str) -> Regex {
-    Regex::new(&kind.path(platform, pattern)).unwrap()
+fn regex(layout: LocalizationLayout, kind: StringsFileKind, pattern: &str) -> Regex {
+    Regex::new(&kind.path(layout, pattern)).unwrap()
 }
 
 lazy_static! {
@@ -58,13 +56,19 @@ pub struct LocalizationChange {
 }
 
 impl LocalizationChange {
+    pub fn new(language: Language, kinds: Vec<StringsFileKind>) -> Self {
+        Self { language, kinds }
+    }
+
     pub fn unsorted_changes_from_file_paths(
         platform: Platform,
         file_paths: &[&str],
     ) -> UnsortedChanges {
+        let layout = platform.localization_layout();
+
         let pairs = file_paths.iter().filter_map(|filename| {
-            StringsFileKind::applicable_iter(platform).find_map(move |kind| {
-                let regex = match platform {
+            StringsFileKind::applicable_iter(layout).find_map(move |kind| {
+                let regex = match layout {
                     Android => {
                         if filename == &ANDROID_DEFAULT_STRINGS_FILENAME {
                             return Some((Language::default(), Main));
@@ -87,6 +91,7 @@ impl LocalizationChange {
                             return None;
                         }
                     }
+                    Untracked => return None,
                 };
 
                 regex
@@ -96,7 +101,7 @@ impl LocalizationChange {
                     .find_map(|language_code| {
                         Language::try_from(language_code)
                             .or_else(|e| {
-                                if platform == Server {
+                                if layout == Server {
                                     Ok(Default::default())
                                 } else {
                                     Err(e)
@@ -161,17 +166,40 @@ impl LocalizationChange {
         changes
     }
 
-    pub fn string(&self, platform: Platform, old_tag: &Tag, new_tag: &Tag) -> String {
-        match (platform, &self.kinds[..]) {
+    pub fn language(&self) -> &Language {
+        &self.language
+    }
+
+    pub fn kinds(&self) -> &[StringsFileKind] {
+        &self.kinds
+    }
+
+    /// Kinds that have no entry in `counts` are shown without counts.
+    pub fn string(
+        &self,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        counts: Option<&KindStringCounts>,
+    ) -> String {
+        let counts_string = |kind: &StringsFileKind| {
+            counts
+                .and_then(|counts| counts.get(kind))
+                .map(|counts| format!(" ({counts})"))
+                .unwrap_or_default()
+        };
+
+        match (platform.localization_layout(), &self.kinds[..]) {
             (Android | Desktop | Server, &[Main]) => format!(
-                "[{}]({})",
+                "[{}]({}){}",
                 self.language,
                 platform.github_comparison_url(
                     &old_tag.name,
                     &new_tag.name,
                     Some(&self.file_paths(platform)[0]),
                     false,
-                )
+                ),
+                counts_string(&Main),
             ),
             _ => format!(
                 "{}: {}",
@@ -181,14 +209,15 @@ impl LocalizationChange {
                     .zip(self.file_paths(platform))
                     .map(|(kind, path)| {
                         format!(
-                            "[{}]({})",
+                            "[{}]({}){}",
                             kind,
                             platform.github_comparison_url(
                                 &old_tag.name,
                                 &new_tag.name,
                                 Some(&path),
                                 false,
-                            )
+                            ),
+                            counts_string(kind),
                         )
                     })
                     .collect::<Vec<_>>()
@@ -198,25 +227,28 @@ impl LocalizationChange {
     }
 
     pub fn file_paths(&self, platform: Platform) -> Vec<String> {
+        let layout = platform.localization_layout();
+
         self.kinds
             .iter()
             .map(|kind| {
-                if !kind.applicable_for_platform(platform) {
+                if !kind.applicable_for_layout(layout) {
                     panic!("unexpected strings file for {platform}: {kind:?}")
                 }
 
-                match (platform, kind) {
+                match (layout, kind) {
                     (Android, _) => match self.language.full_code().as_str() {
                         "en" => ANDROID_DEFAULT_STRINGS_FILENAME.to_owned(),
-                        _ => kind.path(platform, &self.language.full_code().replace('-', "-r")),
+                        _ => kind.path(layout, &self.language.full_code().replace('-', "-r")),
                     },
                     (Ios, Main | InfoPlist | PluralAware) => {
-                        kind.path(platform, &self.language.full_code().replace('-', "_"))
+                        kind.path(layout, &self.language.full_code().replace('-', "_"))
                     }
                     (Ios, AppStoreDescription | AppStoreReleaseNotes) | (Desktop, _) => {
-                        kind.path(platform, &self.language.full_code())
+                        kind.path(layout, &self.language.full_code())
                     }
-                    (Server, _) => kind.path(platform, "<irrelevant>"),
+                    (Server, _) => kind.path(layout, "<irrelevant>"),
+                    (Untracked, _) => unreachable!("no strings files are applicable"),
                 }
             })
             .collect()
@@ -232,14 +264,6 @@ impl LocalizationChange {
         }
     }
 
-    pub fn language(&self) -> &Language {
-        &self.language
-    }
-
-    pub fn kinds(&self) -> &[StringsFileKind] {
-        &self.kinds
-    }
-
     pub fn unsorted_changes(sorted_changes: Vec<LocalizationChange>) -> UnsortedChanges {
         sorted_changes
             .into_iter()
@@ -259,28 +283,33 @@ mod tests {
     use test_case::test_case;
 
     use super::*;
+    use crate::{
+        localization::StringCounts,
+        platform::builtin::{Android, Desktop, Ios, Server},
+    };
 
     #[ignore = "online and doesn't actually test"]
     #[test]
     fn online_localization_change_all() {
-        use strum::IntoEnumIterator;
-
-        use crate::{github, platform::Platform};
+        use crate::github;
 
         #[allow(clippy::type_complexity)]
-        let platforms: HashMap<Platform, HashMap<&str, Vec<&str>>> = Platform::iter()
-            .map(|platform| {
-                let mut map = HashMap::new();
-
-                for kind in StringsFileKind::applicable_iter(platform) {
-                    map.entry(kind.path_base(platform))
-                        .or_insert_with(Vec::new)
-                        .push(kind.path_file_name(platform));
-                }
+        let platforms: HashMap<Platform, HashMap<&str, Vec<&str>>> =
+            Platform::iter(crate::config::Config::default().leak())
+                .map(|platform| {
+                    let mut map = HashMap::new();
 
-                (platform, map)
-            })
-            .collect();
+                    let layout = platform.localization_layout();
+
+                    for kind in StringsFileKind::applicable_iter(layout) {
+                        map.entry(kind.path_base(layout))
+                            .or_insert_with(Vec::new)
+                            .push(kind.path_file_name(layout));
+                    }
+
+                    (platform, map)
+                })
+                .collect();
 
         let client = reqwest::blocking::Client::builder()
             .user_agent(crate::network::USER_AGENT)
@@ -290,7 +319,9 @@ mod tests {
         for (platform, pairs) in platforms {
             for (path, filenames) in pairs {
                 let url = format!(
-                    "https://api.github.com/repos/signalapp/Signal-{platform}/contents/{path}"
+                    "{}/repos/{}/contents/{path}",
+                    platform.config().github_api_base_url,
+                    platform.repo_slug()
                 );
 
                 let entries: Vec<github::ContentsEntry> = client
@@ -491,8 +522,31 @@ mod tests {
         };
 
         assert_str_eq!(
-            localization_change.string(platform, &Tag::new("v1.2.3"), &Tag::new("v1.2.4")),
+            localization_change.string(platform, &Tag::new("v1.2.3"), &Tag::new("v1.2.4"), None),
             result
         )
     }
+
+    #[test]
+    fn string_with_counts() {
+        let localization_change = LocalizationChange::test_change("de", vec![Main, InfoPlist]);
+        let counts = KindStringCounts::from([(
+            Main,
+            StringCounts {
+                added: 42,
+                changed: 7,
+                removed: 1,
+            },
+        )]);
+
+        assert_str_eq!(
+            localization_change.string(
+                Ios,
+                &Tag::new("v1.2.3"),
+                &Tag::new("v1.2.4"),
+                Some(&counts)
+            ),
+            "German (`de`): [main](//github.com/signalapp/Signal-iOS/compare/v1.2.3..v1.2.4#diff-ce4e0493fb72519f07dfda7d73e7f4c2d9cb47a13ead332c8710d0879275b985) (+42 / ~7 / -1 strings) • [info](//github.com/signalapp/Signal-iOS/compare/v1.2.3..v1.2.4#diff-b1c31959bca1cf894f2de63e673c73c2221ad150227c0b29318b62d13c2ddc75)"
+        )
+    }
 }
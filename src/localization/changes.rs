@@ -1,23 +1,27 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use crate::{
     github::{Comparison, Tag},
     localization::{
         Completeness::{self, *},
-        LocalizationChange, UnsortedChanges,
+        Language, LanguageCoverage, LocalizationChange, StringCounts, UnsortedChanges,
+        UnsortedStringCounts,
     },
     platform::Platform,
 };
 
 pub const MAX_CHANGES_WITHOUT_DETAILS_TAG: usize = 20;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct LocalizationChanges<'a> {
     pub platform: Platform,
     pub old_tag: &'a Tag,
     pub new_tag: &'a Tag,
     pub completeness: Completeness,
     pub unsorted_changes: UnsortedChanges,
+    pub string_counts: UnsortedStringCounts,
+    /// Only has languages whose coverage is shown.
+    pub coverage: HashMap<Language, LanguageCoverage>,
 }
 
 impl<'a> LocalizationChanges<'a> {
@@ -40,6 +44,8 @@ impl<'a> LocalizationChanges<'a> {
             new_tag,
             completeness: if complete { Complete } else { Incomplete },
             unsorted_changes: changes,
+            string_counts: UnsortedStringCounts::default(),
+            coverage: HashMap::new(),
         }
     }
 
@@ -50,6 +56,10 @@ impl<'a> LocalizationChanges<'a> {
         ]);
     }
 
+    pub fn add_string_counts(&mut self, string_counts: &UnsortedStringCounts) {
+        self.string_counts = StringCounts::merge(vec![&self.string_counts, string_counts]);
+    }
+
     pub fn full_comparison_notice(&self) -> String {
         format!(
             "You can view the full comparison to {} so far [here]({}).",
@@ -66,7 +76,19 @@ impl<'a> LocalizationChanges<'a> {
     fn language_links(&self) -> String {
         LocalizationChange::sorted_changes(self.unsorted_changes.clone())
             .iter()
-            .map(|change| change.string(self.platform, self.old_tag, self.new_tag))
+            .map(|change| {
+                let string = change.string(
+                    self.platform,
+                    self.old_tag,
+                    self.new_tag,
+                    self.string_counts.get(change.language()),
+                );
+
+                match self.coverage.get(change.language()) {
+                    Some(coverage) => format!("{string} — {coverage}"),
+                    None => string,
+                }
+            })
             .collect::<Vec<_>>()
             .join("\n- ")
     }
@@ -116,7 +138,7 @@ mod tests {
     use super::*;
     use crate::{
         localization::{LocalizationChange, StringsFileKind::*},
-        platform::Platform::*,
+        platform::builtin::*,
     };
 
     #[test_case(Android, "v1.2.3", "v1.2.4", Complete, vec![
@@ -215,8 +237,43 @@ For technical reasons, not all languages may be listed below. However, everythin
             new_tag: &new_tag,
             completeness,
             unsorted_changes: LocalizationChange::unsorted_changes(changes),
+            string_counts: UnsortedStringCounts::default(),
+            coverage: HashMap::new(),
         };
 
         assert_str_eq!(changes.to_string(), result);
     }
+
+    #[test]
+    fn to_string_with_coverage() {
+        let old_tag = Tag::new("v1.2.3");
+        let new_tag = Tag::new("v1.2.4");
+        let language = Language::try_from("de").unwrap();
+
+        let changes = LocalizationChanges {
+            platform: Desktop,
+            old_tag: &old_tag,
+            new_tag: &new_tag,
+            completeness: Complete,
+            unsorted_changes: LocalizationChange::unsorted_changes(vec![
+                LocalizationChange::test_change("de", vec![Main]),
+            ]),
+            string_counts: UnsortedStringCounts::default(),
+            coverage: HashMap::from([(
+                language.clone(),
+                LanguageCoverage {
+                    language,
+                    translated: 982,
+                    total: 1000,
+                    percentage: 98.2,
+                },
+            )]),
+        };
+
+        assert_str_eq!(
+            changes.to_string(),
+            "#### 1 language changed since 1.2.3:
+- [German (`de`)](//github.com/signalapp/Signal-Desktop/compare/v1.2.3..v1.2.4#diff-537dd75b6b02ee0cbf11183aab34b9842f47db0f5b7fea2a82174860150836e5) — 98.2% translated"
+        );
+    }
 }
@@ -0,0 +1,531 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Serialize;
+
+use super::{
+    Language, LocalizationChange, LocalizationLayout, SourceStringsFile, Strings, StringsFileKind,
+    UnsortedChanges,
+};
+use crate::{
+    github::{self, Tag},
+    network::{RunContext, Transport},
+    platform::Platform,
+};
+
+lazy_static! {
+    static ref ANDROID_UNTRANSLATABLE_REGEX: Regex =
+        Regex::new(r#"<(?:string|plurals) name="([^"]+)"[^>]*\btranslatable="false""#).unwrap();
+}
+
+/// Keys of the default (English) strings of a platform, by file kind. Plural variants count as a
+/// single key, since languages have different plural forms.
+pub type SourceKeys = BTreeMap<StringsFileKind, BTreeSet<String>>;
+
+/// How much of the default (English) strings are translated into a language.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LanguageCoverage {
+    pub language: Language,
+    pub translated: usize,
+    pub total: usize,
+    pub percentage: f64,
+}
+
+impl LanguageCoverage {
+    /// Strings with empty values aren't considered translated.
+    pub fn new(
+        language: Language,
+        source_keys: &SourceKeys,
+        translations: &BTreeMap<StringsFileKind, Strings>,
+    ) -> Self {
+        let total = source_keys.values().map(BTreeSet::len).sum();
+
+        let translated = source_keys
+            .iter()
+            .map(|(kind, keys)| {
+                let translated_keys: BTreeSet<_> = translations
+                    .get(kind)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, value)| !value.trim().is_empty())
+                    .map(|(key, _)| base_key(key))
+                    .collect();
+
+                keys.iter()
+                    .filter(|key| translated_keys.contains(key.as_str()))
+                    .count()
+            })
+            .sum();
+
+        let percentage = match total {
+            0 => 100.0,
+            _ => translated as f64 * 100.0 / total as f64,
+        };
+
+        Self {
+            language,
+            translated,
+            total,
+            percentage,
+        }
+    }
+}
+
+/// Like `98.2% translated`.
+impl fmt::Display for LanguageCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}% translated", self.percentage)
+    }
+}
+
+/// Translation coverage of all languages of a platform at a tag.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TranslationCoverage {
+    pub tag: String,
+    pub languages: Vec<LanguageCoverage>,
+    /// Languages whose translations couldn't be fetched or parsed.
+    pub failed_languages: Vec<Language>,
+    /// Where to continue if languages were left out for lack of subrequests.
+    pub next_offset: Option<usize>,
+}
+
+impl TranslationCoverage {
+    /// Lists the languages with translations at `tag` using the directories of the platform's
+    /// [`StringsFileKind::path`] layout, skipping the first `offset` of them. Languages are left
+    /// out once `context` has no subrequests left for them, to be continued from
+    /// [`Self::next_offset`].
+    pub async fn get(
+        transport: &dyn Transport,
+        context: &RunContext,
+        platform: Platform,
+        tag: &Tag,
+        offset: usize,
+    ) -> anyhow::Result<Self> {
+        let layout = platform.localization_layout();
+        let source_files = SourceStringsFile::for_layout(layout);
+
+        let mut file_paths = Vec::new();
+
+        let path_bases: BTreeSet<_> = source_files
+            .iter()
+            .map(|file| file.kind.path_base(layout))
+            .collect();
+
+        for path_base in path_bases {
+            let directory_paths =
+                github::get_directory_paths(transport, platform, &tag.name, path_base)
+                    .await
+                    .with_context(|| format!("couldn't list {path_base} at {}", tag.name))?;
+
+            for directory_path in directory_paths {
+                for file in source_files
+                    .iter()
+                    .filter(|file| file.kind.path_base(layout) == path_base)
+                {
+                    file_paths.push(format!(
+                        "{directory_path}/{}",
+                        file.kind.path_file_name(layout)
+                    ));
+                }
+            }
+        }
+
+        let file_paths: Vec<_> = file_paths.iter().map(String::as_str).collect();
+        let languages = LocalizationChange::unsorted_changes_from_file_paths(platform, &file_paths);
+
+        let languages: Vec<_> = sorted_languages(&languages)
+            .into_iter()
+            .skip(offset)
+            .collect();
+
+        let source_keys = get_source_keys(transport, platform, tag).await?;
+        let max_requests = context.subrequests_left();
+        let coverage = get_languages(
+            transport,
+            platform,
+            tag,
+            &source_keys,
+            &languages,
+            max_requests,
+        )
+        .await;
+
+        let checked = coverage.languages.len() + coverage.failed_languages.len();
+
+        Ok(Self {
+            tag: tag.name.clone(),
+            languages: coverage.languages.into_values().collect(),
+            failed_languages: coverage.failed_languages,
+            next_offset: (checked < languages.len()).then_some(offset + checked),
+        })
+    }
+
+    /// Coverage of the languages in `unsorted_changes`, for showing next to them in a post. Takes at
+    /// most `max_requests` requests if it's given, leaving out the languages that don't fit.
+    pub async fn get_for_changes(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        unsorted_changes: &UnsortedChanges,
+        max_requests: Option<u64>,
+    ) -> anyhow::Result<HashMap<Language, LanguageCoverage>> {
+        if unsorted_changes
+            .keys()
+            .all(|language| *language == Language::default())
+        {
+            return Ok(HashMap::new());
+        }
+
+        let source_files =
+            SourceStringsFile::for_layout(platform.localization_layout()).len() as u64;
+
+        if max_requests.is_some_and(|max_requests| max_requests <= source_files) {
+            tracing::warn!("not enough subrequests left to get translation coverage");
+            return Ok(HashMap::new());
+        }
+
+        let source_keys = get_source_keys(transport, platform, tag).await?;
+
+        let coverage = get_languages(
+            transport,
+            platform,
+            tag,
+            &source_keys,
+            &sorted_languages(unsorted_changes),
+            max_requests.map(|max_requests| max_requests - source_files),
+        )
+        .await;
+
+        Ok(coverage.languages.into_iter().collect())
+    }
+}
+
+async fn get_source_keys(
+    transport: &dyn Transport,
+    platform: Platform,
+    tag: &Tag,
+) -> anyhow::Result<SourceKeys> {
+    let layout = platform.localization_layout();
+    let mut source_keys = SourceKeys::new();
+
+    for file in SourceStringsFile::for_layout(layout) {
+        let text = github::get_file_content_if_exists(transport, platform, &tag.name, &file.path)
+            .await
+            .with_context(|| format!("couldn't get {} at {}", file.path, tag.name))?;
+
+        let Some(text) = text else {
+            continue;
+        };
+
+        let untranslatable = match layout {
+            LocalizationLayout::Android => android_untranslatable_keys(&text),
+            _ => BTreeSet::new(),
+        };
+
+        let keys = file
+            .parse(layout, &text)?
+            .keys()
+            .map(|key| base_key(key).to_string())
+            .filter(|key| !untranslatable.contains(key))
+            .collect();
+
+        source_keys.insert(file.kind, keys);
+    }
+
+    Ok(source_keys)
+}
+
+/// The languages of `unsorted_changes` except for the default one, in order.
+fn sorted_languages(unsorted_changes: &UnsortedChanges) -> Vec<&Language> {
+    let mut languages: Vec<_> = unsorted_changes
+        .keys()
+        .filter(|language| **language != Language::default())
+        .collect();
+
+    languages.sort_unstable();
+    languages
+}
+
+struct LanguagesCoverage {
+    languages: BTreeMap<Language, LanguageCoverage>,
+    failed_languages: Vec<Language>,
+}
+
+/// Stops before taking more than `max_requests` requests (one per file) if it's given. Languages
+/// whose files couldn't be fetched or parsed are listed as failed instead.
+async fn get_languages(
+    transport: &dyn Transport,
+    platform: Platform,
+    tag: &Tag,
+    source_keys: &SourceKeys,
+    languages: &[&Language],
+    max_requests: Option<u64>,
+) -> LanguagesCoverage {
+    let kinds: Vec<_> = source_keys.keys().copied().collect();
+
+    let mut coverage = LanguagesCoverage {
+        languages: BTreeMap::new(),
+        failed_languages: Vec::new(),
+    };
+    let mut requests = 0;
+
+    for language in languages {
+        if max_requests.is_some_and(|max_requests| requests + kinds.len() as u64 > max_requests) {
+            tracing::warn!(
+                "not enough subrequests left, not getting coverage for the rest of languages"
+            );
+            break;
+        }
+
+        requests += kinds.len() as u64;
+
+        match get_language(transport, platform, tag, source_keys, &kinds, language).await {
+            Ok(language_coverage) => {
+                coverage
+                    .languages
+                    .insert((*language).clone(), language_coverage);
+            }
+            Err(e) => {
+                tracing::warn!("couldn't get translation coverage of {language}: {e:?}");
+                coverage.failed_languages.push((*language).clone());
+            }
+        }
+    }
+
+    coverage
+}
+
+async fn get_language(
+    transport: &dyn Transport,
+    platform: Platform,
+    tag: &Tag,
+    source_keys: &SourceKeys,
+    kinds: &[StringsFileKind],
+    language: &Language,
+) -> anyhow::Result<LanguageCoverage> {
+    let layout = platform.localization_layout();
+    let change = LocalizationChange::new(language.clone(), kinds.to_vec());
+    let mut translations = BTreeMap::new();
+
+    for (kind, path) in kinds.iter().zip(change.file_paths(platform)) {
+        let text = github::get_file_content_if_exists(transport, platform, &tag.name, &path)
+            .await
+            .with_context(|| format!("couldn't get {path} at {}", tag.name))?;
+
+        if let Some(text) = text {
+            let strings = kind
+                .parse(layout, &text)
+                .with_context(|| format!("couldn't parse {path} at {}", tag.name))?;
+
+            translations.insert(*kind, strings);
+        }
+    }
+
+    Ok(LanguageCoverage::new(
+        language.clone(),
+        source_keys,
+        &translations,
+    ))
+}
+
+/// Like `key` for `key[one]`.
+fn base_key(key: &str) -> &str {
+    key.split_once('[').map_or(key, |(base, _)| base)
+}
+
+fn android_untranslatable_keys(text: &str) -> BTreeSet<String> {
+    ANDROID_UNTRANSLATABLE_REGEX
+        .captures_iter(text)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        localization::StringsFileKind::*,
+        network::{FakeTransport, Response, RunContext, SubrequestBudgetTransport},
+        platform::builtin::*,
+        utils,
+    };
+
+    fn strings(strings: &[(&str, &str)]) -> Strings {
+        strings
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn language_coverage() {
+        let source_keys = SourceKeys::from([
+            (Main, BTreeSet::from([String::from("a"), String::from("b")])),
+            (
+                PluralAware,
+                BTreeSet::from([String::from("c"), String::from("d")]),
+            ),
+        ]);
+
+        let translations = BTreeMap::from([
+            (Main, strings(&[("a", "A"), ("b", " "), ("old", "Old")])),
+            (PluralAware, strings(&[("c[other]", "Cs")])),
+        ]);
+
+        let coverage = LanguageCoverage::new(
+            Language::try_from("de").unwrap(),
+            &source_keys,
+            &translations,
+        );
+
+        assert_eq!((coverage.translated, coverage.total), (2, 4));
+        assert_eq!(coverage.to_string(), "50.0% translated");
+    }
+
+    #[test_case(None, 1; "unlimited")]
+    #[test_case(Some(2), 1; "enough requests")]
+    #[test_case(Some(1), 0; "not enough requests")]
+    fn get_for_changes(max_requests: Option<u64>, covered_languages: usize) {
+        let url = |path: &str| {
+            format!("https://raw.githubusercontent.com/signalapp/Signal-Android/v7.0.0/app/src/main/res/{path}/strings.xml")
+        };
+
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &url("values"),
+                Response::new(
+                    200,
+                    String::from(
+                        r#"
+<resources>
+    <string name="app_name" translatable="false">Signal</string>
+    <string name="ok">OK</string>
+    <string name="cancel">Cancel</string>
+    <plurals name="n_groups">
+        <item quantity="one">%1$d group</item>
+        <item quantity="other">%1$d groups</item>
+    </plurals>
+</resources>"#,
+                    ),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                &url("values-ja"),
+                Response::new(
+                    200,
+                    String::from(
+                        r#"
+<resources>
+    <string name="ok">OK</string>
+    <plurals name="n_groups">
+        <item quantity="other">%1$d グループ</item>
+    </plurals>
+</resources>"#,
+                    ),
+                ),
+            );
+
+        let japanese = Language::try_from("ja").unwrap();
+        let unsorted_changes = UnsortedChanges::from([
+            (Language::default(), [Main].into()),
+            (japanese.clone(), [Main].into()),
+        ]);
+
+        let coverage = utils::block_on(TranslationCoverage::get_for_changes(
+            &transport,
+            Android,
+            &Tag::new("v7.0.0"),
+            &unsorted_changes,
+            max_requests,
+        ))
+        .unwrap();
+
+        assert_eq!(coverage.len(), covered_languages);
+        assert_eq!(transport.requests().len(), 2 * covered_languages);
+
+        if let Some(coverage) = coverage.get(&japanese) {
+            assert_eq!((coverage.translated, coverage.total), (2, 3));
+        }
+    }
+
+    #[test_case(0, Some(4), &["de"], &["fr"], Some(2); "first page")]
+    #[test_case(2, None, &["ja"], &[], None; "last page")]
+    fn get(
+        offset: usize,
+        subrequest_limit: Option<u64>,
+        languages: &[&str],
+        failed_languages: &[&str],
+        next_offset: Option<usize>,
+    ) {
+        let url = |language: &str| {
+            format!("https://raw.githubusercontent.com/signalapp/Signal-Desktop/v7.0.0/_locales/{language}/messages.json")
+        };
+        let messages = |value: &str| format!(r#"{{"ok": {{"messageformat": "{value}"}}}}"#);
+
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                "https://api.github.com/repos/signalapp/Signal-Desktop/contents/_locales?ref=v7.0.0",
+                Response::new(
+                    200,
+                    serde_json::json!(["en", "de", "fr", "ja"]
+                        .map(|language| serde_json::json!({ "path": format!("_locales/{language}") })))
+                    .to_string(),
+                ),
+            )
+            .with_response(Method::Get, &url("en"), Response::new(200, messages("OK")))
+            .with_response(Method::Get, &url("de"), Response::new(200, messages("OK")))
+            .with_response(Method::Get, &url("fr"), Response::new(200, String::from("{")))
+            .with_response(Method::Get, &url("ja"), Response::new(200, messages("OK")));
+
+        let coverage = match subrequest_limit {
+            Some(limit) => {
+                let transport = SubrequestBudgetTransport::new(&transport, limit);
+                utils::block_on(TranslationCoverage::get(
+                    &transport,
+                    &RunContext::default().with_subrequest_budget(transport.remaining()),
+                    Desktop,
+                    &Tag::new("v7.0.0"),
+                    offset,
+                ))
+            }
+            None => utils::block_on(TranslationCoverage::get(
+                &transport,
+                &RunContext::default(),
+                Desktop,
+                &Tag::new("v7.0.0"),
+                offset,
+            )),
+        }
+        .unwrap();
+
+        let languages_from = |languages: &[&str]| -> Vec<Language> {
+            languages
+                .iter()
+                .map(|language| Language::try_from(*language).unwrap())
+                .collect()
+        };
+
+        assert_eq!(
+            coverage
+                .languages
+                .into_iter()
+                .map(|coverage| coverage.language)
+                .collect::<Vec<_>>(),
+            languages_from(languages)
+        );
+        assert_eq!(coverage.failed_languages, languages_from(failed_languages));
+        assert_eq!(coverage.next_offset, next_offset);
+    }
+}
@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+/// How a repository's strings files are organized, which determines how changed languages are
+/// found among the changed files.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalizationLayout {
+    Android,
+    Ios,
+    Desktop,
+    Server,
+    /// Localization changes are not tracked.
+    #[default]
+    Untracked,
+}
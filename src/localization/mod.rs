@@ -2,14 +2,22 @@ mod change;
 mod changes;
 mod collection;
 mod completeness;
+mod coverage;
 mod language;
+mod layout;
 mod render_mode;
+mod source_strings;
+mod string_counts;
 mod strings_file_kind;
 
 pub use change::*;
 pub use changes::*;
 pub use collection::*;
 pub use completeness::*;
+pub use coverage::*;
 pub use language::*;
+pub use layout::*;
 pub use render_mode::*;
+pub use source_strings::*;
+pub use string_counts::*;
 pub use strings_file_kind::*;
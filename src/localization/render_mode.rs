@@ -1,6 +1,7 @@
+use serde::Serialize;
 use strum_macros::EnumIter;
 
-#[derive(Debug, EnumIter, Clone, Copy)]
+#[derive(Debug, EnumIter, Clone, Copy, Serialize)]
 pub enum LocalizationChangeRenderMode {
     Full,
     WithoutRelease,
@@ -0,0 +1,532 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::Context;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+use super::{
+    LocalizationLayout::{self, *},
+    StringsFileKind::{self, *},
+};
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+    platform::{Platform, ANDROID_DEFAULT_STRINGS_FILENAME, SERVER_STRINGS_FILENAME},
+    utils,
+};
+
+/// String keys mapped to their values. Plural variants have keys like `key[one]`.
+pub type Strings = BTreeMap<String, String>;
+
+/// Lists at most this many changes per file, so that the post stays within the allowed size.
+const MAX_CHANGES_PER_FILE: usize = 50;
+
+/// Longer values are shortened for the same reason.
+const MAX_VALUE_CHARS: usize = 150;
+
+const PLURAL_QUANTITIES: &[&str] = &["zero", "one", "two", "few", "many", "other"];
+
+lazy_static! {
+    static ref ANDROID_STRING_REGEX: Regex =
+        Regex::new(r#"(?s)<string name="([^"]+)"[^>]*>(.*?)</string>"#).unwrap();
+    static ref ANDROID_PLURALS_REGEX: Regex =
+        Regex::new(r#"(?s)<plurals name="([^"]+)"[^>]*>(.*?)</plurals>"#).unwrap();
+    static ref ANDROID_PLURALS_ITEM_REGEX: Regex =
+        Regex::new(r#"(?s)<item quantity="([^"]+)">(.*?)</item>"#).unwrap();
+    static ref IOS_STRING_REGEX: Regex =
+        Regex::new(r#"(?m)^\s*"((?:[^"\\]|\\.)*)"\s*=\s*"((?:[^"\\]|\\.)*)"\s*;"#).unwrap();
+    static ref PLIST_TOKEN_REGEX: Regex = Regex::new(r"<(/?)(dict|key|string)>([^<]*)").unwrap();
+}
+
+/// A default (English) strings file of a platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceStringsFile {
+    pub kind: StringsFileKind,
+    pub path: String,
+}
+
+impl SourceStringsFile {
+    pub fn for_layout(layout: LocalizationLayout) -> Vec<Self> {
+        let file = |kind: StringsFileKind, path: String| Self { kind, path };
+
+        match layout {
+            Android => vec![file(Main, ANDROID_DEFAULT_STRINGS_FILENAME.to_string())],
+            Ios => [Main, InfoPlist, PluralAware]
+                .into_iter()
+                .map(|kind| file(kind, kind.path(Ios, "en")))
+                .collect(),
+            Desktop => vec![file(Main, Main.path(Desktop, "en"))],
+            Server => vec![file(Main, SERVER_STRINGS_FILENAME.to_string())],
+            Untracked => vec![],
+        }
+    }
+
+    pub fn parse(&self, layout: LocalizationLayout, text: &str) -> anyhow::Result<Strings> {
+        self.kind
+            .parse(layout, text)
+            .with_context(|| format!("couldn't parse {}", self.path))
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.trim()
+        .replace("\\'", "'")
+        .replace("\\\"", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+pub(super) fn parse_android_strings_xml(text: &str) -> Strings {
+    let mut strings: Strings = ANDROID_STRING_REGEX
+        .captures_iter(text)
+        .map(|caps| (caps[1].to_string(), unescape_xml(&caps[2])))
+        .collect();
+
+    for caps in ANDROID_PLURALS_REGEX.captures_iter(text) {
+        for item in ANDROID_PLURALS_ITEM_REGEX.captures_iter(&caps[2]) {
+            strings.insert(
+                format!("{}[{}]", &caps[1], &item[1]),
+                unescape_xml(&item[2]),
+            );
+        }
+    }
+
+    strings
+}
+
+/// Parses `"key" = "value";` pairs, used by both `Localizable.strings` and `InfoPlist.strings`.
+pub(super) fn parse_ios_strings(text: &str) -> Strings {
+    IOS_STRING_REGEX
+        .captures_iter(text)
+        .map(|caps| (caps[1].to_string(), caps[2].replace("\\\"", "\"")))
+        .collect()
+}
+
+/// Takes the format key and plural variants of each top-level entry into account.
+pub(super) fn parse_stringsdict(text: &str) -> Strings {
+    let mut strings = Strings::new();
+
+    let mut depth = 0;
+    let mut entry = String::new();
+    let mut key = String::new();
+
+    for caps in PLIST_TOKEN_REGEX.captures_iter(text) {
+        let closing = !caps[1].is_empty();
+        let value = &caps[3];
+
+        match (&caps[2], closing) {
+            ("dict", false) => depth += 1,
+            ("dict", true) => depth -= 1,
+            ("key", false) if depth == 1 => entry = value.to_string(),
+            ("key", false) => key = value.to_string(),
+            ("string", false) => {
+                if depth == 2 && key == "NSStringLocalizedFormatKey" {
+                    strings.insert(entry.clone(), unescape_xml(value));
+                } else if depth == 3 && PLURAL_QUANTITIES.contains(&key.as_str()) {
+                    strings.insert(format!("{entry}[{key}]"), unescape_xml(value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    strings
+}
+
+/// Supports both the `message` and the newer `messageformat` fields.
+pub(super) fn parse_desktop_messages_json(text: &str) -> anyhow::Result<Strings> {
+    let value: Value = serde_json::from_str(text).context("couldn't parse JSON")?;
+
+    Ok(value
+        .as_object()
+        .context("not an object")?
+        .iter()
+        .filter_map(|(key, value)| {
+            let message = value
+                .get("messageformat")
+                .or_else(|| value.get("message"))?
+                .as_str()?;
+
+            Some((key.clone(), message.to_string()))
+        })
+        .collect())
+}
+
+pub(super) fn parse_properties(text: &str) -> Strings {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with(['#', '!']))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringChange {
+    Added {
+        key: String,
+        value: String,
+    },
+    Removed {
+        key: String,
+    },
+    Changed {
+        key: String,
+        old_value: String,
+        new_value: String,
+    },
+}
+
+impl StringChange {
+    /// Sorted by key.
+    pub fn between(old: &Strings, new: &Strings) -> Vec<Self> {
+        let mut changes: Vec<_> = old
+            .iter()
+            .filter_map(|(key, old_value)| match new.get(key) {
+                None => Some(StringChange::Removed { key: key.clone() }),
+                Some(new_value) if new_value != old_value => Some(StringChange::Changed {
+                    key: key.clone(),
+                    old_value: old_value.clone(),
+                    new_value: new_value.clone(),
+                }),
+                Some(_) => None,
+            })
+            .chain(
+                new.iter()
+                    .filter(|(key, _)| !old.contains_key(*key))
+                    .map(|(key, value)| StringChange::Added {
+                        key: key.clone(),
+                        value: value.clone(),
+                    }),
+            )
+            .collect();
+
+        changes.sort_by(|a, b| a.key().cmp(b.key()));
+        changes
+    }
+
+    pub fn key(&self) -> &str {
+        match self {
+            StringChange::Added { key, .. }
+            | StringChange::Removed { key }
+            | StringChange::Changed { key, .. } => key,
+        }
+    }
+}
+
+impl fmt::Display for StringChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |value: &str| {
+            let value = value.replace('\n', " ");
+
+            if value.chars().count() > MAX_VALUE_CHARS {
+                let shortened: String = value.chars().take(MAX_VALUE_CHARS).collect();
+                format!("\"{}…\"", utils::escape_html(&shortened))
+            } else {
+                format!("\"{}\"", utils::escape_html(&value))
+            }
+        };
+
+        match self {
+            StringChange::Added { key, value: v } => write!(f, "Added `{key}`: {}", value(v)),
+            StringChange::Removed { key } => write!(f, "Removed `{key}`"),
+            StringChange::Changed {
+                key,
+                old_value,
+                new_value,
+            } => write!(
+                f,
+                "Changed `{key}`: {} → {}",
+                value(old_value),
+                value(new_value)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceStringsFileChanges {
+    pub file: SourceStringsFile,
+    pub changes: Vec<StringChange>,
+}
+
+/// How the default (English) strings of a platform changed between two tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceStringChanges {
+    pub files: Vec<SourceStringsFileChanges>,
+}
+
+impl SourceStringChanges {
+    /// Upper bound of requests [`Self::get`] makes.
+    pub fn estimated_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        let files = SourceStringsFile::for_layout(platform.localization_layout())
+            .into_iter()
+            .filter(|file| comparison.might_have_changed(&[&file.path]))
+            .count();
+
+        2 * files as u64
+    }
+
+    /// Only fetches files that `comparison` says might have changed.
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        comparison: &Comparison,
+    ) -> anyhow::Result<Self> {
+        let layout = platform.localization_layout();
+        let mut files = Vec::new();
+
+        for file in SourceStringsFile::for_layout(layout) {
+            if !comparison.might_have_changed(&[&file.path]) {
+                tracing::trace!(file.path, "strings file didn't change, skipping");
+                continue;
+            }
+
+            let old = Self::get_strings(transport, platform, old_tag, &file).await?;
+            let new = Self::get_strings(transport, platform, new_tag, &file).await?;
+
+            let changes = StringChange::between(&old, &new);
+            tracing::debug!(file.path, changes.len = changes.len());
+
+            if !changes.is_empty() {
+                files.push(SourceStringsFileChanges { file, changes });
+            }
+        }
+
+        Ok(Self { files })
+    }
+
+    /// A file that doesn't exist at `tag` has no strings.
+    async fn get_strings(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        file: &SourceStringsFile,
+    ) -> anyhow::Result<Strings> {
+        let text = github::get_file_content_if_exists(transport, platform, &tag.name, &file.path)
+            .await
+            .with_context(|| format!("couldn't get {} at {}", file.path, tag.name))?;
+
+        match text {
+            Some(text) => file.parse(platform.localization_layout(), &text),
+            None => Ok(Strings::new()),
+        }
+    }
+
+    /// A collapsed section listing at most `max_items` (and never more than
+    /// [`MAX_CHANGES_PER_FILE`]) changed strings in each file, or an empty string if there are none.
+    pub fn markdown_text(
+        &self,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        max_items: usize,
+    ) -> String {
+        if self.files.is_empty() {
+            return String::new();
+        }
+
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let url = platform.github_comparison_url(
+                    &old_tag.name,
+                    &new_tag.name,
+                    Some(&file.file.path),
+                    false,
+                );
+
+                format!(
+                    "#### [`{}`]({url})\n{}",
+                    file.file.path,
+                    utils::markdown_list(&file.changes, max_items.min(MAX_CHANGES_PER_FILE))
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        format!("[details=\"English strings changed\"]\n[quote]\n{files}\n[/quote]\n[/details]\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use serde_json::json;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin,
+        utils,
+    };
+
+    fn strings(strings: &[(&str, &str)]) -> Strings {
+        strings
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn comparison(files: &[&str]) -> Comparison {
+        serde_json::from_value(json!({
+            "total_commits": 0,
+            "commits": [],
+            "files": files.iter().map(|file| json!({ "filename": file })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test_case(Android, Main, r#"
+<resources>
+    <string name="app_name" translatable="false">Signal</string>
+    <string name="AboutSheet__user_set_display_name">You\'re &lt;b&gt;here&lt;/b&gt;</string>
+    <plurals name="AboutSheet__n_groups">
+        <item quantity="one">%1$d group</item>
+        <item quantity="other">%1$d groups</item>
+    </plurals>
+</resources>"#, &[
+        ("AboutSheet__n_groups[one]", "%1$d group"),
+        ("AboutSheet__n_groups[other]", "%1$d groups"),
+        ("AboutSheet__user_set_display_name", "You're <b>here</b>"),
+        ("app_name", "Signal"),
+    ]; "Android")]
+    #[test_case(Ios, Main, r#"
+/* Comment */
+"ATTACHMENT" = "Attachment";
+"SAY_HI" = "Say \"hi\"";
+"#, &[("ATTACHMENT", "Attachment"), ("SAY_HI", "Say \"hi\"")]; "iOS strings")]
+    #[test_case(Ios, PluralAware, r#"
+<plist version="1.0">
+<dict>
+	<key>MESSAGES_%d</key>
+	<dict>
+		<key>NSStringLocalizedFormatKey</key>
+		<string>%#@messages@</string>
+		<key>messages</key>
+		<dict>
+			<key>NSStringFormatSpecTypeKey</key>
+			<string>NSStringPluralRuleType</string>
+			<key>one</key>
+			<string>%d message</string>
+			<key>other</key>
+			<string>%d messages</string>
+		</dict>
+	</dict>
+</dict>
+</plist>"#, &[
+        ("MESSAGES_%d", "%#@messages@"),
+        ("MESSAGES_%d[one]", "%d message"),
+        ("MESSAGES_%d[other]", "%d messages"),
+    ]; "iOS stringsdict")]
+    #[test_case(Desktop, Main, r#"{
+  "icu:AddUserToAnotherGroupModal__title": { "messageformat": "Add to a group", "description": "Shown as the title" },
+  "smartling": { "placeholder_format_custom": "..." },
+  "copyErrorAndQuit": { "message": "Copy error and quit", "description": "Legacy" }
+}"#, &[
+        ("copyErrorAndQuit", "Copy error and quit"),
+        ("icu:AddUserToAnotherGroupModal__title", "Add to a group"),
+    ]; "Desktop")]
+    #[test_case(Server, Main, "
+# Comment
+BOOST_NAME=Signal Boost
+BOOST_DESCRIPTION = A badge for boosters
+", &[("BOOST_DESCRIPTION", "A badge for boosters"), ("BOOST_NAME", "Signal Boost")]; "Server")]
+    fn parse(
+        layout: LocalizationLayout,
+        kind: StringsFileKind,
+        text: &str,
+        result: &[(&str, &str)],
+    ) {
+        let file = SourceStringsFile {
+            kind,
+            path: String::new(),
+        };
+
+        assert_eq!(file.parse(layout, text).unwrap(), strings(result));
+    }
+
+    #[test]
+    fn get() {
+        let path = "_locales/en/messages.json";
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &format!(
+                    "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.2.0/{path}"
+                ),
+                Response::new(
+                    200,
+                    json!({
+                        "icu:a": { "messageformat": "A" },
+                        "icu:b": { "messageformat": "B" },
+                    })
+                    .to_string(),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                &format!(
+                    "https://raw.githubusercontent.com/signalapp/Signal-Desktop/v1.3.0/{path}"
+                ),
+                Response::new(
+                    200,
+                    json!({
+                        "icu:b": { "messageformat": "Bee" },
+                        "icu:c": { "messageformat": "C\n<b>D</b>" },
+                    })
+                    .to_string(),
+                ),
+            );
+
+        let old_tag = Tag::new("v1.2.0");
+        let new_tag = Tag::new("v1.3.0");
+
+        let changes = utils::block_on(SourceStringChanges::get(
+            &transport,
+            builtin::Desktop,
+            &old_tag,
+            &new_tag,
+            &comparison(&[path, "_locales/de/messages.json"]),
+        ))
+        .unwrap();
+
+        assert_str_eq!(
+            changes.markdown_text(builtin::Desktop, &old_tag, &new_tag, usize::MAX),
+            "[details=\"English strings changed\"]
+[quote]
+#### [`_locales/en/messages.json`](//github.com/signalapp/Signal-Desktop/compare/v1.2.0..v1.3.0#diff-4362c7f7032e9687a0a5910cadc127afbe8259b2b941de40dd4246c35b1446f0)
+- Removed `icu:a`
+- Changed `icu:b`: \"B\" → \"Bee\"
+- Added `icu:c`: \"C &lt;b&gt;D&lt;/b&gt;\"
+[/quote]
+[/details]
+"
+        );
+    }
+
+    #[test]
+    fn get_skips_unchanged_files() {
+        let transport = FakeTransport::new();
+
+        let changes = utils::block_on(SourceStringChanges::get(
+            &transport,
+            builtin::Ios,
+            &Tag::new("7.0.0.1"),
+            &Tag::new("7.0.0.2"),
+            &comparison(&["Signal/translations/de.lproj/Localizable.strings"]),
+        ))
+        .unwrap();
+
+        assert_eq!(changes, SourceStringChanges::default());
+        assert!(transport.requests().is_empty());
+    }
+}
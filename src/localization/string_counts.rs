@@ -0,0 +1,264 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    ops::AddAssign,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::{Language, LocalizationChange, Strings, StringsFileKind, UnsortedChanges};
+use crate::{
+    github::{self, Tag},
+    network::Transport,
+    platform::Platform,
+};
+
+pub type KindStringCounts = BTreeMap<StringsFileKind, StringCounts>;
+pub type UnsortedStringCounts = HashMap<Language, KindStringCounts>;
+
+/// How many strings were added, changed, and removed in a strings file.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StringCounts {
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+}
+
+impl StringCounts {
+    pub fn between(old: &Strings, new: &Strings) -> Self {
+        let mut counts = Self::default();
+
+        for (key, old_value) in old {
+            match new.get(key) {
+                None => counts.removed += 1,
+                Some(new_value) if new_value != old_value => counts.changed += 1,
+                Some(_) => {}
+            }
+        }
+
+        counts.added = new.keys().filter(|key| !old.contains_key(*key)).count();
+        counts
+    }
+
+    /// Counts are summed, so a string that changed in several builds is counted several times.
+    pub fn merge(items: Vec<&UnsortedStringCounts>) -> UnsortedStringCounts {
+        let mut map: UnsortedStringCounts = HashMap::new();
+
+        for unsorted_counts in items {
+            for (language, kind_counts) in unsorted_counts {
+                let entry = map.entry(language.clone()).or_default();
+
+                for (kind, counts) in kind_counts {
+                    *entry.entry(*kind).or_default() += *counts;
+                }
+            }
+        }
+
+        map
+    }
+
+    /// Files that couldn't be fetched or parsed are left out, and so are files that would take
+    /// more than `max_requests` requests in total to count, if it's given.
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        unsorted_changes: &UnsortedChanges,
+        max_requests: Option<u64>,
+    ) -> UnsortedStringCounts {
+        let mut map: UnsortedStringCounts = HashMap::new();
+        let mut requests = 0;
+
+        for change in LocalizationChange::sorted_changes(unsorted_changes.clone()) {
+            for (kind, path) in change.kinds().iter().zip(change.file_paths(platform)) {
+                if max_requests.is_some_and(|max_requests| requests + 2 > max_requests) {
+                    tracing::warn!(
+                        "not enough subrequests left, not counting the rest of strings files"
+                    );
+                    return map;
+                }
+
+                requests += 2;
+
+                match Self::get_file_counts(transport, platform, old_tag, new_tag, *kind, &path)
+                    .await
+                {
+                    Ok(counts) => {
+                        map.entry(change.language().clone())
+                            .or_default()
+                            .insert(*kind, counts);
+                    }
+                    Err(e) => tracing::warn!("couldn't count strings in {path}: {e:?}"),
+                }
+            }
+        }
+
+        map
+    }
+
+    async fn get_file_counts(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        kind: StringsFileKind,
+        path: &str,
+    ) -> anyhow::Result<Self> {
+        let old = Self::get_strings(transport, platform, old_tag, kind, path).await?;
+        let new = Self::get_strings(transport, platform, new_tag, kind, path).await?;
+
+        Ok(Self::between(&old, &new))
+    }
+
+    /// A file that doesn't exist at `tag` has no strings.
+    async fn get_strings(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        kind: StringsFileKind,
+        path: &str,
+    ) -> anyhow::Result<Strings> {
+        let text = github::get_file_content_if_exists(transport, platform, &tag.name, path)
+            .await
+            .with_context(|| format!("couldn't get {path} at {}", tag.name))?;
+
+        match text {
+            Some(text) => kind
+                .parse(platform.localization_layout(), &text)
+                .with_context(|| format!("couldn't parse {path} at {}", tag.name)),
+            None => Ok(Strings::new()),
+        }
+    }
+}
+
+impl AddAssign for StringCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.added += other.added;
+        self.changed += other.changed;
+        self.removed += other.removed;
+    }
+}
+
+/// Like `+42 / ~7 / -1 strings`.
+impl fmt::Display for StringCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "+{} / ~{} / -{} strings",
+            self.added, self.changed, self.removed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        localization::StringsFileKind::*,
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    fn strings(strings: &[(&str, &str)]) -> Strings {
+        strings
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn counts(added: usize, changed: usize, removed: usize) -> StringCounts {
+        StringCounts {
+            added,
+            changed,
+            removed,
+        }
+    }
+
+    #[test]
+    fn between() {
+        let old = strings(&[("a", "A"), ("b", "B"), ("c", "C")]);
+        let new = strings(&[("a", "A"), ("b", "Bee"), ("d", "D"), ("e", "E")]);
+
+        assert_eq!(StringCounts::between(&old, &new), counts(2, 1, 1));
+        assert_eq!(counts(42, 7, 1).to_string(), "+42 / ~7 / -1 strings");
+    }
+
+    #[test]
+    fn merge() {
+        let language = Language::try_from("de").unwrap();
+
+        let first = UnsortedStringCounts::from([(
+            language.clone(),
+            KindStringCounts::from([(Main, counts(1, 2, 0))]),
+        )]);
+        let second = UnsortedStringCounts::from([(
+            language.clone(),
+            KindStringCounts::from([(Main, counts(3, 0, 1)), (InfoPlist, counts(0, 1, 0))]),
+        )]);
+
+        assert_eq!(
+            StringCounts::merge(vec![&first, &second]),
+            UnsortedStringCounts::from([(
+                language,
+                KindStringCounts::from([(Main, counts(4, 2, 1)), (InfoPlist, counts(0, 1, 0))]),
+            )])
+        );
+    }
+
+    #[test_case(None, 1; "unlimited")]
+    #[test_case(Some(2), 1; "enough requests")]
+    #[test_case(Some(1), 0; "not enough requests")]
+    fn get(max_requests: Option<u64>, counted_files: usize) {
+        let url = |tag: &str| {
+            format!("https://raw.githubusercontent.com/signalapp/Signal-Desktop/{tag}/_locales/de/messages.json")
+        };
+
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &url("v7.0.0"),
+                Response::new(
+                    200,
+                    String::from(r#"{"a": {"messageformat": "A"}, "b": {"messageformat": "B"}}"#),
+                ),
+            )
+            .with_response(
+                Method::Get,
+                &url("v7.1.0"),
+                Response::new(
+                    200,
+                    String::from(r#"{"b": {"messageformat": "Bee"}, "c": {"messageformat": "C"}}"#),
+                ),
+            );
+
+        let language = Language::try_from("de").unwrap();
+        let unsorted_changes = UnsortedChanges::from([(language.clone(), [Main].into())]);
+
+        let string_counts = utils::block_on(StringCounts::get(
+            &transport,
+            Desktop,
+            &Tag::new("v7.0.0"),
+            &Tag::new("v7.1.0"),
+            &unsorted_changes,
+            max_requests,
+        ));
+
+        let expected = UnsortedStringCounts::from([(
+            language,
+            KindStringCounts::from([(Main, counts(1, 1, 1))]),
+        )]);
+
+        match counted_files {
+            0 => assert_eq!(string_counts, UnsortedStringCounts::new()),
+            _ => assert_eq!(string_counts, expected),
+        }
+        assert_eq!(transport.requests().len(), 2 * counted_files);
+    }
+}
@@ -4,7 +4,14 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-use crate::platform::Platform::{self, *};
+use super::{
+    source_strings::{
+        parse_android_strings_xml, parse_desktop_messages_json, parse_ios_strings,
+        parse_properties, parse_stringsdict,
+    },
+    LocalizationLayout::{self, *},
+    Strings,
+};
 
 #[derive(
     Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter,
@@ -20,34 +27,40 @@ pub enum StringsFileKind {
 use StringsFileKind::*;
 
 impl StringsFileKind {
-    pub const fn applicable_for_platform(&self, platform: Platform) -> bool {
+    pub const fn applicable_for_layout(&self, layout: LocalizationLayout) -> bool {
         matches!(
-            (platform, self),
+            (layout, self),
             (Android | Desktop | Server, Main) | (Ios, _)
         )
     }
 
-    pub fn applicable_iter(platform: Platform) -> impl Iterator<Item = Self> {
-        Self::iter().filter(move |kind| kind.applicable_for_platform(platform))
+    pub fn applicable_iter(layout: LocalizationLayout) -> impl Iterator<Item = Self> {
+        Self::iter().filter(move |kind| kind.applicable_for_layout(layout))
     }
 
-    pub const fn path_base(&self, platform: Platform) -> &'static str {
-        match (platform, self) {
+    /// Expects the kind to be applicable for `layout`.
+    pub const fn path_base(&self, layout: LocalizationLayout) -> &'static str {
+        match (layout, self) {
             (Android, _) => "app/src/main/res",
             (Ios, Main | InfoPlist | PluralAware) => "Signal/translations",
             (Ios, AppStoreDescription | AppStoreReleaseNotes) => "fastlane/metadata",
             (Desktop, _) => "_locales",
             (Server, _) => "service/src/main/resources/org/signal",
+            (Untracked, _) => "",
         }
     }
 
-    /// Expects `language_placeholder_value` to be in the expected format for `platform` and `kind`.
-    fn path_folder_name(&self, platform: Platform, language_placeholder_value: &str) -> String {
-        if !self.applicable_for_platform(platform) {
-            panic!("unexpected strings file for {platform}: {self:?}")
+    /// Expects `language_placeholder_value` to be in the expected format for `layout` and `kind`.
+    fn path_folder_name(
+        &self,
+        layout: LocalizationLayout,
+        language_placeholder_value: &str,
+    ) -> String {
+        if !self.applicable_for_layout(layout) {
+            panic!("unexpected strings file for {layout:?} layout: {self:?}")
         }
 
-        match (platform, self) {
+        match (layout, self) {
             (Android, _) => format!("values-{language_placeholder_value}"),
             (Ios, Main | InfoPlist | PluralAware) => {
                 format!("{language_placeholder_value}.lproj")
@@ -56,11 +69,13 @@ impl StringsFileKind {
                 language_placeholder_value.to_owned()
             }
             (Server, _) => String::from("badges"),
+            (Untracked, _) => unreachable!("no strings files are applicable"),
         }
     }
 
-    pub const fn path_file_name(&self, platform: Platform) -> &'static str {
-        match (platform, self) {
+    /// Expects the kind to be applicable for `layout`.
+    pub const fn path_file_name(&self, layout: LocalizationLayout) -> &'static str {
+        match (layout, self) {
             (Android, _) => "strings.xml",
             (Ios, Main) => "Localizable.strings",
             (Ios, InfoPlist) => "InfoPlist.strings",
@@ -69,18 +84,35 @@ impl StringsFileKind {
             (Ios, AppStoreReleaseNotes) => "release_notes.txt",
             (Desktop, _) => "messages.json",
             (Server, _) => "Badges.properties",
+            (Untracked, _) => "",
         }
     }
 
-    /// Expects `language_placeholder_value` to be in the expected format for `platform` and `kind`.
-    pub fn path(&self, platform: Platform, language_placeholder_value: &str) -> String {
+    /// Expects `language_placeholder_value` to be in the expected format for `layout` and `kind`.
+    pub fn path(&self, layout: LocalizationLayout, language_placeholder_value: &str) -> String {
         format!(
             "{}/{}/{}",
-            self.path_base(platform),
-            self.path_folder_name(platform, language_placeholder_value),
-            self.path_file_name(platform),
+            self.path_base(layout),
+            self.path_folder_name(layout, language_placeholder_value),
+            self.path_file_name(layout),
         )
     }
+
+    /// Parses a file of this kind. App Store metadata is plain text, so it's a single string.
+    pub fn parse(&self, layout: LocalizationLayout, text: &str) -> anyhow::Result<Strings> {
+        match (layout, self) {
+            (Android, _) => Ok(parse_android_strings_xml(text)),
+            (Ios, Main | InfoPlist) => Ok(parse_ios_strings(text)),
+            (Ios, PluralAware) => Ok(parse_stringsdict(text)),
+            (Ios, AppStoreDescription | AppStoreReleaseNotes) => Ok(match text.trim() {
+                "" => Strings::new(),
+                text => Strings::from([(String::from("text"), text.to_string())]),
+            }),
+            (Desktop, _) => parse_desktop_messages_json(text),
+            (Server, _) => Ok(parse_properties(text)),
+            (Untracked, _) => Ok(Strings::new()),
+        }
+    }
 }
 
 impl fmt::Display for StringsFileKind {
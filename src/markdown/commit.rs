@@ -2,7 +2,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use super::CommitStatus::{self, *};
-use crate::{github, platform::Platform, utils};
+use crate::{github, localization::LocalizationLayout, platform::Platform, utils};
 
 #[derive(Debug, Clone)]
 pub struct Commit<'a> {
@@ -25,6 +25,10 @@ impl<'a> Commit<'a> {
     }
 
     pub fn is_likely_localization_change(&self) -> bool {
+        if self.platform.localization_layout() == LocalizationLayout::Untracked {
+            return false;
+        }
+
         let lowercase = self.full_message.to_lowercase();
 
         lowercase.contains("language")
@@ -125,11 +129,10 @@ impl<'a> Commit<'a> {
 #[cfg(test)]
 mod tests {
     use pretty_assertions::{assert_eq, assert_str_eq};
-    use strum::IntoEnumIterator;
     use test_case::test_case;
 
     use super::*;
-    use crate::platform::Platform::*;
+    use crate::platform::builtin::*;
 
     #[test_case(true, "Updated language translations.")]
     #[test_case(true, "Update strings")]
@@ -144,7 +147,7 @@ mod tests {
     #[test_case(false, "Update GitHub Actions")]
     #[test_case(false, "Test commit.")]
     fn is_likely_localization_change(result: bool, message: &str) {
-        for platform in Platform::iter() {
+        for platform in [Android, Ios, Desktop, Server] {
             assert_eq!(
                 Commit::new(platform, message, "abcdef").is_likely_localization_change(),
                 result
@@ -152,6 +155,11 @@ mod tests {
         }
     }
 
+    #[test]
+    fn untracked_localization_is_never_changed() {
+        assert!(!Commit::new(Libsignal, "Update strings", "abcdef").is_likely_localization_change());
+    }
+
     #[test_case(
         Android, "Test commit.", "abcdef", Normal,
         "- Test commit. [[2]](//github.com/signalapp/Signal-Android/commit/abcdef)\n";
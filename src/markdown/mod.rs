@@ -1,7 +1,9 @@
 mod commit;
 mod commit_status;
 mod post;
+mod section_render_mode;
 
 pub use commit::*;
 pub use commit_status::*;
 pub use post::*;
+pub use section_render_mode::*;
@@ -3,16 +3,38 @@ use std::collections::HashMap;
 use anyhow::bail;
 use strum::IntoEnumIterator;
 
-use super::{Commit, CommitStatus};
+use super::{Commit, CommitStatus, SectionRenderMode};
 use crate::{
+    database_version::DatabaseVersionChange,
+    dependency::DependencyChanges,
     discourse::{self, PostingOutcome},
+    feature_flag::FeatureFlagChanges,
     github::Tag,
-    localization::{LocalizationChangeCollection, LocalizationChangeRenderMode},
-    platform::{android::BuildConfiguration, Platform},
+    localization::{
+        LocalizationChangeCollection, LocalizationChangeRenderMode, LocalizationLayout,
+        SourceStringChanges,
+    },
+    network::{RunContext, Transport},
+    platform::{builtin::Android, BuildConfiguration, HeaderChanges, Platform},
+    release_notes::ReleaseNotes,
 };
 
 pub const MAX_COMMITS_WITHOUT_DETAILS_TAG: usize = 10;
 
+/// Discourse doesn't accept longer posts.
+const MAX_POST_LENGTH: usize = 32_000;
+
+/// Parts of a post that are only shown when something relevant changed.
+#[derive(Debug, Default)]
+pub struct PostSections {
+    pub database_version_change: Option<DatabaseVersionChange>,
+    pub header_changes: HeaderChanges,
+    pub release_notes: Option<ReleaseNotes>,
+    pub dependency_changes: DependencyChanges,
+    pub feature_flag_changes: FeatureFlagChanges,
+    pub source_string_changes: SourceStringChanges,
+}
+
 #[derive(Debug)]
 pub struct Post<'a> {
     platform: Platform,
@@ -20,6 +42,7 @@ pub struct Post<'a> {
     new_tag: &'a Tag,
     new_build_configuration: Option<BuildConfiguration>,
     available: bool,
+    sections: PostSections,
     commits: Vec<Commit<'a>>,
     unfiltered_commits_len: usize,
     localization_change_collection: LocalizationChangeCollection<'a>,
@@ -33,13 +56,14 @@ impl<'a> Post<'a> {
         new_tag: &'a Tag,
         new_build_configuration: Option<BuildConfiguration>,
         available: bool,
+        sections: PostSections,
         commits: Vec<Commit<'a>>,
         unfiltered_commits_len: usize,
         localization_change_collection: LocalizationChangeCollection<'a>,
     ) -> Self {
         assert!(commits.len() <= unfiltered_commits_len);
 
-        if platform == Platform::Android && new_build_configuration.is_none() {
+        if platform == Android && new_build_configuration.is_none() {
             tracing::warn!("no new_build_configuration despite platform = Android")
         }
 
@@ -49,6 +73,7 @@ impl<'a> Post<'a> {
             new_tag,
             new_build_configuration,
             available,
+            sections,
             commits,
             unfiltered_commits_len,
             localization_change_collection,
@@ -99,23 +124,23 @@ impl<'a> Post<'a> {
             .join("\n")
     }
 
-    fn markdown_text(&self, commits_markdown: &str, mode: LocalizationChangeRenderMode) -> String {
+    fn markdown_text(
+        &self,
+        commits_markdown: &str,
+        sections_mode: SectionRenderMode,
+        mode: LocalizationChangeRenderMode,
+    ) -> String {
         let old_version = &self.old_tag.exact_version_string();
         let new_version = &self.new_tag.exact_version_string();
 
         let (metadata_part1, metadata_part2) = match &self.new_build_configuration {
-            Some(BuildConfiguration {
-                canonical_version_code,
-                current_hotfix_version,
-                max_hotfix_versions,
-            }) => {
-                let full_version_code =
-                    (canonical_version_code * max_hotfix_versions) + *current_hotfix_version;
-
-                (format!(" ({full_version_code})"), String::new())
-            }
+            // iOS build numbers are usually the same as the version, so they aren't repeated.
+            Some(configuration) => match configuration.full_build_number() {
+                build_number if build_number == *new_version => (String::new(), String::new()),
+                build_number => (format!(" ({build_number})"), String::new()),
+            },
             None => {
-                if self.platform == Platform::Android {
+                if self.platform == Android {
                     (String::new(), String::from("\n*Couldn't find the build number for this version. The app `build.gradle` kittens have changed...*"))
                 } else {
                     (String::new(), String::new())
@@ -123,6 +148,13 @@ impl<'a> Post<'a> {
             }
         };
 
+        let database_version_notice = match &self.sections.database_version_change {
+            Some(change) => format!("\nDatabase version {change}"),
+            None => String::new(),
+        };
+
+        let header_changes = self.sections.header_changes.markdown_text();
+
         let platform = self.platform;
         let availability_notice = platform.availability_notice(self.available);
         let comparison_url =
@@ -136,7 +168,52 @@ impl<'a> Post<'a> {
 
         let commits_word_suffix = if commits_count == 1 { "" } else { "s" };
 
-        let localization_changes_string = self.localization_change_collection.to_string(mode);
+        let release_notes_string = match (&self.sections.release_notes, sections_mode) {
+            (_, SectionRenderMode::Collapsed) | (None, _) => String::new(),
+            (Some(release_notes), _) => release_notes.markdown_text(),
+        };
+
+        let max_items = sections_mode.max_items();
+
+        let native_updates = &self.sections.dependency_changes.native_updates;
+        let dependency_updates_string = if native_updates.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "#### Dependency updates\n{}\n\n",
+                native_updates
+                    .iter()
+                    .map(|update| format!("- {}", update.markdown_text(platform.config())))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        };
+
+        let dependencies_changed_string = self.sections.dependency_changes.markdown_text(
+            platform,
+            self.old_tag,
+            self.new_tag,
+            max_items,
+        );
+
+        let feature_flags_changed_string = self.sections.feature_flag_changes.markdown_text(
+            platform,
+            self.old_tag,
+            self.new_tag,
+            max_items,
+        );
+
+        let source_strings_changed_string = self.sections.source_string_changes.markdown_text(
+            platform,
+            self.old_tag,
+            self.new_tag,
+            max_items,
+        );
+
+        let localization_changes_string = match platform.localization_layout() {
+            LocalizationLayout::Untracked => String::new(),
+            _ => self.localization_change_collection.to_string(mode),
+        };
 
         let difference = self.unfiltered_commits_len - self.commits.len();
         let filtered_notice = if difference != 0 {
@@ -149,57 +226,67 @@ impl<'a> Post<'a> {
         let provider = self.github_comparison_name();
 
         format!(
-            "## New Version: {new_version}{metadata_part1}{availability_notice}{metadata_part2}
+            "## New Version: {new_version}{metadata_part1}{availability_notice}{metadata_part2}{database_version_notice}{header_changes}
 [quote]
 {commits_count} new commit{commits_word_suffix} since {old_version}{filtered_notice}:
 {commits_prefix}{commits_markdown}{commits_postfix}
 ---
 Gathered from [{provider}]({comparison_url})
 [/quote]
-{localization_changes_string}"
+{release_notes_string}{dependency_updates_string}{dependencies_changed_string}{feature_flags_changed_string}{source_strings_changed_string}{localization_changes_string}"
         )
     }
 
-    pub async fn post(
+    /// Returns the text of the post, using the most detailed section and localization change render
+    /// modes that still fit within the allowed character count, and those modes. Sections are only
+    /// shortened if the post doesn't fit even without localization changes.
+    pub fn markdown(
         &self,
-        is_dry_run: bool,
-        api_key: &str,
-        topic_id: u64,
-        reply_to_post_number: Option<u64>,
-    ) -> anyhow::Result<PostingOutcome> {
+    ) -> anyhow::Result<(String, SectionRenderMode, LocalizationChangeRenderMode)> {
         let commits_markdown = self.commits_markdown();
 
-        let mut post_markdown: Option<String> = None;
+        for sections_mode in SectionRenderMode::iter() {
+            for mode in LocalizationChangeRenderMode::iter() {
+                tracing::debug!(
+                    ?sections_mode,
+                    ?mode,
+                    "trying section and localization change collection render modes"
+                );
 
-        for mode in LocalizationChangeRenderMode::iter() {
-            tracing::debug!(?mode, "trying localization change collection render mode");
+                let text = self.markdown_text(&commits_markdown, sections_mode, mode);
+                tracing::debug!(text.len = text.len());
 
-            let text = self.markdown_text(&commits_markdown, mode);
-            tracing::debug!(text.len = text.len());
-
-            if text.len() > 32_000 {
-                tracing::warn!("text is likely too long to post");
-                post_markdown = None;
-            } else {
-                post_markdown = Some(text);
-                break;
-            }
-        }
-
-        match &post_markdown {
-            Some(markdown_text) => {
-                if !is_dry_run {
-                    discourse::post(markdown_text, api_key, topic_id, reply_to_post_number).await
+                if text.len() > MAX_POST_LENGTH {
+                    tracing::warn!("text is likely too long to post");
                 } else {
-                    tracing::warn!("dry run; not posting to Discourse");
-                    Ok(PostingOutcome::Posted {
-                        id: 0,
-                        number: reply_to_post_number.unwrap_or(0),
-                    })
+                    return Ok((text, sections_mode, mode));
                 }
             }
-            None => bail!("could not make a post that fits within the allowed character count"),
         }
+
+        bail!("could not make a post that fits within the allowed character count")
+    }
+
+    pub async fn post(
+        &self,
+        transport: &dyn Transport,
+        context: &RunContext,
+        api_key: &str,
+        topic_id: u64,
+        reply_to_post_number: Option<u64>,
+    ) -> anyhow::Result<PostingOutcome> {
+        let (markdown_text, _, _) = self.markdown()?;
+
+        discourse::post(
+            transport,
+            context,
+            self.platform.config(),
+            &markdown_text,
+            api_key,
+            topic_id,
+            reply_to_post_number,
+        )
+        .await
     }
 
     pub fn platform(&self) -> Platform {
@@ -218,6 +305,22 @@ Gathered from [{provider}]({comparison_url})
         self.new_build_configuration.as_ref()
     }
 
+    pub fn database_version_change(&self) -> Option<&DatabaseVersionChange> {
+        self.sections.database_version_change.as_ref()
+    }
+
+    pub fn header_changes(&self) -> &HeaderChanges {
+        &self.sections.header_changes
+    }
+
+    pub fn dependency_changes(&self) -> &DependencyChanges {
+        &self.sections.dependency_changes
+    }
+
+    pub fn feature_flag_changes(&self) -> &FeatureFlagChanges {
+        &self.sections.feature_flag_changes
+    }
+
     pub fn commits(&self) -> &[Commit<'_>] {
         self.commits.as_ref()
     }
@@ -248,10 +351,33 @@ mod tests {
 
     use super::*;
     use crate::{
-        localization::{Completeness, LocalizationChange, LocalizationChanges, StringsFileKind::*},
-        platform::Platform::*,
+        dependency::{Dependency, DependencyUpdate, ManifestChanges, PackageChange},
+        localization::{
+            Completeness, LocalizationChange, LocalizationChanges, StringsFileKind::*,
+            UnsortedStringCounts,
+        },
+        platform::{android, builtin::*, ios},
     };
 
+    fn no_localization_changes<'a>(
+        platform: Platform,
+        old_tag: &'a Tag,
+        new_tag: &'a Tag,
+    ) -> LocalizationChangeCollection<'a> {
+        LocalizationChangeCollection {
+            build_changes: LocalizationChanges {
+                platform,
+                old_tag,
+                new_tag,
+                completeness: Completeness::Complete,
+                unsorted_changes: LocalizationChange::unsorted_changes(vec![]),
+                string_counts: UnsortedStringCounts::default(),
+                coverage: HashMap::new(),
+            },
+            release_changes: None,
+        }
+    }
+
     #[test_case(Android, "v1.2.3", "v1.2.4", None, false, vec![
         Commit::new(Android, "Test commit.", "abcdef")
     ], 1, None, "## New Version: 1.2.4
@@ -296,11 +422,15 @@ Note: after clicking a link, it may take a few seconds before GitHub jumps to th
 Localization changes for the release are the same, as this is the first build of the release.
 [/quote]
 [/details]"; "Android: one commit available")]
-    #[test_case(Android, "v1.2.3", "v1.2.4", Some(BuildConfiguration {
+    #[test_case(Android, "v1.2.3", "v1.2.4", Some(BuildConfiguration::Android(android::BuildConfiguration {
     canonical_version_code: 1234,
+    canonical_version_name: None,
     current_hotfix_version: 1,
     max_hotfix_versions: 100,
-}), false, vec![
+    min_sdk: None,
+    target_sdk: None,
+    compile_sdk: None,
+})), false, vec![
     Commit::new(Android, "Test commit.", "abcdef")
 ], 1, None, "## New Version: 1.2.4 (123401)
 Builds [will no longer be published to Firebase App Distribution](/t/17538/114)
@@ -497,6 +627,17 @@ Note: after clicking a link, it may take a few seconds before GitHub jumps to th
 Localization changes for the release are the same, as this is the first build of the release.
 [/quote]
 [/details]"; "Desktop: one commit")]
+    #[test_case(Libsignal, "v0.52.2", "v0.52.3", None, false, vec![
+        Commit::new(Libsignal, "Test commit.", "abcdef")
+    ], 1, None, "## New Version: 0.52.3
+[quote]
+1 new commit since 0.52.2:
+- Test commit. [[1]](//github.com/signalapp/libsignal/commit/abcdef)
+
+---
+Gathered from [signalapp/libsignal v0.52.2...v0.52.3](//github.com/signalapp/libsignal/compare/v0.52.2...v0.52.3)
+[/quote]
+"; "libsignal: one commit")]
     #[test_case(Android, "v1.2.3", "v1.2.4", None, false, vec![
         Commit::new(Android, "Test commit.", "abcdef")
     ], 1, Some(Completeness::Complete), "## New Version: 1.2.4
@@ -549,6 +690,8 @@ Note: after clicking a link, it may take a few seconds before GitHub jumps to th
                         LocalizationChange::test_change("en", vec![Main]),
                         LocalizationChange::test_change("en-US", vec![Main]),
                     ]),
+                    string_counts: UnsortedStringCounts::default(),
+                    coverage: HashMap::new(),
                 },
                 release_changes: Some(LocalizationChanges {
                     platform: Android,
@@ -560,18 +703,11 @@ Note: after clicking a link, it may take a few seconds before GitHub jumps to th
                         LocalizationChange::test_change("en-US", vec![Main]),
                         LocalizationChange::test_change("en-CA", vec![Main]),
                     ]),
+                    string_counts: UnsortedStringCounts::default(),
+                    coverage: HashMap::new(),
                 }),
             },
-            None => LocalizationChangeCollection {
-                build_changes: LocalizationChanges {
-                    platform,
-                    old_tag: &old_tag,
-                    new_tag: &new_tag,
-                    completeness: Completeness::Complete,
-                    unsorted_changes: LocalizationChange::unsorted_changes(vec![]),
-                },
-                release_changes: None,
-            },
+            None => no_localization_changes(platform, &old_tag, &new_tag),
         };
 
         let post = Post::new(
@@ -580,14 +716,200 @@ Note: after clicking a link, it may take a few seconds before GitHub jumps to th
             &new_tag,
             new_build_configuration,
             available,
+            PostSections::default(),
             commits,
             unfiltered_commits_len,
             localization_change_collection,
         );
 
         assert_str_eq!(
-            post.markdown_text(&post.commits_markdown(), LocalizationChangeRenderMode::Full),
+            post.markdown_text(
+                &post.commits_markdown(),
+                SectionRenderMode::Full,
+                LocalizationChangeRenderMode::Full,
+            ),
             result
         );
     }
+
+    #[test]
+    fn post_markdown_with_dependency_changes() {
+        let old_tag = Tag::new("v1.2.0-beta.1");
+        let new_tag = Tag::new("v1.2.0-beta.2");
+
+        let post = Post::new(
+            Desktop,
+            &old_tag,
+            &new_tag,
+            None,
+            false,
+            PostSections {
+                dependency_changes: DependencyChanges {
+                    manifests: vec![ManifestChanges {
+                        manifest: Desktop.definition().manifests[0].clone(),
+                        changes: vec![PackageChange::Updated {
+                            name: String::from("@signalapp/ringrtc"),
+                            old_version: String::from("2.44.0"),
+                            new_version: String::from("2.44.1"),
+                        }],
+                    }],
+                    native_updates: vec![DependencyUpdate {
+                        dependency: Dependency::RingRtc,
+                        old_version: String::from("2.44.0"),
+                        new_version: String::from("2.44.1"),
+                    }],
+                },
+                ..Default::default()
+            },
+            vec![Commit::new(Desktop, "Update RingRTC", "abcdef")],
+            1,
+            no_localization_changes(Desktop, &old_tag, &new_tag),
+        );
+
+        assert!(post
+            .markdown_text(
+                &post.commits_markdown(),
+                SectionRenderMode::Full,
+                LocalizationChangeRenderMode::Full,
+            )
+            .contains(
+                "[/quote]
+#### Dependency updates
+- RingRTC [2.44.0 → 2.44.1](//github.com/signalapp/ringrtc/compare/v2.44.0...v2.44.1)
+
+[details=\"Dependencies changed\"]
+[quote]
+#### [`package.json`](//github.com/signalapp/Signal-Desktop/compare/v1.2.0-beta.1..v1.2.0-beta.2#diff-7ae45ad102eab3b6d7e7896acd08c427a9b25b346470d7bc6507b6481575d519)
+- Updated `@signalapp/ringrtc` 2.44.0 → 2.44.1
+[/quote]
+[/details]
+[details=\"Localization changes\"]"
+            ));
+    }
+
+    #[test]
+    fn post_markdown_shortens_sections() {
+        let old_tag = Tag::new("v1.2.0-beta.1");
+        let new_tag = Tag::new("v1.2.0-beta.2");
+
+        let post = Post::new(
+            Desktop,
+            &old_tag,
+            &new_tag,
+            None,
+            false,
+            PostSections {
+                dependency_changes: DependencyChanges {
+                    manifests: vec![ManifestChanges {
+                        manifest: Desktop.definition().manifests[0].clone(),
+                        changes: (0..1000)
+                            .map(|i| PackageChange::Added {
+                                name: format!("package-with-a-long-name-{i}"),
+                                version: String::from("1.0.0"),
+                            })
+                            .collect(),
+                    }],
+                    native_updates: vec![],
+                },
+                ..Default::default()
+            },
+            vec![Commit::new(Desktop, "Add packages", "abcdef")],
+            1,
+            no_localization_changes(Desktop, &old_tag, &new_tag),
+        );
+
+        let (text, sections_mode, _) = post.markdown().unwrap();
+
+        assert!(matches!(sections_mode, SectionRenderMode::Shortened));
+        assert!(text.contains("- *… and 990 more*"));
+    }
+
+    #[test]
+    fn post_markdown_with_database_version_change() {
+        let old_tag = Tag::new("v1.2.3");
+        let new_tag = Tag::new("v1.2.4");
+
+        let post = Post::new(
+            Desktop,
+            &old_tag,
+            &new_tag,
+            None,
+            false,
+            PostSections {
+                database_version_change: Some(DatabaseVersionChange {
+                    old_version: 210,
+                    new_version: 212,
+                }),
+                ..Default::default()
+            },
+            vec![Commit::new(Desktop, "Test commit.", "abcdef")],
+            1,
+            no_localization_changes(Desktop, &old_tag, &new_tag),
+        );
+
+        assert!(post
+            .markdown_text(
+                &post.commits_markdown(),
+                SectionRenderMode::Full,
+                LocalizationChangeRenderMode::Full,
+            )
+            .starts_with(
+                "## New Version: 1.2.4
+Database version 210 → 212 (downgrade not possible)
+[quote]"
+            ));
+    }
+
+    #[test]
+    fn post_markdown_with_ios_build_configuration_and_release_notes() {
+        let old_tag = Tag::new("7.0.0.1");
+        let new_tag = Tag::new("7.0.0.2");
+
+        let post = Post::new(
+            Ios,
+            &old_tag,
+            &new_tag,
+            Some(BuildConfiguration::Ios(ios::BuildConfiguration {
+                build_number: String::from("7.0.0.2"),
+                marketing_version: String::from("7.0.0"),
+            })),
+            false,
+            PostSections {
+                header_changes: HeaderChanges {
+                    build_configuration: vec![],
+                    added_permissions: vec![String::from("NSMotionUsageDescription")],
+                    removed_permissions: vec![],
+                    electron_update: None,
+                },
+                release_notes: Some(ReleaseNotes {
+                    text: String::from("Stickers!"),
+                }),
+                ..Default::default()
+            },
+            vec![Commit::new(Ios, "Test commit.", "abcdef")],
+            1,
+            no_localization_changes(Ios, &old_tag, &new_tag),
+        );
+
+        let text = post.markdown_text(
+            &post.commits_markdown(),
+            SectionRenderMode::Full,
+            LocalizationChangeRenderMode::Full,
+        );
+
+        assert!(text.starts_with(
+            "## New Version: 7.0.0.2
+Permissions added: `NSMotionUsageDescription`
+[quote]"
+        ));
+        assert!(text.contains(
+            "[/quote]
+#### What's new
+[quote]
+Stickers!
+[/quote]
+
+[details=\"Localization changes\"]"
+        ));
+    }
 }
@@ -0,0 +1,22 @@
+use serde::Serialize;
+use strum_macros::EnumIter;
+
+/// How much of the [`super::PostSections`] is shown, from most to least detailed.
+#[derive(Debug, EnumIter, Clone, Copy, Serialize)]
+pub enum SectionRenderMode {
+    Full,
+    Shortened,
+    /// Only links to the changed files and how many changes each has, without release notes.
+    Collapsed,
+}
+
+impl SectionRenderMode {
+    /// Most changes listed per file in dependency, feature flag, and English string changes.
+    pub fn max_items(&self) -> usize {
+        match self {
+            Self::Full => usize::MAX,
+            Self::Shortened => 10,
+            Self::Collapsed => 0,
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Request, Response, Transport};
+use crate::{state::Storage, utils::BoxFuture};
+
+const KEY_PREFIX: &str = "etag:";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct CachedResponse {
+    etag: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+/// Makes requests with [`Request::cache_with_etag`] conditional, keeping the latest successful
+/// response to each of them in storage.
+///
+/// When the server answers with `304 Not Modified`, the stored response is returned instead, so
+/// callers see the same response as last time without it being downloaded again.
+pub struct EtagCacheTransport<'a> {
+    inner: &'a dyn Transport,
+    storage: Box<dyn Storage>,
+}
+
+impl<'a> EtagCacheTransport<'a> {
+    pub fn new(inner: &'a dyn Transport, storage: Box<dyn Storage>) -> Self {
+        Self { inner, storage }
+    }
+
+    async fn cached_response(&self, key: &str) -> Option<CachedResponse> {
+        let value = match self.storage.get(key).await {
+            Ok(value) => value?,
+            Err(error) => {
+                tracing::warn!(?error, key, "couldn't get cached response");
+                return None;
+            }
+        };
+
+        serde_json::from_str(&value)
+            .map_err(|error| tracing::warn!(?error, key, "couldn't parse cached response"))
+            .ok()
+    }
+
+    async fn cache_response(&self, key: &str, response: &Response) {
+        let Some(etag) = response.header("ETag") else {
+            return;
+        };
+
+        let cached = CachedResponse {
+            etag: etag.to_string(),
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+        };
+
+        let result = match serde_json::to_string(&cached) {
+            Ok(value) => self.storage.put(key, value).await,
+            Err(error) => Err(error.into()),
+        };
+
+        if let Err(error) = result {
+            tracing::warn!(?error, key, "couldn't cache response");
+        }
+    }
+}
+
+impl Transport for EtagCacheTransport<'_> {
+    fn send(&self, mut request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        if !request.cache_with_etag {
+            return self.inner.send(request);
+        }
+
+        Box::pin(async move {
+            let key = format!("{KEY_PREFIX}{}", request.url);
+            let cached = self.cached_response(&key).await;
+
+            if let Some(cached) = &cached {
+                request.set_header("If-None-Match", &cached.etag);
+            }
+
+            let response = self.inner.send(request).await?;
+
+            match cached {
+                Some(cached) if response.status_code == 304 => {
+                    tracing::debug!(key, "not modified, using cached response");
+                    Ok(not_modified_response(cached, response))
+                }
+                _ => {
+                    if response.is_success() {
+                        self.cache_response(&key, &response).await;
+                    }
+
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
+
+/// The cached response, with headers updated from the `304` one (like rate limit information).
+fn not_modified_response(cached: CachedResponse, not_modified: Response) -> Response {
+    let mut response = Response::new(200, cached.body);
+    response.headers = not_modified.headers;
+
+    for (name, value) in cached.headers {
+        if response.header(&name).is_none() {
+            response.headers.push((name, value));
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{create_request, ContentType, FakeTransport},
+        state::MemoryStorage,
+        utils::block_on,
+    };
+
+    const URL: &str = "https://api.github.com/repos/signalapp/Signal-Android/tags";
+
+    fn request(cache_with_etag: bool) -> Request {
+        let mut request = create_request(
+            URL.parse().unwrap(),
+            Method::Get,
+            ContentType::ApplicationJson,
+            ContentType::ApplicationJson,
+            None,
+            None,
+        )
+        .unwrap();
+
+        request.cache_with_etag = cache_with_etag;
+        request
+    }
+
+    #[test]
+    fn uses_cached_response_if_not_modified() {
+        let inner = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                URL,
+                Response::new(200, "[1]")
+                    .with_header("ETag", r#""a""#)
+                    .with_header("Link", "<next>"),
+            )
+            .with_response(
+                Method::Get,
+                URL,
+                Response::new(304, "").with_header("X-RateLimit-Remaining", "59"),
+            );
+
+        let storage = MemoryStorage::new();
+        let transport = EtagCacheTransport::new(&inner, Box::new(storage.clone()));
+
+        let first = block_on(transport.send(request(true))).unwrap();
+        assert_eq!(first.body, "[1]");
+        assert!(storage.value(&format!("etag:{URL}")).is_some());
+
+        let second = block_on(transport.send(request(true))).unwrap();
+        assert_eq!(second.status_code, 200);
+        assert_eq!(second.body, "[1]");
+        assert_eq!(second.header("Link"), Some("<next>"));
+        assert_eq!(second.header("X-RateLimit-Remaining"), Some("59"));
+
+        let requests = inner.requests();
+        assert_eq!(requests[0].header("If-None-Match"), None);
+        assert_eq!(requests[1].header("If-None-Match"), Some(r#""a""#));
+    }
+
+    #[test]
+    fn replaces_cached_response_if_modified() {
+        let inner = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                URL,
+                Response::new(200, "[2]").with_header("ETag", r#""b""#),
+            )
+            .with_response(Method::Get, URL, Response::new(304, ""));
+
+        let storage = MemoryStorage::new().with_value(
+            &format!("etag:{URL}"),
+            r#"{ "etag": "\"a\"", "headers": [], "body": "[1]" }"#,
+        );
+        let transport = EtagCacheTransport::new(&inner, Box::new(storage));
+
+        assert_eq!(block_on(transport.send(request(true))).unwrap().body, "[2]");
+        assert_eq!(block_on(transport.send(request(true))).unwrap().body, "[2]");
+        assert_eq!(inner.requests()[1].header("If-None-Match"), Some(r#""b""#));
+    }
+
+    #[test]
+    fn ignores_other_requests() {
+        let inner = FakeTransport::new().with_response(
+            Method::Get,
+            URL,
+            Response::new(200, "[1]").with_header("ETag", r#""a""#),
+        );
+
+        let storage = MemoryStorage::new();
+        let transport = EtagCacheTransport::new(&inner, Box::new(storage.clone()));
+
+        block_on(transport.send(request(false))).unwrap();
+        assert_eq!(storage.value(&format!("etag:{URL}")), None);
+    }
+}
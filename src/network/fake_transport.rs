@@ -0,0 +1,77 @@
+use std::{cell::RefCell, collections::VecDeque};
+
+use anyhow::anyhow;
+use worker::Method;
+
+use super::{Request, Response, Transport};
+use crate::utils::BoxFuture;
+
+/// Answers requests with canned responses and remembers every request it received.
+///
+/// Responses registered for the same method and URL are returned in order, the last one repeating.
+#[derive(Default)]
+pub struct FakeTransport {
+    responses: RefCell<Vec<(Method, String, VecDeque<Response>)>>,
+    requests: RefCell<Vec<Request>>,
+}
+
+impl FakeTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_response(self, method: Method, url: &str, response: Response) -> Self {
+        self.add_response(method, url, response);
+        self
+    }
+
+    pub fn add_response(&self, method: Method, url: &str, response: Response) {
+        let mut responses = self.responses.borrow_mut();
+
+        match responses
+            .iter_mut()
+            .find(|(existing_method, existing_url, _)| {
+                *existing_method == method && existing_url == url
+            }) {
+            Some((_, _, queue)) => queue.push_back(response),
+            None => responses.push((method, url.to_string(), VecDeque::from([response]))),
+        }
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.borrow().clone()
+    }
+
+    pub fn requests_with_method(&self, method: Method) -> Vec<Request> {
+        self.requests
+            .borrow()
+            .iter()
+            .filter(|request| request.method == method)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Transport for FakeTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        let result = {
+            let mut responses = self.responses.borrow_mut();
+
+            responses
+                .iter_mut()
+                .find(|(method, url, _)| *method == request.method && url == request.url.as_str())
+                .map(|(_, _, queue)| {
+                    if queue.len() > 1 {
+                        queue.pop_front().unwrap()
+                    } else {
+                        queue.front().unwrap().clone()
+                    }
+                })
+                .ok_or_else(|| anyhow!("no fake response for {:?} {}", request.method, request.url))
+        };
+
+        self.requests.borrow_mut().push(request);
+
+        Box::pin(async move { result })
+    }
+}
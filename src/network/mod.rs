@@ -0,0 +1,101 @@
+use std::fmt;
+
+use anyhow::Context;
+use serde::de::DeserializeOwned;
+use worker::{Method, Url};
+
+mod etag_cache;
+#[cfg(test)]
+mod fake_transport;
+#[cfg(feature = "cli")]
+mod reqwest_transport;
+mod retry;
+mod run_context;
+mod subrequest_budget;
+mod transport;
+mod worker_transport;
+
+pub use etag_cache::*;
+#[cfg(test)]
+pub use fake_transport::*;
+#[cfg(feature = "cli")]
+pub use reqwest_transport::*;
+pub use retry::*;
+pub use run_context::*;
+pub use subrequest_budget::*;
+pub use transport::*;
+pub use worker_transport::*;
+
+pub const USER_AGENT: &str = "updates-bot";
+
+pub async fn get_json_from_request<T: DeserializeOwned>(
+    transport: &dyn Transport,
+    request: Request,
+) -> anyhow::Result<T> {
+    let response = fetch(transport, request).await?;
+    json_from_response(&response)
+}
+
+pub async fn fetch(transport: &dyn Transport, request: Request) -> anyhow::Result<Response> {
+    let result = transport.send(request).await.context("could not fetch");
+
+    if let Ok(response) = &result {
+        tracing::debug!(response.status_code = response.status_code);
+
+        if !response.is_success() {
+            tracing::warn!("status code not 2xx: {}", response.status_code);
+        }
+    }
+
+    result
+}
+
+pub fn json_from_response<T: DeserializeOwned>(response: &Response) -> anyhow::Result<T> {
+    serde_json::from_str(&response.body).context("could not get JSON")
+}
+
+pub enum ContentType {
+    ApplicationJson,
+    MultipartFormData(String),
+    TextPlain,
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ContentType::ApplicationJson => String::from("application/json"),
+                ContentType::MultipartFormData(boundary) =>
+                    format!(r#"multipart/form-data; boundary="{boundary}""#),
+                ContentType::TextPlain => String::from("text/plain"),
+            }
+        )
+    }
+}
+
+pub fn create_request(
+    url: Url,
+    method: Method,
+    content_type: ContentType,
+    accept: ContentType,
+    body: Option<String>,
+    discourse_api_key: Option<&str>,
+) -> anyhow::Result<Request> {
+    tracing::debug!(url.domain = url.domain(), ?method, "creating request");
+
+    let mut request = Request::new(url, method);
+
+    if let Some(api_key) = discourse_api_key {
+        request.set_header("User-Api-Key", api_key);
+    }
+
+    request.set_header("Content-Type", &content_type.to_string());
+    request.set_header("Accept", &accept.to_string());
+    request.set_header("User-Agent", USER_AGENT);
+
+    request.body = body;
+
+    Ok(request)
+}
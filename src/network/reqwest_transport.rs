@@ -0,0 +1,60 @@
+use anyhow::Context;
+
+use super::{Request, Response, Transport};
+use crate::utils::BoxFuture;
+
+/// Sends requests using a blocking [`reqwest`] client, for the native command-line binary.
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> anyhow::Result<Self> {
+        let client = reqwest::blocking::Client::builder()
+            .build()
+            .context("could not create HTTP client")?;
+
+        Ok(Self { client })
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        Box::pin(async move {
+            let method = reqwest::Method::from_bytes(request.method.as_ref().as_bytes())
+                .context("invalid method")?;
+
+            let mut builder = self.client.request(method, request.url.as_str());
+
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().context("could not send request")?;
+
+            let status_code = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (name.to_string(), value.to_string()))
+                })
+                .collect();
+
+            let body = response.text().context("couldn't get text")?;
+
+            Ok(Response {
+                status_code,
+                headers,
+                body,
+            })
+        })
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use chrono::DateTime;
+
+use super::{Request, Response, Transport};
+use crate::utils::{self, BoxFuture};
+
+/// Which failures of a request can be retried without risking doing something twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    Never,
+    /// Only retried when the server says it didn't process the request because of rate limiting.
+    OnRateLimit,
+    /// Retried on rate limiting, server errors, and failures to get a response at all.
+    Always,
+}
+
+impl Retry {
+    pub fn default_for(method: &worker::Method) -> Self {
+        use worker::Method::*;
+
+        match method {
+            Get | Head | Options | Put | Delete => Retry::Always,
+            _ => Retry::Never,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Including the first attempt.
+    pub max_attempts: u32,
+    pub initial_delay_milliseconds: u64,
+    /// Longest delay between attempts. If the server asks to wait longer, the request is not retried.
+    pub max_delay_milliseconds: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_milliseconds: 1000,
+            max_delay_milliseconds: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff for the retry with the given number (starting at 1), where `jitter` in
+    /// `0.0..1.0` randomizes the upper half of the delay.
+    fn backoff_milliseconds(&self, retry_number: u32, jitter: f64) -> u64 {
+        let delay = self
+            .initial_delay_milliseconds
+            .saturating_mul(1 << (retry_number - 1).min(32))
+            .min(self.max_delay_milliseconds);
+
+        delay / 2 + ((delay - delay / 2) as f64 * jitter) as u64
+    }
+}
+
+/// Retries failed requests according to their [`Retry`] and a [`RetryPolicy`].
+pub struct RetryingTransport<'a> {
+    inner: &'a dyn Transport,
+    policy: RetryPolicy,
+}
+
+impl<'a> RetryingTransport<'a> {
+    pub fn new(inner: &'a dyn Transport, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl Transport for RetryingTransport<'_> {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        Box::pin(async move {
+            let mut attempt = 1;
+
+            loop {
+                let result = self.inner.send(request.clone()).await;
+
+                let reason = match retry_reason(request.retry, &result) {
+                    Some(reason) if attempt < self.policy.max_attempts => reason,
+                    _ => return result,
+                };
+
+                let retry_after = result
+                    .as_ref()
+                    .ok()
+                    .and_then(|response| response.header("Retry-After"))
+                    .and_then(parse_retry_after);
+
+                let delay_milliseconds = match retry_after {
+                    Some(delay) if delay > self.policy.max_delay_milliseconds => {
+                        tracing::warn!(
+                            url.domain = request.url.domain(),
+                            retry_after_milliseconds = delay,
+                            "server asked to wait too long; not retrying"
+                        );
+                        return result;
+                    }
+                    Some(delay) => delay,
+                    None => self.policy.backoff_milliseconds(attempt, jitter()),
+                };
+
+                tracing::warn!(
+                    url.domain = request.url.domain(),
+                    method = request.method.as_ref(),
+                    attempt,
+                    max_attempts = self.policy.max_attempts,
+                    delay_milliseconds,
+                    "request failed ({reason}); retrying"
+                );
+
+                utils::delay(delay_milliseconds).await;
+                attempt += 1;
+            }
+        })
+    }
+}
+
+fn retry_reason(retry: Retry, result: &anyhow::Result<Response>) -> Option<String> {
+    match (retry, result) {
+        (Retry::Never, _) => None,
+        (_, Ok(response)) if response.status_code == 429 => Some(String::from("status code 429")),
+        (Retry::Always, Ok(response))
+            if [408, 500, 502, 503, 504].contains(&response.status_code) =>
+        {
+            Some(format!("status code {}", response.status_code))
+        }
+        (Retry::Always, Err(error)) => Some(format!("{error:#}")),
+        _ => None,
+    }
+}
+
+/// Parses the value of a `Retry-After` header, either in seconds or as a date, to milliseconds.
+fn parse_retry_after(value: &str) -> Option<u64> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds.saturating_mul(1000));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let now = utils::now().duration_since(UNIX_EPOCH).ok()?;
+    let until = Duration::from_secs(date.timestamp().try_into().ok()?);
+
+    Some(until.saturating_sub(now).as_millis() as u64)
+}
+
+/// Cheap randomness for spreading out retries, which doesn't need to be unpredictable.
+fn jitter() -> f64 {
+    let nanos = utils::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.subsec_nanos())
+        .unwrap_or_default();
+
+    nanos as f64 / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{create_request, ContentType, FakeTransport},
+        utils::block_on,
+    };
+
+    const URL: &str = "https://example.com/";
+
+    const POLICY: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        initial_delay_milliseconds: 0,
+        max_delay_milliseconds: 10_000,
+    };
+
+    fn request(method: Method) -> Request {
+        create_request(
+            URL.parse().unwrap(),
+            method,
+            ContentType::TextPlain,
+            ContentType::TextPlain,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn send(inner: &FakeTransport, request: Request) -> u16 {
+        let transport = RetryingTransport::new(inner, POLICY);
+        block_on(transport.send(request)).unwrap().status_code
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let inner = FakeTransport::new()
+            .with_response(Method::Get, URL, Response::new(502, ""))
+            .with_response(Method::Get, URL, Response::new(200, ""));
+
+        assert_eq!(send(&inner, request(Method::Get)), 200);
+        assert_eq!(inner.requests().len(), 2);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let inner = FakeTransport::new().with_response(Method::Get, URL, Response::new(503, ""));
+
+        assert_eq!(send(&inner, request(Method::Get)), 503);
+        assert_eq!(inner.requests().len(), 3);
+    }
+
+    #[test]
+    fn does_not_retry_client_errors() {
+        let inner = FakeTransport::new().with_response(Method::Get, URL, Response::new(404, ""));
+
+        assert_eq!(send(&inner, request(Method::Get)), 404);
+        assert_eq!(inner.requests().len(), 1);
+    }
+
+    #[test]
+    fn does_not_retry_unsafe_requests() {
+        let inner = FakeTransport::new()
+            .with_response(Method::Post, URL, Response::new(502, ""))
+            .with_response(Method::Post, URL, Response::new(200, ""));
+
+        let mut request = request(Method::Post);
+        assert_eq!(send(&inner, request.clone()), 502);
+
+        request.retry = Retry::OnRateLimit;
+        assert_eq!(send(&inner, request), 200);
+
+        assert_eq!(inner.requests().len(), 2);
+    }
+
+    #[test]
+    fn retries_rate_limited_requests() {
+        let inner = FakeTransport::new()
+            .with_response(
+                Method::Post,
+                URL,
+                Response::new(429, "").with_header("Retry-After", "0"),
+            )
+            .with_response(Method::Post, URL, Response::new(200, ""));
+
+        let mut request = request(Method::Post);
+        request.retry = Retry::OnRateLimit;
+
+        assert_eq!(send(&inner, request), 200);
+        assert_eq!(inner.requests().len(), 2);
+    }
+
+    #[test]
+    fn does_not_wait_longer_than_max_delay() {
+        let inner = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                URL,
+                Response::new(429, "").with_header("Retry-After", "3600"),
+            )
+            .with_response(Method::Get, URL, Response::new(200, ""));
+
+        assert_eq!(send(&inner, request(Method::Get)), 429);
+        assert_eq!(inner.requests().len(), 1);
+    }
+
+    #[test]
+    fn retries_failures_to_get_a_response() {
+        let inner = FakeTransport::new();
+
+        let transport = RetryingTransport::new(&inner, POLICY);
+        assert!(block_on(transport.send(request(Method::Get))).is_err());
+        assert_eq!(inner.requests().len(), 3);
+    }
+
+    #[test_case("120", Some(120_000); "seconds")]
+    #[test_case(" 0 ", Some(0); "zero")]
+    #[test_case("Wed, 21 Oct 2015 07:28:00 GMT", Some(0); "date in the past")]
+    #[test_case("soon", None; "invalid")]
+    fn parse_retry_after(value: &str, milliseconds: Option<u64>) {
+        assert_eq!(super::parse_retry_after(value), milliseconds);
+    }
+
+    #[test_case(1, 0.0, 500)]
+    #[test_case(1, 0.5, 750)]
+    #[test_case(2, 0.0, 1000)]
+    #[test_case(3, 0.999, 3998)]
+    #[test_case(10, 0.0, 15_000; "capped")]
+    fn backoff(retry_number: u32, jitter: f64, milliseconds: u64) {
+        assert_eq!(
+            RetryPolicy::default().backoff_milliseconds(retry_number, jitter),
+            milliseconds
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::github::RateLimit;
+
+/// The GitHub API rate limit reported in the latest response, see
+/// [`crate::github::GitHubTransport::rate_limit`].
+pub type RateLimitHandle = Rc<Cell<Option<RateLimit>>>;
+
+/// How many more requests can be sent, see [`super::SubrequestBudgetTransport::remaining`].
+pub type SubrequestBudgetHandle = Rc<Cell<u64>>;
+
+/// What a run knows about how it's sending requests, for deciding what's worth doing: whether it's
+/// a dry run, and the limits that the transports it sends requests through keep track of.
+#[derive(Clone, Default)]
+pub struct RunContext {
+    /// Whether requests that change something are only recorded instead of being sent, and
+    /// answered with an empty JSON object (see [`crate::dry_run::DryRunTransport`]).
+    pub dry_run: bool,
+    github_rate_limit: Option<RateLimitHandle>,
+    subrequest_budget: Option<SubrequestBudgetHandle>,
+}
+
+impl RunContext {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_github_rate_limit(mut self, rate_limit: RateLimitHandle) -> Self {
+        self.github_rate_limit = Some(rate_limit);
+        self
+    }
+
+    pub fn with_subrequest_budget(mut self, budget: SubrequestBudgetHandle) -> Self {
+        self.subrequest_budget = Some(budget);
+        self
+    }
+
+    /// The GitHub API rate limit, if known.
+    pub fn github_rate_limit(&self) -> Option<RateLimit> {
+        self.github_rate_limit
+            .as_ref()
+            .and_then(|rate_limit| rate_limit.get())
+    }
+
+    /// How many more requests can be sent in this run, if that's limited.
+    pub fn subrequests_left(&self) -> Option<u64> {
+        self.subrequest_budget.as_ref().map(|budget| budget.get())
+    }
+}
@@ -0,0 +1,74 @@
+use std::{cell::Cell, rc::Rc};
+
+use anyhow::bail;
+
+use super::{Request, Response, SubrequestBudgetHandle, Transport};
+use crate::utils::BoxFuture;
+
+/// How many subrequests a Worker on the free plan can make per invocation.
+pub const DEFAULT_SUBREQUEST_LIMIT: u64 = 50;
+
+/// Counts requests against the limit of subrequests per invocation, so that optional work can be
+/// cut short while there are still requests left for posting. Requests beyond the limit fail
+/// without being sent.
+pub struct SubrequestBudgetTransport<'a> {
+    inner: &'a dyn Transport,
+    remaining: SubrequestBudgetHandle,
+}
+
+impl<'a> SubrequestBudgetTransport<'a> {
+    pub fn new(inner: &'a dyn Transport, limit: u64) -> Self {
+        Self {
+            inner,
+            remaining: Rc::new(Cell::new(limit)),
+        }
+    }
+
+    /// Shares how many requests are left, like with [`super::RunContext`].
+    pub fn remaining(&self) -> SubrequestBudgetHandle {
+        self.remaining.clone()
+    }
+}
+
+impl Transport for SubrequestBudgetTransport<'_> {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        let remaining = self.remaining.get();
+
+        if remaining == 0 {
+            return Box::pin(async move {
+                bail!(
+                    "subrequest limit reached, not sending request to {}",
+                    request.url
+                )
+            });
+        }
+
+        self.remaining.set(remaining - 1);
+        self.inner.send(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use worker::Method;
+
+    use super::*;
+    use crate::{network::FakeTransport, utils::block_on};
+
+    #[test]
+    fn stops_at_limit() {
+        let url = "https://example.com/";
+        let inner = FakeTransport::new().with_response(Method::Get, url, Response::new(200, ""));
+        let transport = SubrequestBudgetTransport::new(&inner, 2);
+
+        let send = || block_on(transport.send(Request::new(url.parse().unwrap(), Method::Get)));
+
+        assert!(send().is_ok());
+        assert_eq!(transport.remaining().get(), 1);
+        assert!(send().is_ok());
+        assert!(send().is_err());
+        assert_eq!(transport.remaining().get(), 0);
+        assert_eq!(inner.requests().len(), 2);
+    }
+}
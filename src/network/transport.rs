@@ -0,0 +1,86 @@
+use worker::{Method, Url};
+
+use super::Retry;
+use crate::utils::BoxFuture;
+
+/// Sends HTTP requests on behalf of the bot.
+///
+/// The Workers build uses [`super::WorkerTransport`], while tests and native builds can plug in
+/// anything else that is able to answer requests, like an in-process fake or a local HTTP server.
+pub trait Transport {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub url: Url,
+    pub method: Method,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    /// Defaults to [`Retry::default_for`] the method.
+    pub retry: Retry,
+    /// Whether to make the request conditional, reusing the previous response if it's unchanged
+    /// (see [`super::EtagCacheTransport`]).
+    pub cache_with_etag: bool,
+}
+
+impl Request {
+    pub fn new(url: Url, method: Method) -> Self {
+        Self {
+            retry: Retry::default_for(&method),
+            url,
+            method,
+            headers: Vec::new(),
+            body: None,
+            cache_with_etag: false,
+        }
+    }
+
+    /// Replaces any existing values of the header `name` (compared case-insensitively).
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn new(status_code: u16, body: impl Into<String>) -> Self {
+        Self {
+            status_code,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..=299).contains(&self.status_code)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
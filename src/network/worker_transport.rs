@@ -0,0 +1,56 @@
+use anyhow::{anyhow, Context};
+use worker::{wasm_bindgen::JsValue, Fetch, Headers, RequestInit};
+
+use super::{Request, Response, Transport};
+use crate::utils::BoxFuture;
+
+/// Sends requests using the Workers runtime's `fetch`.
+pub struct WorkerTransport;
+
+impl Transport for WorkerTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, anyhow::Result<Response>> {
+        Box::pin(async move {
+            let mut headers = Headers::new();
+
+            for (name, value) in &request.headers {
+                headers
+                    .set(name, value)
+                    .map_err(|e| anyhow!(e.to_string()))
+                    .with_context(|| format!("could not set header {name}"))?;
+            }
+
+            let mut request_init = RequestInit::new();
+            request_init
+                .with_method(request.method)
+                .with_headers(headers);
+
+            if let Some(body) = request.body {
+                request_init.with_body(Some(JsValue::from_str(&body)));
+            }
+
+            let worker_request =
+                worker::Request::new_with_init(request.url.as_ref(), &request_init)
+                    .map_err(|e| anyhow!(e.to_string()))
+                    .context("could not create request")?;
+
+            let mut response = Fetch::Request(worker_request)
+                .send()
+                .await
+                .map_err(|e| anyhow!(e.to_string()))?;
+
+            let headers = response.headers().entries().collect();
+
+            let body = response
+                .text()
+                .await
+                .map_err(|e| anyhow!(e.to_string()))
+                .context("couldn't get text")?;
+
+            Ok(Response {
+                status_code: response.status_code(),
+                headers,
+                body,
+            })
+        })
+    }
+}
@@ -1,11 +1,25 @@
+use std::collections::BTreeSet;
+
 use anyhow::Context;
-use regex::RegexBuilder;
+use lazy_static::lazy_static;
+use regex::{Regex, RegexBuilder};
+
+lazy_static! {
+    static ref USES_PERMISSION_REGEX: Regex =
+        Regex::new(r#"<uses-permission(?:-sdk-23)?\s+android:name\s*=\s*"([^"]+)""#).unwrap();
+}
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BuildConfiguration {
     pub canonical_version_code: u64,
+    pub canonical_version_name: Option<String>,
     pub current_hotfix_version: u64,
     pub max_hotfix_versions: u64,
+    /// SDK levels are `None` if they aren't specified as literals in the file, like when they
+    /// refer to values defined elsewhere.
+    pub min_sdk: Option<u64>,
+    pub target_sdk: Option<u64>,
+    pub compile_sdk: Option<u64>,
 }
 
 impl BuildConfiguration {
@@ -28,13 +42,61 @@ impl BuildConfiguration {
             Self::get_u64(text, r"val \s+ maxHotfixVersions \s* = \s* (\d+)", 1)
                 .context("couldn't get max_hotfix_versions")?;
 
+        // Optional values
+
+        let canonical_version_name =
+            RegexBuilder::new(r#"val \s+ canonicalVersionName \s* = \s* "([^"]+)""#)
+                .ignore_whitespace(true)
+                .build()
+                .context("couldn't compile regex")?
+                .captures(text)
+                .map(|caps| caps[1].to_string());
+
+        let sdk = |name: &str| {
+            Self::get_u64(text, &format!(r"\b {name} (?:Version)? \s* = \s* (\d+)"), 1).ok()
+        };
+
         Ok(BuildConfiguration {
             canonical_version_code,
+            canonical_version_name,
             current_hotfix_version,
             max_hotfix_versions,
+            min_sdk: sdk("minSdk"),
+            target_sdk: sdk("targetSdk"),
+            compile_sdk: sdk("compileSdk"),
         })
     }
 
+    pub fn full_version_code(&self) -> u64 {
+        (self.canonical_version_code * self.max_hotfix_versions) + self.current_hotfix_version
+    }
+
+    /// Describes values that changed since `old`, like `minSdk 21 → 24`. Values that are unknown
+    /// in either of the configurations are skipped.
+    pub fn changes_since(&self, old: &Self) -> Vec<String> {
+        let sdk = |name: &str, old: Option<u64>, new: Option<u64>| match (old, new) {
+            (Some(old), Some(new)) if old != new => Some(format!("{name} {old} → {new}")),
+            _ => None,
+        };
+
+        let version_name = match (&old.canonical_version_name, &self.canonical_version_name) {
+            (Some(old), Some(new)) if old != new => {
+                Some(format!("canonicalVersionName {old} → {new}"))
+            }
+            _ => None,
+        };
+
+        [
+            version_name,
+            sdk("minSdk", old.min_sdk, self.min_sdk),
+            sdk("targetSdk", old.target_sdk, self.target_sdk),
+            sdk("compileSdk", old.compile_sdk, self.compile_sdk),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
     fn get_u64(text: &str, regex: &str, capture_number: usize) -> anyhow::Result<u64> {
         let re = RegexBuilder::new(regex)
             .ignore_whitespace(true)
@@ -46,11 +108,20 @@ impl BuildConfiguration {
     }
 }
 
+/// Permissions requested in `AndroidManifest.xml`.
+pub fn permissions_from_android_manifest(text: &str) -> BTreeSet<String> {
+    USES_PERMISSION_REGEX
+        .captures_iter(text)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::platform::HeaderChanges;
 
     #[test]
     fn build_configuration() {
@@ -69,9 +140,94 @@ mod tests {
             .unwrap(),
             BuildConfiguration {
                 canonical_version_code: 1428,
+                canonical_version_name: Some(String::from("7.9.6")),
                 current_hotfix_version: 0,
                 max_hotfix_versions: 100,
+                min_sdk: None,
+                target_sdk: None,
+                compile_sdk: None,
             }
         );
     }
+
+    #[test]
+    fn build_configuration_with_sdk_levels() {
+        let configuration = BuildConfiguration::from_app_build_gradle_kts(
+            r#"
+            val canonicalVersionCode = 1428
+            val currentHotfixVersion = 0
+            val maxHotfixVersions = 100
+
+            android {
+              compileSdkVersion = 34
+
+              defaultConfig {
+                minSdk = 21
+                targetSdk = signalTargetSdkVersion
+              }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            (
+                configuration.canonical_version_name,
+                configuration.min_sdk,
+                configuration.target_sdk,
+                configuration.compile_sdk
+            ),
+            (None, Some(21), None, Some(34))
+        );
+    }
+
+    fn test_build_configuration(
+        version_name: &str,
+        min_sdk: u64,
+        target_sdk: Option<u64>,
+    ) -> BuildConfiguration {
+        BuildConfiguration {
+            canonical_version_code: 1428,
+            canonical_version_name: Some(version_name.to_string()),
+            current_hotfix_version: 0,
+            max_hotfix_versions: 100,
+            min_sdk: Some(min_sdk),
+            target_sdk,
+            compile_sdk: Some(34),
+        }
+    }
+
+    #[test]
+    fn changes_since() {
+        let old = test_build_configuration("7.9.6", 21, Some(33));
+        let new = test_build_configuration("7.10.0", 24, None);
+
+        assert_eq!(
+            new.changes_since(&old),
+            ["canonicalVersionName 7.9.6 → 7.10.0", "minSdk 21 → 24"]
+        );
+    }
+
+    #[test]
+    fn permissions() {
+        let old = permissions_from_android_manifest(
+            r#"
+  <uses-permission android:name="android.permission.CAMERA" />
+  <uses-permission android:name="android.permission.READ_CONTACTS"/>
+  <uses-permission-sdk-23 android:name="android.permission.BLUETOOTH" />"#,
+        );
+        let new = permissions_from_android_manifest(
+            r#"
+  <uses-permission android:name="android.permission.CAMERA" />
+  <uses-permission android:name="android.permission.BLUETOOTH_CONNECT" />
+  <uses-permission-sdk-23 android:name="android.permission.BLUETOOTH" />"#,
+        );
+
+        let mut changes = HeaderChanges::default();
+        changes.set_permissions(&old, &new);
+
+        assert_eq!(
+            changes.markdown_text(),
+            "\nPermissions added: `android.permission.BLUETOOTH_CONNECT`\nPermissions removed: `android.permission.READ_CONTACTS`"
+        );
+    }
 }
@@ -0,0 +1,139 @@
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::{android, ios, Platform};
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildConfigurationSourceKind {
+    /// `canonicalVersionCode` and friends in an Android `app/build.gradle.kts`.
+    AppBuildGradleKts,
+    /// `CFBundleVersion` and `CFBundleShortVersionString` in an iOS `Info.plist`.
+    InfoPlist,
+}
+
+/// A file in a platform's repository that the build configuration can be read from (see
+/// [`crate::platform::PlatformDefinition::build_configuration_source`]).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BuildConfigurationSource {
+    pub path: String,
+    pub kind: BuildConfigurationSourceKind,
+    /// `project.pbxproj` to resolve build settings that an `Info.plist` refers to, like
+    /// `$(CURRENT_PROJECT_VERSION)`.
+    #[serde(default)]
+    pub project_path: Option<String>,
+}
+
+impl BuildConfigurationSource {
+    pub fn new(path: &str, kind: BuildConfigurationSourceKind) -> Self {
+        Self {
+            path: path.to_string(),
+            kind,
+            project_path: None,
+        }
+    }
+
+    pub fn for_platform(platform: Platform) -> Option<&'static Self> {
+        platform.definition().build_configuration_source.as_ref()
+    }
+
+    /// Whether changes between configurations of this kind are called out in the post header (see
+    /// [`BuildConfiguration::changes_since`]).
+    pub fn has_header_changes(&self) -> bool {
+        self.kind == BuildConfigurationSourceKind::AppBuildGradleKts
+    }
+}
+
+/// Build metadata read from a platform's sources at a tag.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildConfiguration {
+    Android(android::BuildConfiguration),
+    Ios(ios::BuildConfiguration),
+}
+
+impl BuildConfiguration {
+    /// Returns `None` if the platform has no [`BuildConfigurationSource`].
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(source) = BuildConfigurationSource::for_platform(platform) else {
+            return Ok(None);
+        };
+
+        let get_file = |path: &'static str| async move {
+            github::get_file_content(transport, platform, &tag.name, path)
+                .await
+                .with_context(|| format!("couldn't get {path} at {}", tag.name))
+        };
+
+        let text = get_file(&source.path).await?;
+
+        let configuration = match source.kind {
+            BuildConfigurationSourceKind::AppBuildGradleKts => Self::Android(
+                android::BuildConfiguration::from_app_build_gradle_kts(&text)
+                    .context("couldn't parse build configuration")?,
+            ),
+            BuildConfigurationSourceKind::InfoPlist => {
+                let project_pbxproj = match &source.project_path {
+                    Some(project_path)
+                        if ios::BuildConfiguration::refers_to_build_settings(&text) =>
+                    {
+                        Some(get_file(project_path).await?)
+                    }
+                    _ => None,
+                };
+
+                Self::Ios(
+                    ios::BuildConfiguration::from_info_plist(&text, project_pbxproj.as_deref())
+                        .context("couldn't parse build configuration")?,
+                )
+            }
+        };
+
+        Ok(Some(configuration))
+    }
+
+    /// Upper bound of requests [`Self::get`] makes to get the configuration at the old tag for
+    /// [`Self::changes_since`].
+    pub fn estimated_changes_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        match BuildConfigurationSource::for_platform(platform) {
+            Some(source)
+                if source.has_header_changes()
+                    && comparison.might_have_changed(&[&source.path]) =>
+            {
+                1
+            }
+            _ => 0,
+        }
+    }
+
+    /// Describes values that changed since `old`, like `minSdk 21 → 24`.
+    pub fn changes_since(&self, old: &Self) -> Vec<String> {
+        match (self, old) {
+            (Self::Android(new), Self::Android(old)) => new.changes_since(old),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The number shown in the post header.
+    pub fn full_build_number(&self) -> String {
+        match self {
+            Self::Android(configuration) => configuration.full_version_code().to_string(),
+            Self::Ios(configuration) => configuration.build_number.clone(),
+        }
+    }
+
+    /// The number shown in the Discord "Build number" field.
+    pub fn build_number(&self) -> String {
+        match self {
+            Self::Android(configuration) => configuration.canonical_version_code.to_string(),
+            Self::Ios(configuration) => configuration.build_number.clone(),
+        }
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, ensure};
+use semver::Version;
+use serde::Deserialize;
+
+use crate::{
+    database_version::{DatabaseVersionSource, DatabaseVersionSourceKind},
+    dependency::{Dependency, Manifest, ManifestKind},
+    feature_flag::{FlagSource, FlagSourceKind},
+    localization::LocalizationLayout,
+    platform::{
+        BuildConfigurationSource, BuildConfigurationSourceKind, HeaderSource, HeaderSourceKind,
+    },
+};
+
+/// Everything the bot needs to know to post about a repository.
+///
+/// The built-in definitions are returned by [`PlatformDefinition::builtin`], and can be replaced or
+/// extended with the `PLATFORMS` variable (see [`crate::config::Config::from_env`]).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlatformDefinition {
+    /// Used for the platform's state and in URLs of the bot's endpoints, like `android`.
+    pub key: String,
+    /// Used in posts and messages, like `Android`.
+    pub name: String,
+    /// Like `signalapp/Signal-Android`.
+    pub repository: String,
+
+    #[serde(default)]
+    pub tag_filter: TagFilter,
+    /// Commits whose message contains any of these are not listed in posts.
+    #[serde(default)]
+    pub hidden_commit_messages: Vec<String>,
+    /// Whether to include the rest of commit messages after the first line.
+    #[serde(default = "default_show_commit_details")]
+    pub show_commit_details: bool,
+
+    pub topic: TopicStrategy,
+    /// Name of the variable with the role ID to mention in Discord.
+    #[serde(default = "default_discord_mention_role")]
+    pub discord_mention_role: String,
+    /// Color of the Discord embed, like `1935971` (`0x1d8663`).
+    pub color: u64,
+
+    #[serde(default)]
+    pub localization: LocalizationLayout,
+    /// Whether to show how much of each changed language is translated.
+    #[serde(default)]
+    pub show_translation_coverage: bool,
+
+    /// Files listing the packages the platform depends on, whose changes are summarized in posts.
+    #[serde(default)]
+    pub manifests: Vec<Manifest>,
+    /// Names of packages in the platform's manifests that are [`Dependency`] libraries, like
+    /// `{ "org.signal:ringrtc-android": "ringrtc" }`.
+    #[serde(default)]
+    pub native_dependencies: BTreeMap<String, Dependency>,
+    /// Files declaring feature flags, whose changes are listed in posts. Only the first one that
+    /// exists at a tag is used, so files that replaced each other over time can be given in order.
+    #[serde(default)]
+    pub flag_sources: Vec<FlagSource>,
+    /// File the version of the app's local database schema is read from, to call out changes.
+    #[serde(default)]
+    pub database_version_source: Option<DatabaseVersionSource>,
+    /// Files that changes of permissions and the Electron version are read from, to call them out
+    /// in the post header.
+    #[serde(default)]
+    pub header_sources: Vec<HeaderSource>,
+    /// File the build number shown in posts is read from.
+    #[serde(default)]
+    pub build_configuration_source: Option<BuildConfigurationSource>,
+    /// File with the English release notes of the store listing, quoted in posts when they change.
+    #[serde(default)]
+    pub release_notes_path: Option<String>,
+}
+
+fn default_show_commit_details() -> bool {
+    true
+}
+
+fn default_discord_mention_role() -> String {
+    String::from("DISCORD_UPDATES_MENTION_ROLE")
+}
+
+/// Which tags are posted about.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TagFilter {
+    #[default]
+    All,
+    /// Versions like `1.2.3.4` are filtered out (the `4` is parsed into build metadata).
+    WithoutBuildMetadata,
+    /// Versions without prerelease or build metadata parts, like `0.52.3`.
+    Release,
+    PrereleaseContains {
+        value: String,
+    },
+}
+
+impl TagFilter {
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            TagFilter::All => true,
+            TagFilter::WithoutBuildMetadata => version.build.is_empty(),
+            TagFilter::Release => version.pre.is_empty() && version.build.is_empty(),
+            TagFilter::PrereleaseContains { value } => version.pre.contains(value),
+        }
+    }
+}
+
+/// Where posts about new versions go.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TopicStrategy {
+    /// A "Beta feedback for the upcoming … release" topic for each release. Once a new one is used,
+    /// the previous one gets an archiving message.
+    BetaFeedbackTopics,
+    /// A single topic, whose ID is in the given variable.
+    FixedTopic { topic_id_variable: String },
+}
+
+impl PlatformDefinition {
+    pub fn builtin() -> Vec<Self> {
+        let definition = |key: &str, name: &str, topic, color, localization| Self {
+            key: key.to_string(),
+            name: name.to_string(),
+            repository: format!("signalapp/Signal-{name}"),
+            tag_filter: TagFilter::All,
+            hidden_commit_messages: Vec::new(),
+            show_commit_details: true,
+            topic,
+            discord_mention_role: default_discord_mention_role(),
+            color,
+            localization,
+            show_translation_coverage: false,
+            manifests: Vec::new(),
+            native_dependencies: BTreeMap::new(),
+            flag_sources: Vec::new(),
+            database_version_source: None,
+            header_sources: Vec::new(),
+            build_configuration_source: None,
+            release_notes_path: None,
+        };
+
+        let native_dependencies = |dependencies: &[(&str, Dependency)]| {
+            dependencies
+                .iter()
+                .map(|(name, dependency)| (name.to_string(), *dependency))
+                .collect()
+        };
+
+        let beta_filter = TagFilter::PrereleaseContains {
+            value: String::from("beta"),
+        };
+
+        vec![
+            Self {
+                tag_filter: TagFilter::WithoutBuildMetadata,
+                manifests: vec![Manifest::new(
+                    "gradle/libs.versions.toml",
+                    ManifestKind::GradleVersionCatalog,
+                )],
+                native_dependencies: native_dependencies(&[
+                    ("org.signal:libsignal-android", Dependency::Libsignal),
+                    ("org.signal:libsignal-client", Dependency::Libsignal),
+                    ("org.signal:ringrtc-android", Dependency::RingRtc),
+                ]),
+                flag_sources: vec![
+                    FlagSource::new(
+                        "app/src/main/java/org/thoughtcrime/securesms/util/RemoteConfig.kt",
+                        FlagSourceKind::Kotlin,
+                    ),
+                    FlagSource::new(
+                        "app/src/main/java/org/thoughtcrime/securesms/util/FeatureFlags.kt",
+                        FlagSourceKind::Kotlin,
+                    ),
+                ],
+                database_version_source: Some(DatabaseVersionSource::new(
+                    "app/src/main/java/org/thoughtcrime/securesms/database/helpers/SignalDatabaseMigrations.kt",
+                    DatabaseVersionSourceKind::DatabaseVersionConstant,
+                )),
+                header_sources: vec![HeaderSource::new(
+                    "app/src/main/AndroidManifest.xml",
+                    HeaderSourceKind::AndroidManifest,
+                )],
+                build_configuration_source: Some(BuildConfigurationSource::new(
+                    "app/build.gradle.kts",
+                    BuildConfigurationSourceKind::AppBuildGradleKts,
+                )),
+                release_notes_path: Some(String::from(
+                    "app/src/main/play/release-notes/en-US/default.txt",
+                )),
+                ..definition(
+                    "android",
+                    "Android",
+                    TopicStrategy::BetaFeedbackTopics,
+                    0x1d8663,
+                    LocalizationLayout::Android,
+                )
+            },
+            Self {
+                tag_filter: beta_filter.clone(),
+                hidden_commit_messages: vec![
+                    String::from("Bump build to"),
+                    String::from("Feature flags for"),
+                ],
+                show_commit_details: false,
+                manifests: vec![
+                    Manifest::new("Podfile.lock", ManifestKind::PodfileLock),
+                    Manifest::new(
+                        "Signal.xcworkspace/xcshareddata/swiftpm/Package.resolved",
+                        ManifestKind::PackageResolved,
+                    ),
+                ],
+                native_dependencies: native_dependencies(&[
+                    ("LibSignalClient", Dependency::Libsignal),
+                    ("SignalRingRTC", Dependency::RingRtc),
+                ]),
+                flag_sources: vec![
+                    FlagSource::new(
+                        "SignalServiceKit/Util/FeatureFlags.swift",
+                        FlagSourceKind::Swift,
+                    ),
+                    FlagSource::new(
+                        "SignalServiceKit/src/Util/FeatureFlags.swift",
+                        FlagSourceKind::Swift,
+                    ),
+                ],
+                database_version_source: Some(DatabaseVersionSource::new(
+                    "SignalServiceKit/Storage/Database/GRDBSchemaMigrator.swift",
+                    DatabaseVersionSourceKind::GrdbSchemaVersion,
+                )),
+                header_sources: vec![HeaderSource::new(
+                    "Signal/Signal-Info.plist",
+                    HeaderSourceKind::InfoPlist,
+                )],
+                build_configuration_source: Some(BuildConfigurationSource {
+                    project_path: Some(String::from("Signal.xcodeproj/project.pbxproj")),
+                    ..BuildConfigurationSource::new(
+                        "Signal/Signal-Info.plist",
+                        BuildConfigurationSourceKind::InfoPlist,
+                    )
+                }),
+                release_notes_path: Some(String::from(
+                    "fastlane/metadata/en-US/release_notes.txt",
+                )),
+                ..definition(
+                    "ios",
+                    "iOS",
+                    TopicStrategy::BetaFeedbackTopics,
+                    0x336ba3,
+                    LocalizationLayout::Ios,
+                )
+            },
+            Self {
+                tag_filter: beta_filter,
+                manifests: vec![Manifest::new("package.json", ManifestKind::PackageJson)],
+                native_dependencies: native_dependencies(&[
+                    ("@signalapp/libsignal-client", Dependency::Libsignal),
+                    ("@signalapp/ringrtc", Dependency::RingRtc),
+                ]),
+                flag_sources: vec![FlagSource::new(
+                    "ts/RemoteConfig.ts",
+                    FlagSourceKind::TypeScript,
+                )],
+                database_version_source: Some(DatabaseVersionSource::new(
+                    "ts/sql/migrations/index.ts",
+                    DatabaseVersionSourceKind::SchemaVersionMigrations,
+                )),
+                header_sources: vec![HeaderSource::new(
+                    "package.json",
+                    HeaderSourceKind::PackageJson,
+                )],
+                ..definition(
+                    "desktop",
+                    "Desktop",
+                    TopicStrategy::BetaFeedbackTopics,
+                    0xaa377a,
+                    LocalizationLayout::Desktop,
+                )
+            },
+            Self {
+                discord_mention_role: String::from("DISCORD_SERVER_UPDATES_MENTION_ROLE"),
+                manifests: vec![Manifest::new("pom.xml", ManifestKind::PomXml)],
+                native_dependencies: native_dependencies(&[
+                    ("org.signal:libsignal-client", Dependency::Libsignal),
+                    ("org.signal:libsignal-server", Dependency::Libsignal),
+                ]),
+                ..definition(
+                    "server",
+                    "Server",
+                    TopicStrategy::FixedTopic {
+                        topic_id_variable: String::from("TOPIC_ID_FOR_SERVER_UPDATES"),
+                    },
+                    0x6058ca,
+                    LocalizationLayout::Server,
+                )
+            },
+            Self {
+                repository: String::from("signalapp/libsignal"),
+                tag_filter: TagFilter::Release,
+                discord_mention_role: String::from("DISCORD_LIBSIGNAL_UPDATES_MENTION_ROLE"),
+                ..definition(
+                    "libsignal",
+                    "libsignal",
+                    TopicStrategy::FixedTopic {
+                        topic_id_variable: String::from("TOPIC_ID_FOR_LIBSIGNAL_UPDATES"),
+                    },
+                    0x3a76f0,
+                    LocalizationLayout::Untracked,
+                )
+            },
+        ]
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            !self.key.is_empty()
+                && self
+                    .key
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+            "platform key should consist of lowercase letters, digits and dashes: {}",
+            self.key
+        );
+
+        ensure!(!self.name.is_empty(), "name for {} is empty", self.key);
+
+        if self
+            .repository
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .count()
+            != 2
+        {
+            bail!(
+                "repository for {} is not in the owner/name format: {}",
+                self.key,
+                self.repository
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test]
+    fn deserialize_with_defaults() {
+        let definition: PlatformDefinition = serde_json::from_str(
+            r#"{
+                "key": "libsignal",
+                "name": "libsignal",
+                "repository": "signalapp/libsignal",
+                "topic": { "kind": "fixed_topic", "topic_id_variable": "TOPIC_ID_FOR_LIBSIGNAL" },
+                "color": 1
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(definition.tag_filter, TagFilter::All);
+        assert!(definition.show_commit_details);
+        assert_eq!(
+            definition.discord_mention_role,
+            "DISCORD_UPDATES_MENTION_ROLE"
+        );
+        assert_eq!(definition.localization, LocalizationLayout::Untracked);
+        assert!(!definition.show_translation_coverage);
+        assert!(definition.manifests.is_empty());
+        assert!(definition.native_dependencies.is_empty());
+        assert!(definition.flag_sources.is_empty());
+        assert_eq!(definition.database_version_source, None);
+        assert!(definition.header_sources.is_empty());
+        assert_eq!(definition.build_configuration_source, None);
+        assert_eq!(definition.release_notes_path, None);
+    }
+
+    #[test_case(TagFilter::All, "1.2.3", true)]
+    #[test_case(TagFilter::WithoutBuildMetadata, "1.2.3", true)]
+    #[test_case(TagFilter::WithoutBuildMetadata, "1.2.3+4", false)]
+    #[test_case(TagFilter::Release, "0.52.3", true)]
+    #[test_case(TagFilter::Release, "0.52.3-beta.1", false)]
+    #[test_case(TagFilter::Release, "0.52.3+1", false)]
+    #[test_case(TagFilter::PrereleaseContains { value: String::from("beta") }, "1.2.3-beta.1", true)]
+    #[test_case(TagFilter::PrereleaseContains { value: String::from("beta") }, "1.2.3", false)]
+    fn tag_filter(filter: TagFilter, version: &str, result: bool) {
+        assert_eq!(filter.matches(&Version::parse(version).unwrap()), result);
+    }
+
+    #[test_case("Android", "Android", "signalapp/Signal-Android"; "uppercase key")]
+    #[test_case("android", "", "signalapp/Signal-Android"; "empty name")]
+    #[test_case("android", "Android", "Signal-Android"; "repository without owner")]
+    fn invalid(key: &str, name: &str, repository: &str) {
+        let definition = PlatformDefinition {
+            key: key.to_string(),
+            name: name.to_string(),
+            repository: repository.to_string(),
+            ..PlatformDefinition::builtin().remove(0)
+        };
+
+        assert!(definition.validate().is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+
+use anyhow::Context;
+use serde_json::Value;
+
+/// Chromium and Node major versions bundled with each Electron major version, from
+/// https://releases.electronjs.org.
+const ELECTRON_BUNDLED_VERSIONS: &[(u64, u64, u64)] = &[
+    (25, 114, 18),
+    (26, 116, 18),
+    (27, 118, 18),
+    (28, 120, 18),
+    (29, 122, 20),
+    (30, 124, 20),
+    (31, 126, 20),
+    (32, 128, 20),
+    (33, 130, 20),
+    (34, 132, 20),
+    (35, 134, 22),
+    (36, 136, 22),
+    (37, 138, 22),
+    (38, 140, 22),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElectronVersion(pub String);
+
+impl ElectronVersion {
+    pub fn from_package_json(text: &str) -> anyhow::Result<Self> {
+        let value: Value = serde_json::from_str(text).context("couldn't parse JSON")?;
+
+        let version = ["devDependencies", "dependencies"]
+            .iter()
+            .find_map(|key| value.get(key)?.get("electron")?.as_str())
+            .context("couldn't find electron dependency")?;
+
+        Ok(Self(version.trim_start_matches(['^', '~']).to_string()))
+    }
+
+    pub fn major(&self) -> Option<u64> {
+        self.0.split('.').next()?.parse().ok()
+    }
+
+    /// Chromium and Node major versions, if known.
+    pub fn bundled_versions(&self) -> Option<(u64, u64)> {
+        let major = self.major()?;
+
+        ELECTRON_BUNDLED_VERSIONS
+            .iter()
+            .find(|(electron, _, _)| *electron == major)
+            .map(|(_, chromium, node)| (*chromium, *node))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElectronUpdate {
+    pub old_version: ElectronVersion,
+    pub new_version: ElectronVersion,
+}
+
+/// Like `28.1.0 → 29.0.0 (Chromium 120 → 122, Node 18 → 20)`, where bundled versions are only
+/// mentioned if they're known and changed.
+impl fmt::Display for ElectronUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} → {}", self.old_version.0, self.new_version.0)?;
+
+        if let (Some((old_chromium, old_node)), Some((new_chromium, new_node))) = (
+            self.old_version.bundled_versions(),
+            self.new_version.bundled_versions(),
+        ) {
+            let mut bundled = Vec::new();
+
+            if old_chromium != new_chromium {
+                bundled.push(format!("Chromium {old_chromium} → {new_chromium}"));
+            }
+
+            if old_node != new_node {
+                bundled.push(format!("Node {old_node} → {new_node}"));
+            }
+
+            if !bundled.is_empty() {
+                write!(f, " ({})", bundled.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test]
+    fn from_package_json() {
+        let package_json = json!({
+            "dependencies": { "uuid": "9.0.0" },
+            "devDependencies": { "electron": "^28.1.0" },
+        })
+        .to_string();
+
+        assert_eq!(
+            ElectronVersion::from_package_json(&package_json).unwrap(),
+            ElectronVersion(String::from("28.1.0"))
+        );
+    }
+
+    #[test_case("28.1.0", "29.0.0", "28.1.0 → 29.0.0 (Chromium 120 → 122, Node 18 → 20)"; "new major")]
+    #[test_case("29.0.0", "29.1.0", "29.0.0 → 29.1.0"; "same major")]
+    #[test_case("38.0.0", "99.0.0", "38.0.0 → 99.0.0"; "unknown major")]
+    fn display(old_version: &str, new_version: &str, result: &str) {
+        let update = ElectronUpdate {
+            old_version: ElectronVersion(old_version.to_string()),
+            new_version: ElectronVersion(new_version.to_string()),
+        };
+
+        assert_eq!(update.to_string(), result);
+    }
+}
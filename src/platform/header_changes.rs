@@ -0,0 +1,284 @@
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::{
+    android,
+    desktop::{ElectronUpdate, ElectronVersion},
+    ios, BuildConfiguration, BuildConfigurationSource, Platform,
+};
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderSourceKind {
+    /// Permissions requested in an Android `AndroidManifest.xml`.
+    AndroidManifest,
+    /// Privacy usage description keys in an iOS `Info.plist`.
+    InfoPlist,
+    /// The Electron version in a `package.json`.
+    PackageJson,
+}
+
+/// A file in a platform's repository that changes called out in the post header are read from (see
+/// [`crate::platform::PlatformDefinition::header_sources`]).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HeaderSource {
+    pub path: String,
+    pub kind: HeaderSourceKind,
+}
+
+impl HeaderSource {
+    pub fn new(path: &str, kind: HeaderSourceKind) -> Self {
+        Self {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    pub fn for_platform(platform: Platform) -> &'static [Self] {
+        &platform.definition().header_sources
+    }
+
+    async fn get_text(
+        &self,
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+    ) -> anyhow::Result<String> {
+        github::get_file_content(transport, platform, &tag.name, &self.path)
+            .await
+            .with_context(|| format!("couldn't get {} at {}", self.path, tag.name))
+    }
+}
+
+/// Changes between two builds that are called out in the post header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderChanges {
+    /// Like `minSdk 21 → 24`.
+    pub build_configuration: Vec<String>,
+    /// Android permissions or iOS privacy usage description keys.
+    pub added_permissions: Vec<String>,
+    pub removed_permissions: Vec<String>,
+    pub electron_update: Option<ElectronUpdate>,
+}
+
+impl HeaderChanges {
+    /// Upper bound of requests [`Self::get`] makes.
+    pub fn estimated_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        let source_requests = HeaderSource::for_platform(platform)
+            .iter()
+            .filter(|source| comparison.might_have_changed(&[&source.path]))
+            .count() as u64
+            * 2;
+
+        source_requests + BuildConfiguration::estimated_changes_requests(platform, comparison)
+    }
+
+    /// Compares the build configuration, permissions and Electron version with the ones at
+    /// `old_tag`, only fetching files that `comparison` says might have changed.
+    pub async fn get(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        comparison: &Comparison,
+        new_build_configuration: Option<&BuildConfiguration>,
+    ) -> anyhow::Result<Self> {
+        let mut changes = Self::default();
+
+        if let (Some(source), Some(new_build_configuration)) = (
+            BuildConfigurationSource::for_platform(platform),
+            new_build_configuration,
+        ) {
+            if source.has_header_changes() && comparison.might_have_changed(&[&source.path]) {
+                let old_build_configuration = BuildConfiguration::get(transport, platform, old_tag)
+                    .await
+                    .context("couldn't get old build configuration")?
+                    .context("no old build configuration")?;
+
+                changes.build_configuration =
+                    new_build_configuration.changes_since(&old_build_configuration);
+            }
+        }
+
+        let mut old_permissions = BTreeSet::new();
+        let mut new_permissions = BTreeSet::new();
+
+        for source in HeaderSource::for_platform(platform) {
+            if !comparison.might_have_changed(&[&source.path]) {
+                tracing::trace!(source.path, "header source didn't change, skipping");
+                continue;
+            }
+
+            let old_text = source.get_text(transport, platform, old_tag).await?;
+            let new_text = source.get_text(transport, platform, new_tag).await?;
+
+            match source.kind {
+                HeaderSourceKind::AndroidManifest => {
+                    old_permissions
+                        .append(&mut android::permissions_from_android_manifest(&old_text));
+                    new_permissions
+                        .append(&mut android::permissions_from_android_manifest(&new_text));
+                }
+                HeaderSourceKind::InfoPlist => {
+                    old_permissions
+                        .append(&mut ios::usage_description_keys_from_info_plist(&old_text));
+                    new_permissions
+                        .append(&mut ios::usage_description_keys_from_info_plist(&new_text));
+                }
+                HeaderSourceKind::PackageJson => {
+                    let old_version = ElectronVersion::from_package_json(&old_text)
+                        .context("couldn't parse old Electron version")?;
+                    let new_version = ElectronVersion::from_package_json(&new_text)
+                        .context("couldn't parse new Electron version")?;
+
+                    if old_version != new_version {
+                        changes.electron_update = Some(ElectronUpdate {
+                            old_version,
+                            new_version,
+                        });
+                    }
+                }
+            }
+        }
+
+        changes.set_permissions(&old_permissions, &new_permissions);
+
+        tracing::debug!(?changes);
+        Ok(changes)
+    }
+
+    pub fn set_permissions(&mut self, old: &BTreeSet<String>, new: &BTreeSet<String>) {
+        self.added_permissions = new.difference(old).cloned().collect();
+        self.removed_permissions = old.difference(new).cloned().collect();
+    }
+
+    /// Lines to add to the post header, each starting with a newline.
+    pub fn markdown_text(&self) -> String {
+        let permissions = |permissions: &[String]| {
+            permissions
+                .iter()
+                .map(|permission| format!("`{permission}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut text = String::new();
+
+        if !self.build_configuration.is_empty() {
+            text += &format!("\n{}", self.build_configuration.join(", "));
+        }
+
+        if let Some(update) = &self.electron_update {
+            text += &format!("\nElectron {update}");
+        }
+
+        if !self.added_permissions.is_empty() {
+            text += &format!(
+                "\nPermissions added: {}",
+                permissions(&self.added_permissions)
+            );
+        }
+
+        if !self.removed_permissions.is_empty() {
+            text += &format!(
+                "\nPermissions removed: {}",
+                permissions(&self.removed_permissions)
+            );
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+        utils,
+    };
+
+    fn comparison(files: &[&str]) -> Comparison {
+        serde_json::from_value(json!({
+            "total_commits": 0,
+            "commits": [],
+            "files": files.iter().map(|file| json!({ "filename": file })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn get() {
+        let url = |tag: &str| {
+            format!("https://raw.githubusercontent.com/signalapp/Signal-Desktop/{tag}/package.json")
+        };
+        let package_json =
+            |version: &str| json!({ "devDependencies": { "electron": version } }).to_string();
+
+        let transport = FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &url("v7.0.0"),
+                Response::new(200, package_json("31.1.0")),
+            )
+            .with_response(
+                Method::Get,
+                &url("v7.1.0"),
+                Response::new(200, package_json("32.0.1")),
+            );
+
+        let comparison = comparison(&["package.json"]);
+        assert_eq!(HeaderChanges::estimated_requests(Desktop, &comparison), 2);
+
+        let changes = utils::block_on(HeaderChanges::get(
+            &transport,
+            Desktop,
+            &Tag::new("v7.0.0"),
+            &Tag::new("v7.1.0"),
+            &comparison,
+            None,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            changes.electron_update,
+            Some(ElectronUpdate {
+                old_version: ElectronVersion(String::from("31.1.0")),
+                new_version: ElectronVersion(String::from("32.0.1")),
+            })
+        );
+        assert!(changes.added_permissions.is_empty());
+    }
+
+    #[test]
+    fn get_skips_unchanged_sources() {
+        let transport = FakeTransport::new();
+        let comparison = comparison(&["ts/main.ts"]);
+
+        assert_eq!(HeaderChanges::estimated_requests(Android, &comparison), 0);
+
+        let changes = utils::block_on(HeaderChanges::get(
+            &transport,
+            Android,
+            &Tag::new("v7.0.0"),
+            &Tag::new("v7.0.1"),
+            &comparison,
+            None,
+        ))
+        .unwrap();
+
+        assert_eq!(changes, HeaderChanges::default());
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Context};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref USAGE_DESCRIPTION_KEY_REGEX: Regex =
+        Regex::new(r"<key>(NS\w+UsageDescription)</key>").unwrap();
+    static ref BUILD_SETTING_REFERENCE_REGEX: Regex = Regex::new(r"^\$\((\w+)\)$").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildConfiguration {
+    /// `CFBundleVersion`.
+    pub build_number: String,
+    /// `CFBundleShortVersionString`.
+    pub marketing_version: String,
+}
+
+impl BuildConfiguration {
+    /// Values that refer to build settings, like `$(CURRENT_PROJECT_VERSION)`, are resolved using
+    /// `project_pbxproj` (see [`Self::refers_to_build_settings`]).
+    pub fn from_info_plist(
+        info_plist: &str,
+        project_pbxproj: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let resolve = |key: &str| -> anyhow::Result<String> {
+            let value = plist_string(info_plist, key)?;
+
+            match BUILD_SETTING_REFERENCE_REGEX.captures(&value) {
+                Some(caps) => build_setting(
+                    project_pbxproj.context("project.pbxproj is needed but missing")?,
+                    &caps[1],
+                ),
+                None => Ok(value),
+            }
+        };
+
+        Ok(Self {
+            build_number: resolve("CFBundleVersion").context("couldn't get build_number")?,
+            marketing_version: resolve("CFBundleShortVersionString")
+                .context("couldn't get marketing_version")?,
+        })
+    }
+
+    pub fn refers_to_build_settings(info_plist: &str) -> bool {
+        ["CFBundleVersion", "CFBundleShortVersionString"]
+            .iter()
+            .filter_map(|key| plist_string(info_plist, key).ok())
+            .any(|value| BUILD_SETTING_REFERENCE_REGEX.is_match(&value))
+    }
+}
+
+/// Privacy usage description keys declared in `Info.plist`, like `NSCameraUsageDescription`.
+pub fn usage_description_keys_from_info_plist(text: &str) -> BTreeSet<String> {
+    USAGE_DESCRIPTION_KEY_REGEX
+        .captures_iter(text)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+fn plist_string(text: &str, key: &str) -> anyhow::Result<String> {
+    let re = Regex::new(&format!(
+        r"<key>{}</key>\s*<string>([^<]*)</string>",
+        regex::escape(key)
+    ))
+    .context("couldn't compile regex")?;
+
+    let caps = re.captures(text).context("couldn't find key")?;
+    Ok(caps[1].trim().to_string())
+}
+
+/// Uses the first value of `name` in `project.pbxproj`, since it's the same for all targets that
+/// matter.
+fn build_setting(text: &str, name: &str) -> anyhow::Result<String> {
+    let re = Regex::new(&format!(r#"\b{}\s*=\s*"?([^";]+)"?;"#, regex::escape(name)))
+        .context("couldn't compile regex")?;
+
+    match re.captures(text) {
+        Some(caps) => Ok(caps[1].trim().to_string()),
+        None => bail!("couldn't find build setting {name}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INFO_PLIST: &str = r#"
+<dict>
+	<key>CFBundleShortVersionString</key>
+	<string>$(MARKETING_VERSION)</string>
+	<key>CFBundleVersion</key>
+	<string>$(CURRENT_PROJECT_VERSION)</string>
+	<key>NSCameraUsageDescription</key>
+	<string>Signal uses your camera to take photos and for video calls.</string>
+	<key>NSContactsUsageDescription</key>
+	<string>Signal uses your contacts to find users you know.</string>
+</dict>"#;
+
+    #[test]
+    fn build_configuration() {
+        assert!(BuildConfiguration::refers_to_build_settings(INFO_PLIST));
+
+        assert_eq!(
+            BuildConfiguration::from_info_plist(
+                INFO_PLIST,
+                Some(
+                    r#"
+				CURRENT_PROJECT_VERSION = 7.0.0.2;
+				MARKETING_VERSION = "7.0.0";
+				CURRENT_PROJECT_VERSION = 1;"#
+                )
+            )
+            .unwrap(),
+            BuildConfiguration {
+                build_number: String::from("7.0.0.2"),
+                marketing_version: String::from("7.0.0"),
+            }
+        );
+    }
+
+    #[test]
+    fn build_configuration_without_build_settings() {
+        let info_plist = r#"
+	<key>CFBundleShortVersionString</key>
+	<string>6.1.0</string>
+	<key>CFBundleVersion</key>
+	<string>6.1.0.5</string>"#;
+
+        assert!(!BuildConfiguration::refers_to_build_settings(info_plist));
+
+        assert_eq!(
+            BuildConfiguration::from_info_plist(info_plist, None).unwrap(),
+            BuildConfiguration {
+                build_number: String::from("6.1.0.5"),
+                marketing_version: String::from("6.1.0"),
+            }
+        );
+    }
+
+    #[test]
+    fn usage_description_keys() {
+        assert_eq!(
+            usage_description_keys_from_info_plist(INFO_PLIST),
+            BTreeSet::from([
+                String::from("NSCameraUsageDescription"),
+                String::from("NSContactsUsageDescription")
+            ])
+        );
+    }
+}
@@ -1,55 +1,125 @@
-use std::fmt;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
 
 use semver::Version;
-use strum_macros::EnumIter;
 
-use crate::utils;
+use crate::{config::Config, localization::LocalizationLayout, utils};
 
 pub mod android;
+mod build_configuration;
+mod definition;
+pub mod desktop;
+mod header_changes;
+pub mod ios;
+
+pub use build_configuration::*;
+use builtin::*;
+pub use definition::*;
+pub use header_changes::*;
 
 pub const ANDROID_DEFAULT_STRINGS_FILENAME: &str = "app/src/main/res/values/strings.xml";
 pub const SERVER_STRINGS_FILENAME: &str =
     "service/src/main/resources/org/signal/badges/Badges.properties";
 
-#[derive(Debug, Clone, Copy, EnumIter, PartialEq, Eq, Hash)]
-pub enum Platform {
-    Android,
-    Ios,
-    Desktop,
-    Server,
+thread_local! {
+    /// The configuration of [`builtin`] platforms that weren't given another one.
+    static DEFAULT_CONFIG: &'static Config = Config::default().leak();
+}
+
+/// A platform the bot posts about, referring to a [`PlatformDefinition`] of a [`Config`].
+///
+/// Platforms are equal if they're at the same position in their configurations.
+#[derive(Clone, Copy)]
+pub struct Platform {
+    index: usize,
+    /// `None` for [`builtin`] platforms, which use the default configuration until they're given
+    /// another one with [`Platform::with_config`].
+    config: Option<&'static Config>,
 }
 
-use Platform::*;
+/// Platforms with built-in definitions, which always come first in the configuration.
+///
+/// Some features, like Android's Firebase availability and build configuration, are only supported
+/// for these.
+#[allow(non_upper_case_globals, dead_code)]
+pub mod builtin {
+    use super::Platform;
+
+    pub const Android: Platform = Platform::builtin(0);
+    pub const Ios: Platform = Platform::builtin(1);
+    pub const Desktop: Platform = Platform::builtin(2);
+    pub const Server: Platform = Platform::builtin(3);
+    pub const Libsignal: Platform = Platform::builtin(4);
+}
 
 impl Platform {
+    const fn builtin(index: usize) -> Self {
+        Self {
+            index,
+            config: None,
+        }
+    }
+
+    /// All platforms of `config`.
+    pub fn iter(config: &'static Config) -> impl Iterator<Item = Self> {
+        (0..config.platforms.len()).map(|index| Self {
+            index,
+            config: Some(config),
+        })
+    }
+
+    pub fn from_state_key(config: &'static Config, key: &str) -> Option<Self> {
+        Self::iter(config).find(|platform| platform.state_key() == key)
+    }
+
+    /// The same platform, referring to its definition in `config`.
+    pub fn with_config(&self, config: &'static Config) -> Self {
+        Self {
+            index: self.index,
+            config: Some(config),
+        }
+    }
+
+    pub fn config(&self) -> &'static Config {
+        self.config
+            .unwrap_or_else(|| DEFAULT_CONFIG.with(|config| *config))
+    }
+
+    pub fn definition(&self) -> &'static PlatformDefinition {
+        self.config()
+            .platforms
+            .get(self.index)
+            .expect("platform should be defined in its configuration")
+    }
+
     pub fn github_api_tags_url(&self) -> String {
-        format!("https://api.github.com/repos/signalapp/Signal-{self}/tags")
+        self.github_api_repo_url("tags")
     }
 
     pub fn should_post_version(&self, version: &Version) -> bool {
-        match self {
-            Android => version.build.is_empty(), // versions like 1.2.3.4 are filtered out (the "4" is parsed into `build` by lenient_semver)
-            Ios | Desktop => version.pre.contains("beta"),
-            Server => true,
-        }
+        self.definition().tag_filter.matches(version)
     }
 
     pub fn should_show_commit(&self, full_message: &str) -> bool {
-        match self {
-            Android | Desktop | Server => true,
-            Ios => {
-                !full_message.contains("Bump build to")
-                    && !full_message.contains("Feature flags for")
-            }
-        }
+        !self
+            .definition()
+            .hidden_commit_messages
+            .iter()
+            .any(|hidden| full_message.contains(hidden.as_str()))
     }
 
     pub fn should_show_commit_details(&self) -> bool {
-        matches!(self, Android | Desktop | Server)
+        self.definition().show_commit_details
+    }
+
+    pub fn should_show_translation_coverage(&self) -> bool {
+        self.definition().show_translation_coverage
     }
 
     pub fn github_api_comparison_url(&self, old: &str, new: &str) -> String {
-        format!("https://api.github.com/repos/signalapp/Signal-{self}/compare/{old}...{new}")
+        self.github_api_repo_url(&format!("compare/{old}...{new}"))
     }
 
     pub fn github_comparison_url(
@@ -59,90 +129,126 @@ impl Platform {
         file_path: Option<&str>,
         specify_protocol: bool,
     ) -> String {
-        let base = match file_path {
+        let path = match file_path {
             Some(file_path) => format!(
-                "//github.com/signalapp/Signal-{self}/compare/{old}..{new}#diff-{}", // note: using `..` instead of `...`
+                "{}/compare/{old}..{new}#diff-{}", // note: using `..` instead of `...`
+                self.repo_slug(),
                 utils::sha256_string(file_path)
             ),
-            None => format!("//github.com/signalapp/Signal-{self}/compare/{old}...{new}"),
+            None => format!("{}/compare/{old}...{new}", self.repo_slug()),
         };
 
-        format!("{}{base}", if specify_protocol { "https:" } else { "" })
+        self.config().github_url(&path, specify_protocol)
+    }
+
+    pub fn github_api_contents_url(&self, path: &str, revision: &str) -> String {
+        self.github_api_repo_url(&format!("contents/{path}?ref={revision}"))
     }
 
     pub fn github_api_commit_url(&self, sha: &str) -> String {
-        format!("https://api.github.com/repos/signalapp/Signal-{self}/commits/{sha}")
+        self.github_api_repo_url(&format!("commits/{sha}"))
     }
 
     pub fn github_commit_url(&self, sha: &str) -> String {
-        format!("//github.com/signalapp/Signal-{self}/commit/{sha}")
+        self.config()
+            .github_url(&format!("{}/commit/{sha}", self.repo_slug()), false)
     }
 
     pub fn github_raw_url(&self, revision: &str) -> String {
-        format!("https://raw.githubusercontent.com/signalapp/Signal-{self}/{revision}")
+        format!(
+            "{}/{}/{revision}",
+            self.config().github_raw_base_url,
+            self.repo_slug()
+        )
     }
 
-    pub const fn availability_notice(&self, available: bool) -> &'static str {
-        match self {
-            Android => {
-                if available {
-                    "\nSomehow available via [Firebase App Distribution](/t/17538) despite [this](/t/17538/114)? :eyes:"
-                } else {
-                    "\nAvailable soon via [Obtainium](/t/65274) or the Google Play Store"
-                }
-            }
-            Ios | Desktop | Server => "",
+    fn github_api_repo_url(&self, path: &str) -> String {
+        format!(
+            "{}/repos/{}/{path}",
+            self.config().github_api_base_url,
+            self.repo_slug()
+        )
+    }
+
+    pub fn availability_notice(&self, available: bool) -> &'static str {
+        if *self != Android {
+            ""
+        } else if available {
+            "\nSomehow available via [Firebase App Distribution](/t/17538) despite [this](/t/17538/114)? :eyes:"
+        } else {
+            "\nAvailable soon via [Obtainium](/t/65274) or the Google Play Store"
         }
     }
 
+    pub fn topic_strategy(&self) -> TopicStrategy {
+        self.definition().topic.clone()
+    }
+
+    /// `fixed_topic_id` is required for platforms with [`TopicStrategy::FixedTopic`].
     pub fn discourse_topic_slug_url(
         &self,
         version: &Version,
-        topic_id_for_server_updates: u64,
-    ) -> String {
-        match self {
-            Android | Ios | Desktop => format!(
-                "https://community.signalusers.org/t/beta-feedback-for-the-upcoming-{}-{}-{}-release.json",
-                self.to_string().to_ascii_lowercase(), version.major, version.minor
+        fixed_topic_id: Option<u64>,
+    ) -> anyhow::Result<String> {
+        let path = match (self.topic_strategy(), fixed_topic_id) {
+            (TopicStrategy::BetaFeedbackTopics, _) => format!(
+                "t/beta-feedback-for-the-upcoming-{}-{}-{}-release.json",
+                self.to_string().to_ascii_lowercase(),
+                version.major,
+                version.minor
             ),
-            Server => format!(
-                "https://community.signalusers.org/t/{}.json", topic_id_for_server_updates)
-        }
+            (TopicStrategy::FixedTopic { .. }, Some(topic_id)) => format!("t/{topic_id}.json"),
+            (TopicStrategy::FixedTopic { .. }, None) => {
+                anyhow::bail!("no topic ID for {self}")
+            }
+        };
+
+        Ok(self.config().discourse_url(&path))
     }
 
     pub fn state_key(&self) -> String {
-        self.to_string().to_lowercase()
+        self.definition().key.clone()
     }
 
-    pub const fn color(&self) -> u64 {
-        match self {
-            Android => 0x1d8663,
-            Ios => 0x336ba3,
-            Desktop => 0xaa377a,
-            Server => 0x6058ca,
-        }
+    pub fn color(&self) -> u64 {
+        self.definition().color
     }
 
     pub fn archiving_message_necessary(&self) -> bool {
-        !matches!(self, Server)
+        matches!(self.topic_strategy(), TopicStrategy::BetaFeedbackTopics)
     }
 
     pub fn repo_slug(&self) -> String {
-        format!("signalapp/Signal-{self}")
+        self.definition().repository.clone()
+    }
+
+    pub fn localization_layout(&self) -> LocalizationLayout {
+        self.definition().localization
+    }
+}
+
+impl PartialEq for Platform {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl Eq for Platform {}
+
+impl Hash for Platform {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
     }
 }
 
 impl fmt::Display for Platform {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Android => "Android",
-                Ios => "iOS",
-                Desktop => "Desktop",
-                Server => "Server",
-            }
-        )
+        write!(f, "{}", self.definition().name)
+    }
+}
+
+impl fmt::Debug for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.definition().name)
     }
 }
@@ -0,0 +1,175 @@
+use anyhow::Context;
+
+use crate::{
+    github::{self, Comparison, Tag},
+    network::Transport,
+    platform::Platform,
+    utils,
+};
+
+/// English release notes of the store listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseNotes {
+    pub text: String,
+}
+
+impl ReleaseNotes {
+    pub fn path(platform: Platform) -> Option<&'static str> {
+        platform.definition().release_notes_path.as_deref()
+    }
+
+    /// Upper bound of requests [`Self::get_if_changed`] makes.
+    pub fn estimated_requests(platform: Platform, comparison: &Comparison) -> u64 {
+        match Self::path(platform) {
+            Some(path) if comparison.might_have_changed(&[path]) => 2,
+            _ => 0,
+        }
+    }
+
+    /// Returns `None` unless the release notes exist at `new_tag` and changed since `old_tag`.
+    pub async fn get_if_changed(
+        transport: &dyn Transport,
+        platform: Platform,
+        old_tag: &Tag,
+        new_tag: &Tag,
+        comparison: &Comparison,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(path) = Self::path(platform) else {
+            return Ok(None);
+        };
+
+        if !comparison.might_have_changed(&[path]) {
+            tracing::trace!("release notes didn't change, skipping");
+            return Ok(None);
+        }
+
+        let Some(new_text) = Self::get_text(transport, platform, new_tag, path).await? else {
+            return Ok(None);
+        };
+
+        let old_text = Self::get_text(transport, platform, old_tag, path).await?;
+
+        if new_text.is_empty() || old_text.as_ref() == Some(&new_text) {
+            return Ok(None);
+        }
+
+        Ok(Some(Self { text: new_text }))
+    }
+
+    async fn get_text(
+        transport: &dyn Transport,
+        platform: Platform,
+        tag: &Tag,
+        path: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let text = github::get_file_content_if_exists(transport, platform, &tag.name, path)
+            .await
+            .with_context(|| format!("couldn't get {path} at {}", tag.name))?;
+
+        Ok(text.map(|text| text.trim().to_string()))
+    }
+
+    /// A quoted section, followed by an empty line.
+    pub fn markdown_text(&self) -> String {
+        format!(
+            "#### What's new\n[quote]\n{}\n[/quote]\n\n",
+            utils::escape_html(&self.text)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use test_case::test_case;
+    use worker::Method;
+
+    use super::*;
+    use crate::{
+        network::{FakeTransport, Response},
+        platform::builtin::*,
+    };
+
+    fn comparison(files: &[&str]) -> Comparison {
+        serde_json::from_value(json!({
+            "total_commits": 0,
+            "commits": [],
+            "files": files.iter().map(|file| json!({ "filename": file })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    fn transport(old_text: Option<&str>, new_text: &str) -> FakeTransport {
+        let url = |tag: &str| {
+            format!(
+                "https://raw.githubusercontent.com/signalapp/Signal-iOS/{tag}/fastlane/metadata/en-US/release_notes.txt"
+            )
+        };
+
+        FakeTransport::new()
+            .with_response(
+                Method::Get,
+                &url("7.0.0.1"),
+                match old_text {
+                    Some(text) => Response::new(200, text.to_string()),
+                    None => Response::new(404, String::from("404: Not Found")),
+                },
+            )
+            .with_response(
+                Method::Get,
+                &url("7.0.0.2"),
+                Response::new(200, new_text.to_string()),
+            )
+    }
+
+    #[test_case(Some("Bug fixes."), "Stickers!\n", Some("Stickers!"); "changed")]
+    #[test_case(None, "Stickers!", Some("Stickers!"); "added")]
+    #[test_case(Some("Stickers!"), "Stickers!\n", None; "unchanged")]
+    fn get_if_changed(old_text: Option<&str>, new_text: &str, result: Option<&str>) {
+        let release_notes = utils::block_on(ReleaseNotes::get_if_changed(
+            &transport(old_text, new_text),
+            Ios,
+            &Tag::new("7.0.0.1"),
+            &Tag::new("7.0.0.2"),
+            &comparison(&["fastlane/metadata/en-US/release_notes.txt"]),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            release_notes,
+            result.map(|text| ReleaseNotes {
+                text: text.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn markdown_text() {
+        let release_notes = ReleaseNotes {
+            text: String::from("Bold <b>claims</b> & more."),
+        };
+
+        assert_eq!(
+            release_notes.markdown_text(),
+            "#### What's new\n[quote]\nBold &lt;b&gt;claims&lt;/b&gt; &amp; more.\n[/quote]\n\n"
+        );
+    }
+
+    #[test]
+    fn get_if_changed_skips_unchanged_file() {
+        let transport = FakeTransport::new();
+
+        let release_notes = utils::block_on(ReleaseNotes::get_if_changed(
+            &transport,
+            Android,
+            &Tag::new("v7.0.0"),
+            &Tag::new("v7.0.1"),
+            &comparison(&["app/build.gradle.kts"]),
+        ))
+        .unwrap();
+
+        assert_eq!(release_notes, None);
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use std::{fs, io, path::PathBuf};
+
+use anyhow::Context;
+
+use super::Storage;
+use crate::utils::BoxFuture;
+
+/// Stores all keys in a single JSON file, as an object mapping each key to its value.
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn read(&self) -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+        match fs::read_to_string(&self.path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("could not parse {}", self.path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Default::default()),
+            Err(e) => Err(e).with_context(|| format!("could not read {}", self.path.display())),
+        }
+    }
+}
+
+impl Storage for FileStorage {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        Box::pin(async move { Ok(self.read()?.get(key).map(|value| value.to_string())) })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: String) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut map = self.read()?;
+            map.insert(
+                key.to_string(),
+                serde_json::from_str(&value).context("value is not valid JSON")?,
+            );
+
+            let text = serde_json::to_string_pretty(&map).context("could not serialize")?;
+            fs::write(&self.path, text)
+                .with_context(|| format!("could not write {}", self.path.display()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::utils::block_on;
+
+    fn temporary_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "signalupdates-bot-{}-{name}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let storage = FileStorage::new(temporary_path("missing"));
+        assert_eq!(block_on(storage.get("state")).unwrap(), None);
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let path = temporary_path("round-trip");
+        let storage = FileStorage::new(&path);
+
+        block_on(storage.put("state", String::from(r#"{"a":1}"#))).unwrap();
+        block_on(storage.put(
+            "mostRecentAndroidFirebaseVersion",
+            String::from(r#""1.2.3""#),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            block_on(storage.get("state")).unwrap().as_deref(),
+            Some(r#"{"a":1}"#)
+        );
+        assert_eq!(
+            block_on(storage.get("mostRecentAndroidFirebaseVersion"))
+                .unwrap()
+                .as_deref(),
+            Some(r#""1.2.3""#)
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_value_is_rejected() {
+        let path = temporary_path("invalid");
+        let storage = FileStorage::new(&path);
+
+        assert!(block_on(storage.put("state", String::from("not json"))).is_err());
+        assert!(!path.exists());
+    }
+}
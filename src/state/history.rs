@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use super::PlatformState;
+use crate::github::Tag;
+
+/// How many of the most recent changes are kept in the state document.
+pub const MAX_HISTORY_ENTRIES: usize = 50;
+
+/// A committed change of a platform's state.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub id: u64,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// See [`crate::platform::Platform::state_key`].
+    pub platform: String,
+    pub old_tag: Tag,
+    pub new_tag: Tag,
+    pub reason: ChangeReason,
+    /// State of the platform before this change, to be able to roll it back.
+    pub previous_state: PlatformState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ChangeReason {
+    Posted,
+    ApprovalConfirmed,
+    ArchivingPosted,
+    RolledBack { entry_id: u64 },
+    Edited,
+    PendingStateCleared,
+    MarkedAsPosted,
+    ArchivingMessageReset,
+}
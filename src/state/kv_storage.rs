@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Context};
+use worker_kv::KvStore;
+
+use super::Storage;
+use crate::utils::BoxFuture;
+
+/// Stores values in a Workers KV namespace.
+pub struct KvStorage {
+    kv_store: KvStore,
+}
+
+impl KvStorage {
+    pub fn new(kv_store: KvStore) -> Self {
+        Self { kv_store }
+    }
+}
+
+impl Storage for KvStorage {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        Box::pin(async move {
+            self.kv_store
+                .get(key)
+                .text()
+                .await
+                .map_err(|e| anyhow!(e.to_string()))
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: String) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.kv_store
+                .put(key, value.as_str())
+                .map_err(|e| anyhow!(e.to_string()))
+                .context("could not create request to put to KV")?
+                .execute()
+                .await
+                .map_err(|e| anyhow!(e.to_string()))
+        })
+    }
+}
@@ -0,0 +1,41 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::Storage;
+use crate::utils::BoxFuture;
+
+/// Keeps values in memory.
+///
+/// Clones share the same values, so a clone can be kept around to inspect what was stored.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    values: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_value(self, key: &str, value: &str) -> Self {
+        self.values
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn value(&self, key: &str) -> Option<String> {
+        self.values.borrow().get(key).cloned()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, anyhow::Result<Option<String>>> {
+        let value = self.value(key);
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, value: String) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.values.borrow_mut().insert(key.to_string(), value);
+        Box::pin(async move { Ok(()) })
+    }
+}
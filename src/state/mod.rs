@@ -0,0 +1,899 @@
+use std::{collections::HashMap, time::UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    config::Config,
+    env::EnvExt,
+    github::Tag,
+    localization::{Completeness, UnsortedChanges, UnsortedStringCounts},
+    platform::Platform,
+    utils,
+};
+
+#[cfg(any(test, feature = "cli"))]
+mod file_storage;
+mod history;
+mod kv_storage;
+#[cfg(test)]
+mod memory_storage;
+mod schema;
+mod storage;
+
+#[cfg(feature = "cli")]
+pub use file_storage::*;
+pub use history::*;
+pub use kv_storage::*;
+#[cfg(test)]
+pub use memory_storage::*;
+pub use schema::StateDocument;
+pub use storage::*;
+
+const STATE_KV_KEY: &str = "state";
+const STATE_KV_MOST_RECENT_ANDROID_FIREBASE_VERSION_KEY: &str = "mostRecentAndroidFirebaseVersion";
+
+pub type State = HashMap<String, PlatformState>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlatformState {
+    pub last_posted_tag_previous_release: Tag,
+    pub last_posted_tag: Tag,
+
+    #[serde(default)]
+    pub last_post: Option<PostInformation>,
+
+    #[serde(default)]
+    pub posted_archiving_message: bool,
+
+    #[serde(default)]
+    pub localization_changes_completeness: Completeness,
+    #[serde(default)]
+    pub localization_changes: UnsortedChanges,
+    /// Sums of the string counts of each build of the release.
+    #[serde(default)]
+    pub localization_string_counts: UnsortedStringCounts,
+
+    #[serde(default)]
+    pub pending_state: Option<Box<PlatformState>>,
+}
+
+impl PlatformState {
+    /// A state with nothing posted since `last_posted_tag`.
+    pub fn new(last_posted_tag_previous_release: Tag, last_posted_tag: Tag) -> Self {
+        Self {
+            last_posted_tag_previous_release,
+            last_posted_tag,
+            last_post: None,
+            posted_archiving_message: false,
+            localization_changes_completeness: Completeness::default(),
+            localization_changes: UnsortedChanges::default(),
+            localization_string_counts: UnsortedStringCounts::default(),
+            pending_state: None,
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<(Version, Version)> {
+        let last_posted_version_previous_release: Version = self
+            .last_posted_tag_previous_release
+            .to_version()
+            .context("couldn't convert last_posted_tag_previous_release to a Version")?;
+
+        let last_posted_version: Version = self
+            .last_posted_tag
+            .to_version()
+            .context("couldn't convert last_posted_tag to a Version")?;
+
+        if last_posted_version_previous_release >= last_posted_version {
+            bail!("last_posted_version_previous_release >= last_posted_version");
+        }
+
+        if let Some(platform_state) = self.pending_state.as_deref() {
+            let (pending_last_posted_version_previous_release, pending_last_posted_version) =
+                platform_state.validate().context("invalid pending state")?;
+
+            if platform_state.last_post.is_some() {
+                bail!("pending state unexpectedly has last post");
+            }
+
+            if pending_last_posted_version_previous_release < last_posted_version_previous_release {
+                bail!("pending_last_posted_version_previous_release < last_posted_version_previous_release");
+            }
+
+            if pending_last_posted_version <= last_posted_version {
+                bail!("pending_last_posted_version <= last_posted_version");
+            }
+        }
+
+        Ok((last_posted_version_previous_release, last_posted_version))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PostInformation {
+    pub id: u64,
+    pub number: u64,
+}
+
+pub struct StateController {
+    storage: Box<dyn Storage>,
+    /// Which platforms there should be state for.
+    config: &'static Config,
+    document: StateDocument,
+}
+
+impl StateController {
+    async fn get_json<T>(storage: &dyn Storage, key: &str) -> anyhow::Result<Option<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        storage
+            .get(key)
+            .await
+            .and_then(|text| {
+                text.map(|text| serde_json::from_str(&text))
+                    .transpose()
+                    .map_err(Into::into)
+            })
+            .with_context(|| format!("could not get value for key {key}"))
+    }
+
+    pub async fn from_env(env: &dyn EnvExt, config: &'static Config) -> anyhow::Result<Self> {
+        Self::from_storage(env.state_storage()?, config).await
+    }
+
+    pub async fn from_storage(
+        storage: Box<dyn Storage>,
+        config: &'static Config,
+    ) -> anyhow::Result<Self> {
+        let document: Option<Value> = Self::get_json(&*storage, STATE_KV_KEY).await?;
+
+        match document {
+            Some(document) => {
+                let legacy = if schema::version_of(&document)? < schema::CURRENT_VERSION {
+                    schema::LegacyValues {
+                        most_recent_android_firebase_version: Self::get_json(
+                            &*storage,
+                            STATE_KV_MOST_RECENT_ANDROID_FIREBASE_VERSION_KEY,
+                        )
+                        .await?,
+                    }
+                } else {
+                    schema::LegacyValues::default()
+                };
+
+                let controller = Self {
+                    storage,
+                    config,
+                    document: schema::migrate(document, &legacy)?,
+                };
+
+                controller.validate_state().context("invalid state")?;
+                tracing::trace!("state appears to be valid");
+
+                Ok(controller)
+            }
+            None => bail!("no state in storage"),
+        }
+    }
+
+    /// Platforms without state are skipped, their state is initialized when they're checked.
+    fn validate_state(&self) -> anyhow::Result<()> {
+        for platform in Platform::iter(self.config) {
+            tracing::trace!(%platform, "validating platform state");
+
+            match self.document.platforms.get(platform.state_key().as_str()) {
+                Some(platform_state) => {
+                    platform_state
+                        .validate()
+                        .with_context(|| format!("invalid state for {platform}"))?;
+                }
+                None => tracing::warn!(%platform, "no state for platform"),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn document(&self) -> &StateDocument {
+        &self.document
+    }
+
+    pub fn has_platform_state(&self, platform: Platform) -> bool {
+        self.document
+            .platforms
+            .contains_key(platform.state_key().as_str())
+    }
+
+    pub fn platform_state(&self, platform: Platform) -> anyhow::Result<&PlatformState> {
+        self.document
+            .platforms
+            .get(platform.state_key().as_str())
+            .with_context(|| format!("no state for {platform}"))
+    }
+
+    pub fn most_recent_android_firebase_version_tag(&self) -> &Tag {
+        &self.document.most_recent_android_firebase_version
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.document.history
+    }
+
+    pub async fn set_platform_state(
+        &mut self,
+        platform: Platform,
+        state: PlatformState,
+        reason: ChangeReason,
+    ) -> anyhow::Result<()> {
+        if self.change_platform_state(platform, state, reason)? {
+            match self.commit_changes().await {
+                Ok(_) => tracing::debug!("saved state to KV"),
+                Err(e) => return Err(e.context("could not save state to KV")),
+            }
+        } else {
+            tracing::warn!(%platform, "platform state did not change");
+        }
+
+        Ok(())
+    }
+
+    /// Sets the first state of a platform that has none yet, without recording it in the history
+    /// as there's nothing to roll back to.
+    pub async fn initialize_platform_state(
+        &mut self,
+        platform: Platform,
+        state: PlatformState,
+    ) -> anyhow::Result<()> {
+        if self.has_platform_state(platform) {
+            bail!("state for {platform} is already initialized");
+        }
+
+        state.validate().context("tried to set invalid state")?;
+
+        tracing::debug!(%platform, ?state, "initialized platform state");
+        self.document.platforms.insert(platform.state_key(), state);
+
+        match self.commit_changes().await {
+            Ok(_) => tracing::debug!("saved state to KV"),
+            Err(e) => return Err(e.context("could not save state to KV")),
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the states of all platforms at once, saving them only if all are valid.
+    pub async fn set_platform_states(
+        &mut self,
+        mut states: State,
+        reason: ChangeReason,
+    ) -> anyhow::Result<()> {
+        if let Some(key) = states
+            .keys()
+            .find(|key| Platform::from_state_key(self.config, key).is_none())
+        {
+            bail!("unknown platform: {key}");
+        }
+
+        for platform in Platform::iter(self.config) {
+            states
+                .get(platform.state_key().as_str())
+                .with_context(|| format!("no state for {platform}"))?
+                .validate()
+                .with_context(|| format!("invalid state for {platform}"))?;
+        }
+
+        let mut changed = false;
+        for platform in Platform::iter(self.config) {
+            let state = states.remove(platform.state_key().as_str()).unwrap();
+
+            if self.has_platform_state(platform) {
+                changed |= self.change_platform_state(platform, state, reason)?;
+            } else {
+                self.document.platforms.insert(platform.state_key(), state);
+                changed = true;
+            }
+        }
+
+        if changed {
+            match self.commit_changes().await {
+                Ok(_) => tracing::debug!("saved state to KV"),
+                Err(e) => return Err(e.context("could not save state to KV")),
+            }
+        } else {
+            tracing::warn!("state did not change");
+        }
+
+        Ok(())
+    }
+
+    /// Changes the state of `platform` in memory and records the change in the history, returning
+    /// whether the state was actually different.
+    fn change_platform_state(
+        &mut self,
+        platform: Platform,
+        state: PlatformState,
+        reason: ChangeReason,
+    ) -> anyhow::Result<bool> {
+        let platform_state = self
+            .document
+            .platforms
+            .get_mut(platform.state_key().as_str())
+            .with_context(|| format!("no state for {platform}"))?;
+
+        if *platform_state == state {
+            return Ok(false);
+        }
+
+        state.validate().context("tried to set invalid state")?;
+
+        let new_tag = state.last_posted_tag.clone();
+        let previous_state = std::mem::replace(platform_state, state);
+        tracing::debug!(%platform, ?platform_state, ?reason, "changed platform state");
+
+        let entry = HistoryEntry {
+            id: self.document.history.last().map_or(1, |entry| entry.id + 1),
+            timestamp: utils::now()
+                .duration_since(UNIX_EPOCH)
+                .context("time is before the Unix epoch")?
+                .as_secs(),
+            platform: platform.state_key(),
+            old_tag: previous_state.last_posted_tag.clone(),
+            new_tag,
+            reason,
+            previous_state,
+        };
+
+        let history = &mut self.document.history;
+        history.push(entry);
+        if history.len() > MAX_HISTORY_ENTRIES {
+            history.drain(..history.len() - MAX_HISTORY_ENTRIES);
+        }
+
+        Ok(true)
+    }
+
+    /// Forgets about a post that is waiting for approval, for example if it was rejected.
+    pub async fn clear_pending_state(&mut self, platform: Platform) -> anyhow::Result<()> {
+        let mut state = self.platform_state(platform)?.clone();
+        state.pending_state = None;
+
+        self.set_platform_state(platform, state, ChangeReason::PendingStateCleared)
+            .await
+    }
+
+    /// Makes the bot consider `tag` already posted, the same way as if it had just posted it.
+    pub async fn mark_as_posted(&mut self, platform: Platform, tag: Tag) -> anyhow::Result<()> {
+        let current = self.platform_state(platform)?;
+
+        let current_version = current
+            .last_posted_tag
+            .to_version()
+            .context("couldn't convert last_posted_tag to a Version")?;
+        let version = tag
+            .to_version()
+            .context("couldn't convert tag to a Version")?;
+
+        if version <= current_version {
+            bail!("{} is not newer than the last posted tag", tag.name);
+        }
+
+        let same_release =
+            version.major == current_version.major && version.minor == current_version.minor;
+
+        let state = if same_release {
+            PlatformState {
+                last_posted_tag: tag,
+                pending_state: None,
+                ..current.clone()
+            }
+        } else {
+            PlatformState {
+                last_posted_tag_previous_release: current.last_posted_tag.clone(),
+                last_posted_tag: tag,
+                last_post: current.last_post.clone(),
+                posted_archiving_message: false,
+                localization_changes_completeness: Completeness::Incomplete,
+                localization_changes: UnsortedChanges::default(),
+                localization_string_counts: UnsortedStringCounts::default(),
+                pending_state: None,
+            }
+        };
+
+        self.set_platform_state(platform, state, ChangeReason::MarkedAsPosted)
+            .await
+    }
+
+    /// Makes the bot post the archiving message to the previous release's topic again.
+    pub async fn reset_posted_archiving_message(
+        &mut self,
+        platform: Platform,
+    ) -> anyhow::Result<()> {
+        let mut state = self.platform_state(platform)?.clone();
+        state.posted_archiving_message = false;
+
+        self.set_platform_state(platform, state, ChangeReason::ArchivingMessageReset)
+            .await
+    }
+
+    /// Restores the state `platform` had before the change recorded in the history entry with
+    /// the given ID. The rollback itself is recorded in the history as well.
+    pub async fn roll_back(&mut self, platform: Platform, entry_id: u64) -> anyhow::Result<()> {
+        let previous_state = self
+            .document
+            .history
+            .iter()
+            .find(|entry| entry.id == entry_id && entry.platform == platform.state_key())
+            .map(|entry| entry.previous_state.clone())
+            .with_context(|| format!("no history entry {entry_id} for {platform}"))?;
+
+        self.set_platform_state(
+            platform,
+            previous_state,
+            ChangeReason::RolledBack { entry_id },
+        )
+        .await
+    }
+
+    pub async fn set_firebase(
+        &mut self,
+        most_recent_android_firebase_version: Tag,
+    ) -> anyhow::Result<()> {
+        let existing = &mut self.document.most_recent_android_firebase_version;
+
+        if *existing != most_recent_android_firebase_version {
+            *existing = most_recent_android_firebase_version;
+            tracing::debug!(new=?existing, "changed most recent android firebase version");
+
+            match self.commit_changes().await {
+                Ok(_) => tracing::debug!("saved firebase state to KV"),
+                Err(e) => return Err(e.context("could not save firebase state to KV")),
+            }
+        } else {
+            tracing::warn!(
+                ?existing,
+                "most recent android firebase version did not change"
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn put_json<T>(&self, key: &str, value: &T) -> anyhow::Result<()>
+    where
+        T: Serialize,
+    {
+        let text = serde_json::to_string(value).context("could not serialize value")?;
+
+        self.storage
+            .put(key, text)
+            .await
+            .with_context(|| format!("could not put value for key {key}"))
+    }
+
+    async fn commit_changes(&self) -> anyhow::Result<()> {
+        // Another deployment may have upgraded the state since it was loaded.
+        if let Some(stored) = Self::get_json::<Value>(&*self.storage, STATE_KV_KEY).await? {
+            schema::ensure_supported(schema::version_of(&stored)?)
+                .context("refusing to overwrite state")?;
+        }
+
+        self.put_json(STATE_KV_KEY, &self.document).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    use super::*;
+    use crate::{platform::builtin::*, utils::block_on};
+
+    const VALID_STATE: &str = r#"{
+        "android": {
+            "last_posted_tag_previous_release": { "name": "v1.2.3" },
+            "last_posted_tag": { "name": "v1.3.0" }
+        },
+        "ios": {
+            "last_posted_tag_previous_release": { "name": "1.2.0.4-beta" },
+            "last_posted_tag": { "name": "1.3.0.4-beta" }
+        },
+        "desktop": {
+            "last_posted_tag_previous_release": { "name": "v1.2.0-beta.1" },
+            "last_posted_tag": { "name": "v1.3.0-beta.1" }
+        },
+        "server": {
+            "last_posted_tag_previous_release": { "name": "v11.99.0" },
+            "last_posted_tag": { "name": "v12.6.0" }
+        },
+        "libsignal": {
+            "last_posted_tag_previous_release": { "name": "v0.51.0" },
+            "last_posted_tag": { "name": "v0.52.3" }
+        }
+    }"#;
+
+    fn platform_state(previous_release: &str, last: &str) -> PlatformState {
+        PlatformState::new(Tag::new(previous_release), Tag::new(last))
+    }
+
+    fn controller(storage: &MemoryStorage) -> anyhow::Result<StateController> {
+        block_on(StateController::from_storage(
+            Box::new(storage.clone()),
+            Config::default().leak(),
+        ))
+    }
+
+    #[test]
+    fn no_state() {
+        assert!(controller(&MemoryStorage::new()).is_err());
+    }
+
+    #[test]
+    fn valid_state() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let controller = controller(&storage).unwrap();
+
+        assert_eq!(
+            controller.platform_state(Android).unwrap(),
+            &platform_state("v1.2.3", "v1.3.0")
+        );
+        assert_eq!(
+            controller.most_recent_android_firebase_version_tag(),
+            &Tag::new("v0.0.0")
+        );
+    }
+
+    #[test]
+    fn missing_platform_is_skipped() {
+        let storage = MemoryStorage::new().with_value(
+            STATE_KV_KEY,
+            r#"{ "android": { "last_posted_tag_previous_release": { "name": "v1.2.3" }, "last_posted_tag": { "name": "v1.3.0" } } }"#,
+        );
+        let controller = controller(&storage).unwrap();
+
+        assert!(controller.has_platform_state(Android));
+        assert!(!controller.has_platform_state(Ios));
+        assert!(controller.platform_state(Ios).is_err());
+    }
+
+    #[test]
+    fn initialize_platform_state() {
+        let storage = MemoryStorage::new().with_value(
+            STATE_KV_KEY,
+            r#"{ "android": { "last_posted_tag_previous_release": { "name": "v1.2.3" }, "last_posted_tag": { "name": "v1.3.0" } } }"#,
+        );
+        let mut controller = controller(&storage).unwrap();
+
+        assert!(block_on(
+            controller
+                .initialize_platform_state(Ios, platform_state("1.3.0.4-beta", "1.2.0.4-beta"))
+        )
+        .is_err());
+        assert!(block_on(
+            controller.initialize_platform_state(Android, platform_state("v1.3.0", "v1.4.0"))
+        )
+        .is_err());
+
+        let state = platform_state("1.2.0.4-beta", "1.3.0.4-beta");
+        block_on(controller.initialize_platform_state(Ios, state.clone())).unwrap();
+
+        assert_eq!(controller.platform_state(Ios).unwrap(), &state);
+        assert!(controller.history().is_empty());
+        assert_eq!(
+            self::controller(&storage)
+                .unwrap()
+                .platform_state(Ios)
+                .unwrap(),
+            &state
+        );
+    }
+
+    #[test]
+    fn stored_firebase_version() {
+        let storage = MemoryStorage::new()
+            .with_value(STATE_KV_KEY, VALID_STATE)
+            .with_value(
+                STATE_KV_MOST_RECENT_ANDROID_FIREBASE_VERSION_KEY,
+                r#""1.3.0""#,
+            );
+
+        assert_eq!(
+            controller(&storage)
+                .unwrap()
+                .most_recent_android_firebase_version_tag(),
+            &Tag::new("v1.3.0")
+        );
+    }
+
+    #[test_case(platform_state("v1.2.3", "v1.3.0"), true; "valid")]
+    #[test_case(platform_state("v1.3.0", "v1.3.0"), false; "same versions")]
+    #[test_case(platform_state("v1.3.0", "v1.2.3"), false; "previous release is newer")]
+    #[test_case(platform_state("v1.2.3", "not a version"), false; "not a version")]
+    #[test_case(PlatformState {
+        pending_state: Some(Box::new(platform_state("v1.2.3", "v1.3.1"))),
+        ..platform_state("v1.2.3", "v1.3.0")
+    }, true; "valid pending state")]
+    #[test_case(PlatformState {
+        pending_state: Some(Box::new(platform_state("v1.2.3", "v1.3.0"))),
+        ..platform_state("v1.2.3", "v1.3.0")
+    }, false; "pending state is not newer")]
+    #[test_case(PlatformState {
+        pending_state: Some(Box::new(platform_state("v1.2.0", "v1.3.1"))),
+        ..platform_state("v1.2.3", "v1.3.0")
+    }, false; "pending state has older previous release")]
+    #[test_case(PlatformState {
+        pending_state: Some(Box::new(PlatformState {
+            last_post: Some(PostInformation { id: 1, number: 2 }),
+            ..platform_state("v1.2.3", "v1.3.1")
+        })),
+        ..platform_state("v1.2.3", "v1.3.0")
+    }, false; "pending state has last post")]
+    fn validate(state: PlatformState, is_valid: bool) {
+        assert_eq!(state.validate().is_ok(), is_valid);
+    }
+
+    #[test]
+    fn set_platform_state() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        let new_state = PlatformState {
+            last_post: Some(PostInformation { id: 1, number: 2 }),
+            ..platform_state("v1.3.0", "v1.4.0")
+        };
+
+        block_on(controller.set_platform_state(Android, new_state.clone(), ChangeReason::Posted))
+            .unwrap();
+        assert_eq!(controller.platform_state(Android).unwrap(), &new_state);
+
+        let stored: StateDocument =
+            serde_json::from_str(&storage.value(STATE_KV_KEY).unwrap()).unwrap();
+        assert_eq!(stored, controller.document);
+        assert_eq!(stored.version, schema::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn current_version() {
+        let document = StateDocument {
+            version: schema::CURRENT_VERSION,
+            platforms: serde_json::from_str(VALID_STATE).unwrap(),
+            most_recent_android_firebase_version: Tag::new("v1.3.0"),
+            history: vec![],
+        };
+
+        let storage = MemoryStorage::new()
+            .with_value(STATE_KV_KEY, &serde_json::to_string(&document).unwrap())
+            .with_value(
+                STATE_KV_MOST_RECENT_ANDROID_FIREBASE_VERSION_KEY,
+                r#""1.0.0""#,
+            );
+
+        assert_eq!(controller(&storage).unwrap().document, document);
+    }
+
+    #[test]
+    fn refuse_to_overwrite_newer_version() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        let newer = format!(
+            r#"{{ "version": {}, "platforms": {{}} }}"#,
+            schema::CURRENT_VERSION + 1
+        );
+        let storage = storage.with_value(STATE_KV_KEY, &newer);
+
+        assert!(block_on(controller.set_firebase(Tag::new("v1.3.0"))).is_err());
+        assert_eq!(storage.value(STATE_KV_KEY), Some(newer));
+    }
+
+    #[test]
+    fn set_invalid_platform_state() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        assert!(block_on(controller.set_platform_state(
+            Android,
+            platform_state("v1.4.0", "v1.3.0"),
+            ChangeReason::Posted,
+        ))
+        .is_err());
+        assert_eq!(
+            controller.platform_state(Android).unwrap(),
+            &platform_state("v1.2.3", "v1.3.0")
+        );
+        assert_eq!(storage.value(STATE_KV_KEY).as_deref(), Some(VALID_STATE));
+    }
+
+    #[test]
+    fn set_firebase() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        block_on(controller.set_firebase(Tag::new("v1.3.0"))).unwrap();
+
+        assert_eq!(
+            controller.most_recent_android_firebase_version_tag(),
+            &Tag::new("v1.3.0")
+        );
+        let stored: StateDocument =
+            serde_json::from_str(&storage.value(STATE_KV_KEY).unwrap()).unwrap();
+        assert_eq!(
+            stored.most_recent_android_firebase_version,
+            Tag::new("v1.3.0")
+        );
+    }
+
+    #[test]
+    fn history_and_rollback() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        block_on(controller.set_platform_state(
+            Android,
+            platform_state("v1.3.0", "v1.4.0"),
+            ChangeReason::Posted,
+        ))
+        .unwrap();
+        block_on(controller.set_platform_state(
+            Android,
+            platform_state("v1.3.0", "v1.4.1"),
+            ChangeReason::Posted,
+        ))
+        .unwrap();
+
+        let history = controller.history();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].id, 1);
+        assert_eq!(history[0].old_tag, Tag::new("v1.3.0"));
+        assert_eq!(history[0].new_tag, Tag::new("v1.4.0"));
+        assert_eq!(history[1].id, 2);
+        assert_eq!(
+            history[1].previous_state,
+            platform_state("v1.3.0", "v1.4.0")
+        );
+
+        assert!(block_on(controller.roll_back(Ios, 1)).is_err());
+        block_on(controller.roll_back(Android, 1)).unwrap();
+
+        assert_eq!(
+            controller.platform_state(Android).unwrap(),
+            &platform_state("v1.2.3", "v1.3.0")
+        );
+
+        let last = controller.history().last().unwrap();
+        assert_eq!(last.id, 3);
+        assert_eq!(last.reason, ChangeReason::RolledBack { entry_id: 1 });
+        assert_eq!(last.new_tag, Tag::new("v1.3.0"));
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        for patch in 1..=MAX_HISTORY_ENTRIES + 5 {
+            block_on(controller.set_platform_state(
+                Desktop,
+                platform_state("v1.2.0-beta.1", &format!("v1.3.{patch}-beta.1")),
+                ChangeReason::Posted,
+            ))
+            .unwrap();
+        }
+
+        let history = controller.history();
+        assert_eq!(history.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.first().unwrap().id, 6);
+        assert_eq!(history.last().unwrap().id, (MAX_HISTORY_ENTRIES + 5) as u64);
+    }
+
+    #[test]
+    fn set_platform_states() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        let mut states = controller.document().platforms.clone();
+        states.insert(Android.state_key(), platform_state("v1.3.0", "v1.4.0"));
+
+        block_on(controller.set_platform_states(states.clone(), ChangeReason::Edited)).unwrap();
+        assert_eq!(controller.document().platforms, states);
+        assert_eq!(controller.history().len(), 1);
+
+        let mut invalid = states.clone();
+        invalid.insert(
+            Ios.state_key(),
+            platform_state("1.4.0.1-beta", "1.3.0.4-beta"),
+        );
+        assert!(block_on(controller.set_platform_states(invalid, ChangeReason::Edited)).is_err());
+
+        let mut unknown = states.clone();
+        unknown.insert(String::from("windows"), platform_state("v1.0.0", "v1.1.0"));
+        assert!(block_on(controller.set_platform_states(unknown, ChangeReason::Edited)).is_err());
+
+        let mut missing = states.clone();
+        missing.remove(&Server.state_key());
+        assert!(block_on(controller.set_platform_states(missing, ChangeReason::Edited)).is_err());
+
+        assert_eq!(controller.document().platforms, states);
+    }
+
+    #[test]
+    fn clear_pending_state() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        let with_pending = PlatformState {
+            pending_state: Some(Box::new(platform_state("v1.2.3", "v1.3.1"))),
+            ..platform_state("v1.2.3", "v1.3.0")
+        };
+        block_on(controller.set_platform_state(Android, with_pending, ChangeReason::Posted))
+            .unwrap();
+
+        block_on(controller.clear_pending_state(Android)).unwrap();
+
+        assert_eq!(
+            controller.platform_state(Android).unwrap(),
+            &platform_state("v1.2.3", "v1.3.0")
+        );
+        assert_eq!(
+            controller.history().last().unwrap().reason,
+            ChangeReason::PendingStateCleared
+        );
+    }
+
+    #[test_case("v1.3.1", Ok(PlatformState {
+        last_post: Some(PostInformation { id: 1, number: 2 }),
+        posted_archiving_message: true,
+        ..platform_state("v1.2.3", "v1.3.1")
+    }); "same release")]
+    #[test_case("v1.4.0", Ok(PlatformState {
+        last_post: Some(PostInformation { id: 1, number: 2 }),
+        ..platform_state("v1.3.0", "v1.4.0")
+    }); "new release")]
+    #[test_case("v1.3.0", Err(()); "same tag")]
+    #[test_case("v1.2.9", Err(()); "older tag")]
+    fn mark_as_posted(tag: &str, expected: Result<PlatformState, ()>) {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        let current = PlatformState {
+            last_post: Some(PostInformation { id: 1, number: 2 }),
+            posted_archiving_message: true,
+            ..platform_state("v1.2.3", "v1.3.0")
+        };
+        block_on(controller.set_platform_state(Android, current, ChangeReason::Posted)).unwrap();
+
+        let result = block_on(controller.mark_as_posted(Android, Tag::new(tag)));
+
+        assert_eq!(
+            result
+                .map(|_| controller.platform_state(Android).unwrap().clone())
+                .map_err(|_| ()),
+            expected
+        );
+    }
+
+    #[test]
+    fn reset_posted_archiving_message() {
+        let storage = MemoryStorage::new().with_value(STATE_KV_KEY, VALID_STATE);
+        let mut controller = controller(&storage).unwrap();
+
+        let posted = PlatformState {
+            posted_archiving_message: true,
+            ..platform_state("v1.2.3", "v1.3.0")
+        };
+        block_on(controller.set_platform_state(Android, posted, ChangeReason::ArchivingPosted))
+            .unwrap();
+
+        block_on(controller.reset_posted_archiving_message(Android)).unwrap();
+
+        assert!(
+            !controller
+                .platform_state(Android)
+                .unwrap()
+                .posted_archiving_message
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::{bail, ensure, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{HistoryEntry, State};
+use crate::github::Tag;
+
+/// Version of [`StateDocument`] written by this version of the bot.
+pub const CURRENT_VERSION: u64 = 4;
+
+/// Everything the bot stores under the `state` key.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StateDocument {
+    pub version: u64,
+    pub platforms: State,
+    pub most_recent_android_firebase_version: Tag,
+    pub history: Vec<HistoryEntry>,
+}
+
+/// Values that older versions of the schema kept outside of the state document.
+#[derive(Debug, Default)]
+pub struct LegacyValues {
+    pub most_recent_android_firebase_version: Option<String>,
+}
+
+type Migration = fn(Value, &LegacyValues) -> anyhow::Result<Value>;
+
+/// `MIGRATIONS[i]` upgrades a document of version `i + 1` to version `i + 2`.
+const MIGRATIONS: [Migration; (CURRENT_VERSION - 1) as usize] =
+    [migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// Returns the schema version of a stored document.
+///
+/// Version 1 documents are the bare map of platform states, so they have no `version` field.
+pub fn version_of(document: &Value) -> anyhow::Result<u64> {
+    match document.get("version") {
+        Some(version) => version
+            .as_u64()
+            .filter(|version| *version >= 1)
+            .with_context(|| format!("invalid state schema version: {version}")),
+        None => Ok(1),
+    }
+}
+
+/// Fails if this version of the bot can't safely read or overwrite a document of `version`.
+pub fn ensure_supported(version: u64) -> anyhow::Result<()> {
+    if version > CURRENT_VERSION {
+        bail!("state has schema version {version}, but only versions up to {CURRENT_VERSION} are supported");
+    }
+
+    Ok(())
+}
+
+/// Runs all migrations needed to bring `document` up to [`CURRENT_VERSION`].
+pub fn migrate(mut document: Value, legacy: &LegacyValues) -> anyhow::Result<StateDocument> {
+    let mut version = version_of(&document)?;
+    ensure_supported(version)?;
+
+    while version < CURRENT_VERSION {
+        tracing::info!(from = version, to = version + 1, "migrating state");
+
+        document = MIGRATIONS[(version - 1) as usize](document, legacy)
+            .with_context(|| format!("could not migrate state from version {version}"))?;
+        version += 1;
+
+        ensure!(
+            version_of(&document)? == version,
+            "migration to version {version} produced a document of another version"
+        );
+    }
+
+    serde_json::from_value(document).context("could not parse state")
+}
+
+/// Wraps the map of platform states and moves the most recent Android Firebase version, which
+/// used to be stored under a separate key, into the document.
+fn migrate_v1_to_v2(document: Value, legacy: &LegacyValues) -> anyhow::Result<Value> {
+    ensure!(document.is_object(), "state is not an object");
+
+    let most_recent_android_firebase_version = Tag::from_exact_version_string(
+        legacy
+            .most_recent_android_firebase_version
+            .as_deref()
+            .unwrap_or("0.0.0"),
+    );
+
+    Ok(json!({
+        "version": 2,
+        "platforms": document,
+        "most_recent_android_firebase_version": most_recent_android_firebase_version,
+    }))
+}
+
+/// Adds the (empty) history of state changes.
+fn migrate_v2_to_v3(mut document: Value, _legacy: &LegacyValues) -> anyhow::Result<Value> {
+    let object = document
+        .as_object_mut()
+        .context("state document is not an object")?;
+
+    object.insert(String::from("version"), json!(3));
+    object.insert(String::from("history"), json!([]));
+
+    Ok(document)
+}
+
+/// Marks the state as coming from a version of the bot that posts about libsignal. Its state is
+/// initialized with the latest tag the first time it's checked, like for any platform without
+/// state, so nothing else changes.
+fn migrate_v3_to_v4(mut document: Value, _legacy: &LegacyValues) -> anyhow::Result<Value> {
+    let object = document
+        .as_object_mut()
+        .context("state document is not an object")?;
+
+    object.insert(String::from("version"), json!(4));
+
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(json!({ "android": {} }), Some(1); "bare platforms")]
+    #[test_case(json!({ "version": 2, "platforms": {} }), Some(2); "versioned")]
+    #[test_case(json!({ "version": 0 }), None; "zero")]
+    #[test_case(json!({ "version": "2" }), None; "string")]
+    fn version_of(document: Value, version: Option<u64>) {
+        assert_eq!(super::version_of(&document).ok(), version);
+    }
+
+    #[test_case(None, "v0.0.0"; "without firebase version")]
+    #[test_case(Some("1.3.0"), "v1.3.0"; "with firebase version")]
+    fn migrate_from_v1(firebase_version: Option<&str>, expected_tag: &str) {
+        let document = json!({
+            "android": {
+                "last_posted_tag_previous_release": { "name": "v1.2.3" },
+                "last_posted_tag": { "name": "v1.3.0" }
+            }
+        });
+
+        let legacy = LegacyValues {
+            most_recent_android_firebase_version: firebase_version.map(String::from),
+        };
+
+        let migrated = migrate(document, &legacy).unwrap();
+
+        assert_eq!(migrated.version, CURRENT_VERSION);
+        assert_eq!(
+            migrated.most_recent_android_firebase_version,
+            Tag::new(expected_tag)
+        );
+        assert_eq!(
+            migrated.platforms["android"].last_posted_tag,
+            Tag::new("v1.3.0")
+        );
+        assert_eq!(migrated.history, []);
+    }
+
+    #[test_case(None; "without libsignal")]
+    #[test_case(Some("v0.60.0"); "with libsignal")]
+    fn migrate_from_v3(libsignal_tag: Option<&str>) {
+        let mut document = json!({
+            "version": 3,
+            "platforms": {},
+            "most_recent_android_firebase_version": { "name": "v1.3.0" },
+            "history": []
+        });
+
+        if let Some(tag) = libsignal_tag {
+            document["platforms"]["libsignal"] = json!({
+                "last_posted_tag_previous_release": { "name": "v0.59.0" },
+                "last_posted_tag": { "name": tag }
+            });
+        }
+
+        let migrated = migrate(document, &LegacyValues::default()).unwrap();
+
+        assert_eq!(migrated.version, CURRENT_VERSION);
+        assert_eq!(
+            migrated
+                .platforms
+                .get("libsignal")
+                .map(|state| state.last_posted_tag.clone()),
+            libsignal_tag.map(Tag::new)
+        );
+    }
+
+    #[test]
+    fn newer_version_is_refused() {
+        let document = json!({
+            "version": CURRENT_VERSION + 1,
+            "platforms": {},
+            "most_recent_android_firebase_version": { "name": "v1.3.0" }
+        });
+
+        assert!(migrate(document, &LegacyValues::default()).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+use crate::utils::BoxFuture;
+
+/// Key-value storage holding JSON text, like the `STATE` KV namespace.
+pub trait Storage {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, anyhow::Result<Option<String>>>;
+    fn put<'a>(&'a self, key: &'a str, value: String) -> BoxFuture<'a, anyhow::Result<()>>;
+}
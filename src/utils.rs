@@ -1,19 +1,24 @@
-use std::time::{Duration, SystemTime};
-
-use anyhow::Context;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    time::{Duration, SystemTime},
+};
+
+use cfg_if::cfg_if;
 use chrono::prelude::*;
-use factorial::Factorial;
 use sha2::{Digest, Sha256};
-use worker::Delay;
 
-use crate::platform::Platform;
+use crate::{config::Config, platform::Platform};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 
-pub fn discourse_topic_url(topic_id: u64) -> String {
-    format!("https://community.signalusers.org/t/{topic_id}")
+pub fn discourse_topic_url(config: &Config, topic_id: u64) -> String {
+    config.discourse_url(&format!("t/{topic_id}"))
 }
 
-pub fn discourse_post_url(topic_id: u64, post_number: u64) -> String {
-    format!("{}/{post_number}", discourse_topic_url(topic_id))
+pub fn discourse_post_url(config: &Config, topic_id: u64, post_number: u64) -> String {
+    format!("{}/{post_number}", discourse_topic_url(config, topic_id))
 }
 
 pub fn sha256_string(input: &str) -> String {
@@ -24,42 +29,89 @@ pub fn sha256_string(input: &str) -> String {
 /// Asynchronously waits for the specified number of milliseconds.
 pub async fn delay(milliseconds: u64) {
     tracing::trace!(milliseconds, "waiting");
-    Delay::from(Duration::from_millis(milliseconds)).await;
+
+    cfg_if! {
+        if #[cfg(target_family = "wasm")] {
+            worker::Delay::from(Duration::from_millis(milliseconds)).await;
+        } else {
+            // Native futures are driven by `block_on`, so blocking the thread is fine.
+            std::thread::sleep(Duration::from_millis(milliseconds));
+        }
+    }
+
     tracing::trace!(milliseconds, "done waiting");
 }
 
 pub fn now() -> SystemTime {
-    SystemTime::UNIX_EPOCH + Duration::from_millis(worker::Date::now().as_millis())
+    cfg_if! {
+        if #[cfg(target_family = "wasm")] {
+            SystemTime::UNIX_EPOCH + Duration::from_millis(worker::Date::now().as_millis())
+        } else {
+            SystemTime::now()
+        }
+    }
 }
 
-pub fn platforms_order(
-    all_platforms: &[Platform],
-    time: NaiveTime,
-) -> anyhow::Result<Vec<Platform>> {
-    if all_platforms.is_empty() {
-        return Ok(vec![]);
-    }
+/// Rotates `all_platforms` by the current minute, so that each of them regularly gets to be checked
+/// first.
+pub fn platforms_order(all_platforms: &[Platform], time: NaiveTime) -> Vec<Platform> {
+    let mut platforms = all_platforms.to_vec();
 
-    let permutation_count = all_platforms.len().factorial();
+    if !platforms.is_empty() {
+        let start = time.minute() as usize % platforms.len();
+        platforms.rotate_left(start);
+    }
 
-    let index: usize = time
-        .minute()
-        .try_into()
-        .context("should be able to convert to usize")?;
+    platforms
+}
 
-    let index = index % permutation_count;
+pub fn escape_html(string: &str) -> String {
+    askama_escape::escape(string, askama_escape::Html).to_string()
+}
 
-    let platforms = permute::permutations_of(all_platforms)
-        .nth(index)
-        .context("there must be enough permutations")?
-        .copied()
-        .collect::<Vec<_>>();
+/// A Markdown list of the first `max_items` of `items`, followed by how many were left out.
+pub fn markdown_list<T: fmt::Display>(items: &[T], max_items: usize) -> String {
+    let mut lines: Vec<_> = items
+        .iter()
+        .take(max_items)
+        .map(|item| format!("- {item}"))
+        .collect();
+
+    let omitted = items.len().saturating_sub(max_items);
+    if omitted != 0 {
+        lines.push(format!("- *… and {omitted} more*"));
+    }
 
-    Ok(platforms)
+    lines.join("\n")
 }
 
-pub fn escape_html(string: &str) -> String {
-    askama_escape::escape(string, askama_escape::Html).to_string()
+/// Drives a future to completion on the current thread.
+#[cfg(any(test, feature = "cli"))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    use std::{
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -67,33 +119,45 @@ mod tests {
     use std::collections::HashSet;
 
     use pretty_assertions::assert_eq;
-    use strum::IntoEnumIterator;
     use test_case::test_case;
 
     use super::*;
+    use crate::platform::builtin::*;
 
-    #[test_case(&Platform::iter().collect::<Vec<Platform>>(), 24; "all")]
-    #[test_case(&[Platform::Android, Platform::Ios, Platform::Desktop, Platform::Server], 24; "four")]
-    #[test_case(&[Platform::Android, Platform::Ios, Platform::Desktop], 6; "three")]
-    #[test_case(&[Platform::Android, Platform::Ios], 2; "two")]
-    #[test_case(&[Platform::Android], 1; "one")]
+    #[test_case(&[Android, Ios, Desktop, Server, Libsignal], 5; "all")]
+    #[test_case(&[Android, Ios, Desktop], 3; "three")]
+    #[test_case(&[Android, Ios], 2; "two")]
+    #[test_case(&[Android], 1; "one")]
     #[test_case(&[], 1; "none")]
     fn platforms_order_len(all_platforms: &[Platform], result_len: usize) {
         let mut set = HashSet::new();
 
         for minute in 0..=59 {
-            set.insert(
-                platforms_order(
-                    all_platforms,
-                    NaiveTime::from_hms_opt(0, minute, 0).unwrap(),
-                )
-                .unwrap(),
-            );
+            set.insert(platforms_order(
+                all_platforms,
+                NaiveTime::from_hms_opt(0, minute, 0).unwrap(),
+            ));
         }
 
         assert_eq!(set.len(), result_len);
     }
 
+    #[test]
+    fn every_platform_goes_first() {
+        let all_platforms: Vec<_> = Platform::iter(Config::default().leak()).collect();
+
+        let first: HashSet<_> = (0..=59)
+            .map(|minute| {
+                platforms_order(
+                    &all_platforms,
+                    NaiveTime::from_hms_opt(0, minute, 0).unwrap(),
+                )[0]
+            })
+            .collect();
+
+        assert_eq!(first, all_platforms.into_iter().collect());
+    }
+
     #[test_case(
         "Test commit & message <HtmlTag/>'s \"continuation\"",
         "Test commit &amp; message &lt;HtmlTag/&gt;&#x27;s &quot;continuation&quot;";
@@ -102,4 +166,11 @@ mod tests {
     fn escape_html_ok(input: &str, output: &str) {
         assert_eq!(escape_html(input), output);
     }
+
+    #[test_case(usize::MAX, "- a\n- b\n- c"; "all")]
+    #[test_case(2, "- a\n- b\n- *… and 1 more*"; "some")]
+    #[test_case(0, "- *… and 3 more*"; "none")]
+    fn markdown_list(max_items: usize, result: &str) {
+        assert_eq!(super::markdown_list(&["a", "b", "c"], max_items), result);
+    }
 }